@@ -1,4 +1,4 @@
-use crate::{symbol_table::{self, SymbolTable}, tokenizer::{self, Array, DataType, KeyWords, Token, TokenType, Tokenizer}, ErrorPipeline, ErrorType};
+use crate::{symbol_table::{self, SymbolTable}, syntax_tree::{SyntaxKind, SyntaxNode, TreeBuilder}, tokenizer::{self, Array, DataType, KeyWords, Token, TokenType, Tokenizer}, ErrorPipeline, ErrorType};
 use core::fmt;
 use std::{mem::{self, discriminant}, path::Display};
 
@@ -8,19 +8,45 @@ pub enum Node {
     Variable(String),
     DeclareVar(Var),
     Assignment(BinExp),
+    CompoundAssignment(BinExp),
     Literal(LiteralValue),
     BinaryExpression(BinExp),
     Function(FuncDef),
+    // func(x: i32) : bool { .. } used as a value, the name is left empty
+    Closure(FuncDef),
     FunctionCall(FuncCall),
     MethodCall(MethodCall),
+    ClosureCall(ClosureCall),
     Import(Import),
     Return(Option<Box<AstNode<Node>>>),
     Conditional(ConditionalBlock),
     For(ForLoop),
     While(WhileLoop),
+    Loop(InfiniteLoop),
     BooleanNot(NotExp),
+    BitwiseNot(NotExp),
+    Negation(NotExp),
+    Reference(Reference),
+    // *r, the value a reference points to
+    Deref(NotExp),
+    // x?, the value in a Some or Ok, a None or Err is returned from the function
+    Try(NotExp),
+    FieldAccess(FieldAccess),
+    Index(IndexExp),
+    ArrayLiteral(ArrayLiteral),
+    Struct(StructDef),
+    StructLiteral(StructLiteral),
+    Trait(TraitDef),
+    Impl(ImplBlock),
+    Enum(EnumDef),
+    EnumValue(EnumValue),
+    Match(MatchExp),
     Tuple(TupleBody),
+    Destructure(Destructure),
     Range(Range),
+    Cast(CastExp),
+    Break(LoopExit),
+    Continue(LoopExit),
     ParserError(ParserError),
 }
 
@@ -100,6 +126,37 @@ impl Body {
         }
         return false;
     }
+
+    // a `loop` that no break leaves never finishes, like a return it ends the function
+    pub fn contains_endless_loop(&self) -> bool {
+        self.instructions.iter().any(|instruction| match &instruction.node {
+            Node::Body(bd) => bd.contains_endless_loop(),
+            Node::Loop(l) => !l.body.breaks_out(&l.label, false),
+            _ => false
+        })
+    }
+
+    // whether a break inside the body leaves the loop with the given label, an
+    // unlabeled break only leaves it when it is not nested in another loop
+    fn breaks_out(&self,label:&Option<AstNode<String>>,nested:bool) -> bool {
+        self.instructions.iter().any(|instruction| match &instruction.node {
+            Node::Break(exit) => match &exit.label {
+                Some(target) => label.as_ref().is_some_and(|label| label.node == target.node),
+                None => !nested
+            },
+            Node::Body(bd) => bd.breaks_out(label, nested),
+            Node::Conditional(con) => {
+                con.if_block.1.breaks_out(label, nested)
+                || con.elif_block.iter().any(|(_,body)| body.breaks_out(label, nested))
+                || con.else_block.as_ref().is_some_and(|body| body.breaks_out(label, nested))
+            },
+            Node::Match(m) => m.arms.iter().any(|arm| matches!(&arm.body.node,Node::Body(bd) if bd.breaks_out(label, nested))),
+            Node::While(w) => w.body.breaks_out(label, true),
+            Node::For(f) => f.body.breaks_out(label, true),
+            Node::Loop(l) => l.body.breaks_out(label, true),
+            _ => false
+        })
+    }
 }
 
 impl fmt::Debug for AstNode<Node>{
@@ -132,12 +189,30 @@ impl fmt::Debug for AstNode<bool>{
     }
 }
 
+impl fmt::Debug for AstNode<u32>{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f,"{}",self.node)
+    }
+}
+
 impl fmt::Debug for AstNode<Var>{
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f,"{:#?}",self.node)
     }
 }
 
+impl fmt::Debug for AstNode<EnumVariant>{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f,"{:#?}",self.node)
+    }
+}
+
+impl fmt::Debug for AstNode<Pattern>{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f,"{:#?}",self.node)
+    }
+}
+
 
 #[derive(Debug,Clone)]
 pub struct ConditionalBlock{
@@ -150,26 +225,62 @@ pub struct ConditionalBlock{
 pub struct Range{
     pub start:Box<AstNode<Node>>,
     pub end:Box<AstNode<Node>>,
+    // start..=end
+    pub inclusive:bool,
 }
 
 #[derive(Debug,Clone)]
 pub struct ForLoop{
+    pub label:Option<AstNode<String>>,
     pub var:Option<Box<AstNode<Node>>>,
+    // a range, or an array, slice or str whose elements are visited
     pub range:Box<AstNode<Node>>,
+    pub step:Option<Box<AstNode<Node>>>,
+    // for x in (a..b).rev() visits the range from its last element down to a
+    pub reverse:Option<AstNode<String>>,
     pub body:Body,
 }
 
 #[derive(Debug,Clone)]
 pub struct WhileLoop{
+    pub label:Option<AstNode<String>>,
     pub condition:Box<AstNode<Node>>,
     pub body:Body,
 }
 
+// only left through break, a `loop` used as a value gives the value of its break
+#[derive(Debug,Clone)]
+pub struct InfiniteLoop{
+    pub label:Option<AstNode<String>>,
+    pub body:Body,
+}
+
+// break or continue, without a label it applies to the innermost loop
+#[derive(Debug,Clone)]
+pub struct LoopExit{
+    pub label:Option<AstNode<String>>,
+    pub value:Option<Box<AstNode<Node>>>,
+}
+
+#[derive(Debug,Clone)]
+pub enum ArrayLiteral {
+    Elements(Vec<AstNode<Node>>),
+    // [value; count]
+    Repeat(Box<AstNode<Node>>,AstNode<u32>),
+}
+
 #[derive(Debug,Clone)]
 pub struct TupleBody {
     pub members: Vec<AstNode<Node>>,
 }
 
+#[derive(Debug,Clone)]
+pub struct Destructure {
+    pub names: Vec<AstNode<String>>,
+    pub var_type: Option<AstNode<DataType>>,
+    pub value: Box<AstNode<Node>>,
+}
+
 #[derive(Debug,Clone)]
 pub struct BinExp {
     pub left: Box<AstNode<Node>>,
@@ -184,19 +295,140 @@ pub struct NotExp {
     pub exp: Box<AstNode<Node>>
 }
 
+// &x or &mut x
+#[derive(Debug,Clone)]
+pub struct Reference {
+    pub target: Box<AstNode<Node>>,
+    pub mutable: bool,
+}
+
+#[derive(Debug,Clone)]
+pub struct FieldAccess {
+    pub target: Box<AstNode<Node>>,
+    pub field: AstNode<String>,
+}
+
+// the methods of a trait are signatures, every type implementing it defines them
+#[derive(Debug,Clone)]
+pub struct TraitDef {
+    pub trait_name: AstNode<String>,
+    pub methods: Vec<AstNode<Node>>,
+    pub public: bool,
+}
+
+// impl Point { .. } or impl Shape for Point { .. }
+#[derive(Debug,Clone)]
+pub struct ImplBlock {
+    pub trait_name: Option<AstNode<String>>,
+    pub target: AstNode<DataType>,
+    pub methods: Vec<AstNode<Node>>,
+}
+
+impl ImplBlock {
+    // a method is a function named after the type it belongs to
+    pub fn method_name(target:&DataType,method:&str) -> String {
+        format!("{}::{}",target.to_string(),method)
+    }
+}
+
+#[derive(Debug,Clone)]
+pub struct StructDef {
+    pub struct_name: AstNode<String>,
+    pub fields: Vec<AstNode<Var>>,
+    pub public: bool,
+}
+
+#[derive(Debug,Clone)]
+pub struct StructLiteral {
+    pub struct_name: AstNode<String>,
+    pub fields: Vec<(AstNode<String>,AstNode<Node>)>,
+}
+
+#[derive(Debug,Clone)]
+pub struct EnumDef {
+    pub enum_name: AstNode<String>,
+    pub variants: Vec<AstNode<EnumVariant>>,
+    pub public: bool,
+}
+
+#[derive(Debug,Clone)]
+pub struct EnumVariant {
+    pub name: AstNode<String>,
+    pub payload: Vec<AstNode<DataType>>,
+}
+
+#[derive(Debug,Clone)]
+pub struct EnumValue {
+    pub enum_name: AstNode<String>,
+    pub variant: AstNode<String>,
+    pub arguments: Vec<AstNode<Node>>,
+}
+
+#[derive(Debug,Clone)]
+pub struct MatchExp {
+    pub scrutinee: Box<AstNode<Node>>,
+    pub arms: Vec<MatchArm>,
+}
+
+#[derive(Debug,Clone)]
+pub struct MatchArm {
+    pub pattern: AstNode<Pattern>,
+    // Node::Body for a block arm, any other node is the value of the arm
+    pub body: Box<AstNode<Node>>,
+}
+
+#[derive(Debug,Clone)]
+pub enum Pattern {
+    Wildcard,
+    Literal(LiteralValue),
+    // start..end, start..=end when the flag is set
+    Range(LiteralValue,LiteralValue,bool),
+    Variant(VariantPattern),
+}
+
+#[derive(Debug,Clone)]
+pub struct VariantPattern {
+    pub enum_name: AstNode<String>,
+    pub variant: AstNode<String>,
+    pub bindings: Vec<AstNode<String>>,
+}
+
+// value as data_type
+#[derive(Debug,Clone)]
+pub struct CastExp {
+    pub value: Box<AstNode<Node>>,
+    pub target: AstNode<DataType>,
+}
+
+#[derive(Debug,Clone)]
+pub struct IndexExp {
+    pub target: Box<AstNode<Node>>,
+    pub index: Box<AstNode<Node>>,
+}
+
 #[derive(Debug,Clone)]
 pub struct Var {
     pub constant:Option<AstNode<bool>>,
     pub name: AstNode<String>,
-    pub var_type: AstNode<DataType>,
+    // left out by a let that infers the type from its initializer
+    pub var_type: Option<AstNode<DataType>>,
+    // only set for a top-level const
+    pub public: bool,
 }
 
 #[derive(Debug,Clone)]
 pub struct FuncDef {
     pub function_name: AstNode<String>,
+    // func max<T>, every call instantiates the function for the types it infers
+    pub type_parameters: Vec<AstNode<String>>,
     pub body:Body,
     pub return_type: AstNode<DataType>,
     pub parameters: Vec<AstNode<Var>>,
+    pub public: bool,
+    // declared without a body, the runtime provides the implementation
+    pub runtime: bool,
+    // extern func, a C function linked into the program
+    pub external: bool,
 }
 
 #[derive(Debug,Clone)]
@@ -205,6 +437,13 @@ pub struct FuncCall {
     pub arguments: Vec<AstNode<Node>>,
 }
 
+// a call of a function value that is not a plain name, make_adder(1)(2)
+#[derive(Debug,Clone)]
+pub struct ClosureCall {
+    pub callee:Box<AstNode<Node>>,
+    pub arguments: Vec<AstNode<Node>>,
+}
+
 #[derive(Debug,Clone)]
 pub struct MethodCall {
     pub caller:Option<Box<AstNode<Node>>>,
@@ -219,21 +458,129 @@ pub struct Import {
     pub alias: Option<AstNode<String>>,
 }
 
+// the arguments of a call and the ')' closing them
+type Arguments = (Vec<AstNode<Node>>,AstNode<TokenType>);
+
 pub struct ArkParser<'source,'error_pipe,'tokenizer> {
     tokenizer: &'tokenizer mut Tokenizer<'source,'error_pipe>,
     look_ahead: Token,
     err_pipe:&'error_pipe ErrorPipeline,
+    tree: Option<TreeBuilder>,
+    panic_mode: bool,
+    statement_errors: u32,
+    previous_token: TokenType,
+    previous_token_end: (u32,u32),
+    // set while parsing the condition of if, while and for, where `name {` starts the body
+    no_struct_literal: bool,
+    // only the standard library may declare functions without a body
+    runtime_declarations: bool,
+    // type of self and Self inside a trait or impl block
+    self_type: Option<DataType>,
+    // the '>>' that closed a type argument list nested in another one, it closes both
+    closed_type_arguments: Option<AstNode<TokenType>>,
+    // the end of the file was reached inside a block, the blocks around it are
+    // left open by the same missing brace
+    unclosed_block: bool,
+}
+
+// what follows the parameters and return type of a function
+#[derive(Clone,Copy,PartialEq)]
+enum FunctionKind {
+    Plain,
+    // extern func, a ';' instead of the body
+    Extern,
+    // a method of a trait, a ';' instead of the body
+    Signature,
+    // an anonymous function used as a value, no name
+    Closure,
 }
 
+// after this many errors inside one statement the rest are dropped as they are
+// almost always caused by the first one
+const MAX_STATEMENT_ERRORS: u32 = 3;
+
 impl<'source,'error_pipe,'tokenizer> ArkParser<'source,'error_pipe,'tokenizer> {
     pub fn new(tokenizer:&'tokenizer mut Tokenizer<'source,'error_pipe>,error_pipe: &'error_pipe ErrorPipeline) -> ArkParser<'source,'error_pipe,'tokenizer> {
         return ArkParser {
             look_ahead: tokenizer.get_next_token(),
             tokenizer,
             err_pipe:error_pipe,
+            tree:None,
+            panic_mode:false,
+            statement_errors:0,
+            previous_token:TokenType::EOF,
+            previous_token_end:(1,1),
+            no_struct_literal:false,
+            runtime_declarations:false,
+            self_type:None,
+            closed_type_arguments:None,
+            unclosed_block:false,
         };
     }
 
+    pub fn allow_runtime_declarations(&mut self) {
+        self.runtime_declarations = true;
+    }
+
+    fn advance(&mut self) {
+        let next = self.tokenizer.get_next_token();
+        let consumed = mem::replace(&mut self.look_ahead, next);
+        self.previous_token = consumed.token.clone();
+        self.previous_token_end = (consumed.pos.0,consumed.pos.1 + consumed.length);
+        if let Some(tree) = &mut self.tree {
+            tree.token(consumed);
+        }
+    }
+
+    fn checkpoint(&self) -> usize {
+        match &self.tree {
+            Some(tree) => tree.checkpoint(),
+            None => 0
+        }
+    }
+
+    fn tree_depth(&self) -> usize {
+        match &self.tree {
+            Some(tree) => tree.depth(),
+            None => 0
+        }
+    }
+
+    fn start_node(&mut self,kind:SyntaxKind) -> usize {
+        let depth = self.tree_depth();
+        if let Some(tree) = &mut self.tree {
+            tree.start_node(kind);
+        }
+        depth
+    }
+
+    fn start_node_at(&mut self,checkpoint:usize,kind:SyntaxKind) -> usize {
+        let depth = self.tree_depth();
+        if let Some(tree) = &mut self.tree {
+            tree.start_node_at(checkpoint,kind);
+        }
+        depth
+    }
+
+    // the tokens skipped since the checkpoint while recovering from an error are
+    // kept in the tree under an Error node
+    fn wrap_skipped(&mut self,checkpoint:usize) {
+        if self.checkpoint() > checkpoint {
+            let marker = self.start_node_at(checkpoint, SyntaxKind::Error);
+            self.finish_node(marker);
+        }
+    }
+
+    // closes every node opened since the marker, nodes left open by an early
+    // error return are closed together with their parent
+    fn finish_node(&mut self,marker:usize) {
+        if let Some(tree) = &mut self.tree {
+            while tree.depth() > marker.max(1) {
+                tree.finish_node();
+            }
+        }
+    }
+
     fn expected(&mut self, expected: &TokenType) -> bool {
         match expected{
             TokenType::Keyword(keyword) =>{
@@ -268,7 +615,7 @@ impl<'source,'error_pipe,'tokenizer> ArkParser<'source,'error_pipe,'tokenizer> {
                     match &self.look_ahead.token{
                         TokenType::Keyword(look_ahead_key) => {
                             if mem::discriminant(look_ahead_key) == mem::discriminant(keyword) {
-                                self.look_ahead = self.tokenizer.get_next_token();
+                                self.advance();
                                 return Ok(AstNode::new(node, pos, length));
                             }
                             // else{
@@ -290,7 +637,7 @@ impl<'source,'error_pipe,'tokenizer> ArkParser<'source,'error_pipe,'tokenizer> {
             _ => {
                 let node = self.look_ahead.token.clone();
                 if mem::discriminant(&node) == mem::discriminant(expected) {
-                    self.look_ahead = self.tokenizer.get_next_token();
+                    self.advance();
                     return Ok(AstNode::new(node, pos, length));
                 }
                 // else{
@@ -303,18 +650,21 @@ impl<'source,'error_pipe,'tokenizer> ArkParser<'source,'error_pipe,'tokenizer> {
         //println!("expected: {:#?} founded: {:#?}",expected,self.look_ahead);
         //self.look_ahead = self.tokenizer.get_next_token();
         //self.skip_until_delim();
+        // a closing brace missing at the end of the file was already reported for
+        // the innermost block that needed one
+        if *expected == TokenType::RightBrace && self.expected(&TokenType::EOF) {
+            let already_reported = mem::replace(&mut self.unclosed_block, true);
+            if already_reported {
+                return Err(
+                    AstNode { node: Node::ParserError(ParserError::new(ErrorType::SyntaxError, error_message, pos)), pos, length }
+                );
+            }
+        }
         return Err(
             AstNode { node: Node::ParserError(self.raise_error(error_message)), pos, length }
         );
         
     }
-    fn skip_until(&mut self,delimiter:&TokenType){
-        
-        while !self.expected(delimiter) && !self.expected(&TokenType::EOF) {
-            //println!("current: {:#?}",self.look_ahead);
-            self.look_ahead = self.tokenizer.get_next_token();
-        }
-    }
     fn skip_block(&mut self){
         let mut s: Vec<bool> = vec![];
         
@@ -324,12 +674,7 @@ impl<'source,'error_pipe,'tokenizer> ArkParser<'source,'error_pipe,'tokenizer> {
             while !s.is_empty(){
                 //println!("skip: {:#?}",self.look_ahead);
                 if self.expected(&TokenType::EOF){
-                    self.err_pipe.raise_error(
-                        ErrorType::SyntaxError,
-                        "unclosed brace",
-                        self.look_ahead.pos,
-                        self.look_ahead.length
-                    );
+                    self.report_unclosed_brace(open.pos);
                     return;
                 }
                 else if self.expected(&TokenType::LeftBrace){
@@ -338,7 +683,7 @@ impl<'source,'error_pipe,'tokenizer> ArkParser<'source,'error_pipe,'tokenizer> {
                 else if self.expected(&TokenType::RightBrace){
                     s.pop();
                 }
-                self.look_ahead = self.tokenizer.get_next_token();
+                self.advance();
             }
             //self.eat(&TokenType::RightBrace, "expected open brace").unwrap();
         }
@@ -346,7 +691,7 @@ impl<'source,'error_pipe,'tokenizer> ArkParser<'source,'error_pipe,'tokenizer> {
     fn raise_error(&mut self,error_message:&str) -> ParserError {
         
         let error_pos = self.look_ahead.pos;
-        self.err_pipe.raise_error(crate::ErrorType::SyntaxError, error_message, error_pos, 1);
+        self.report_error_at(error_message, error_pos, 1);
 
         let err = ParserError::new(
             ErrorType::SyntaxError,
@@ -358,20 +703,300 @@ impl<'source,'error_pipe,'tokenizer> ArkParser<'source,'error_pipe,'tokenizer> {
         err
     }
 
+    // errors raised while already in panic mode are cascades of the first one
+    fn report_error_at(&mut self,error_message:&str,error_pos:(u32,u32),length:u32) {
+        if !self.panic_mode && self.statement_errors < MAX_STATEMENT_ERRORS {
+            self.err_pipe.raise_error(crate::ErrorType::SyntaxError, error_message, error_pos, length);
+        }
+        self.statement_errors += 1;
+        self.panic_mode = true;
+    }
+
+    // only the innermost block left open at the end of the file is reported
+    fn report_unclosed_brace(&mut self,open_pos:(u32,u32)) {
+        if !self.unclosed_block {
+            self.report_error_at("unclosed brace", open_pos, 1);
+            self.unclosed_block = true;
+        }
+    }
+
+    // a statement that only lacks its semicolon is kept, the error points right
+    // after the last token of the statement instead of at the next line
+    fn expect_semicolon(&mut self,statement:AstNode<Node>) -> AstNode<Node> {
+        if self.expected(&TokenType::SemiColon) {
+            self.advance();
+            return statement;
+        }
+        if let Node::ParserError(_) = statement.node {
+            return statement;
+        }
+        let error_pos = self.previous_token_end;
+        self.report_error_at("missing semicolon", error_pos, 1);
+        self.panic_mode = false;
+        statement
+    }
+
+    fn is_statement_start(&mut self) -> bool {
+        matches!(
+            self.look_ahead.token,
+            TokenType::Keyword(
+                KeyWords::FUNC
+                | KeyWords::STRUCT
+                | KeyWords::ENUM
+                | KeyWords::MATCH
+                | KeyWords::IMPORT
+                | KeyWords::CONST
+                | KeyWords::PUB
+                | KeyWords::EXTERN
+                | KeyWords::TRAIT
+                | KeyWords::IMPL
+                | KeyWords::LET
+                | KeyWords::RETURN
+                | KeyWords::FOR
+                | KeyWords::WHILE
+                | KeyWords::IF
+                | KeyWords::LOOP
+                | KeyWords::BREAK
+                | KeyWords::CONTINUE
+            )
+        ) || matches!(self.look_ahead.token, TokenType::Label(_))
+    }
+
+    // skips tokens until a point where parsing can resume: after a semicolon, before
+    // a closing brace or before a keyword that starts a new statement
+    fn synchronize(&mut self) {
+        self.panic_mode = false;
+        if self.previous_token == TokenType::RightBrace && self.expected(&TokenType::SemiColon) {
+            self.advance();
+            return;
+        }
+        if self.previous_token == TokenType::SemiColon || self.previous_token == TokenType::RightBrace {
+            return;
+        }
+        let checkpoint = self.checkpoint();
+        loop {
+            if self.expected(&TokenType::EOF) || self.expected(&TokenType::RightBrace) || self.is_statement_start() {
+                break;
+            }
+            if self.expected(&TokenType::SemiColon) {
+                self.wrap_skipped(checkpoint);
+                self.advance();
+                return;
+            }
+            if self.expected(&TokenType::LeftBrace) {
+                self.skip_block();
+                self.panic_mode = false;
+                break;
+            }
+            self.advance();
+        }
+        self.wrap_skipped(checkpoint);
+    }
+
+    // used when a function header is broken, the body is still parsed so errors
+    // inside it are reported
+    fn recover_function(&mut self,error:AstNode<Node>,marker:usize) -> AstNode<Node> {
+        let checkpoint = self.checkpoint();
+        while !self.expected(&TokenType::LeftBrace)
+            && !self.expected(&TokenType::EOF)
+            && !self.expected(&TokenType::Keyword(KeyWords::FUNC))
+        {
+            self.advance();
+        }
+        self.wrap_skipped(checkpoint);
+        self.panic_mode = false;
+        if self.expected(&TokenType::LeftBrace) {
+            let outer_errors = mem::replace(&mut self.statement_errors, 0);
+            self.parse_block();
+            self.statement_errors = outer_errors;
+        }
+        self.finish_node(marker);
+        error
+    }
+
+    fn skip_block_after_header(&mut self) {
+        let checkpoint = self.checkpoint();
+        while !self.expected(&TokenType::LeftBrace)
+            && !self.expected(&TokenType::RightBrace)
+            && !self.expected(&TokenType::EOF)
+        {
+            self.advance();
+        }
+        self.skip_block();
+        self.wrap_skipped(checkpoint);
+        self.panic_mode = false;
+    }
+
+    // a type starts with a type keyword, a name, '(' of a tuple, 'func' or '&' of a reference
+    fn at_data_type(&mut self) -> bool {
+        self.expected(&TokenType::DataType(DataType::Void))
+            || self.expected(&TokenType::BitwiseAnd)
+            || self.expected(&TokenType::Identifier(String::new()))
+            || self.expected(&TokenType::LeftParen)
+            || self.expected(&TokenType::Keyword(KeyWords::FUNC))
+    }
+
     fn parse_data_type(&mut self) -> Result<AstNode<DataType>,AstNode<Node>> {
-        let data_type_t = match self.eat(&TokenType::DataType(DataType::Void), "Expected a type"){
-            Ok(dt) => {dt},
-            Err(e) => return Err(e)
-        };
-        let mut data_type = if let TokenType::DataType(t) = data_type_t.node {
-            t
+        let marker = self.start_node(SyntaxKind::DataType);
+        // &T and &mut T refer to a T, the array suffix belongs to T
+        if self.expected(&TokenType::BitwiseAnd) {
+            let ampersand = self.eat(&TokenType::BitwiseAnd, "")?;
+            let mutable = self.expected(&TokenType::Keyword(KeyWords::MUT)) && self.eat(&TokenType::Keyword(KeyWords::MUT), "").is_ok();
+            let target = self.parse_data_type()?;
+            let end = AstNode::new(TokenType::DataType(target.node.clone()),target.pos,target.length);
+            self.finish_node(marker);
+            return Ok(AstNode::new(DataType::Reference(Box::new(target.node),mutable),ampersand.pos,Self::span_length(ampersand.pos, &end)));
         }
-        else{
-            DataType::Void
+        // func(i32, bool) : bool is the type of a function value, without ':' it returns nothing
+        if self.expected(&TokenType::Keyword(KeyWords::FUNC)) {
+            let func_keyword = self.eat(&TokenType::Keyword(KeyWords::FUNC), "")?;
+            self.eat(&TokenType::LeftParen, "expected '(' after 'func' in a function type")?;
+            let mut parameters = vec![];
+            while !self.expected(&TokenType::RightParen) && !self.expected(&TokenType::EOF) {
+                match self.parse_data_type() {
+                    Ok(t) => parameters.push(t.node),
+                    Err(e) => return Err(e)
+                }
+                if self.expected(&TokenType::Comma) {
+                    self.advance();
+                }
+                else {
+                    break;
+                }
+            }
+            let mut end = self.eat(&TokenType::RightParen, "expected ')' after the parameter types")?;
+            let mut return_type = DataType::Void;
+            // in a return type the ':' of the return type of the function type is ambiguous
+            // with nothing, so `: func(i32) : bool {` always reads bool as part of the type
+            if self.expected(&TokenType::Colon) {
+                self.advance();
+                let returned = self.parse_data_type()?;
+                end = AstNode::new(TokenType::DataType(returned.node.clone()),returned.pos,returned.length);
+                return_type = returned.node;
+            }
+            let function = AstNode::new(TokenType::Keyword(KeyWords::FUNC),func_keyword.pos,Self::span_length(func_keyword.pos, &end));
+            return self.parse_array_suffix(DataType::Function(parameters,Box::new(return_type)), function, marker);
+        }
+        // (i32, bool) is a tuple type, () has no elements
+        if self.expected(&TokenType::LeftParen) {
+            let open = self.eat(&TokenType::LeftParen, "")?;
+            let mut elements = vec![];
+            while !self.expected(&TokenType::RightParen) && !self.expected(&TokenType::EOF) {
+                match self.parse_data_type() {
+                    Ok(t) => elements.push(t.node),
+                    Err(e) => return Err(e)
+                }
+                if self.expected(&TokenType::Comma) {
+                    self.advance();
+                }
+                else {
+                    break;
+                }
+            }
+            let close = self.eat(&TokenType::RightParen, "expected ')' after the tuple type")?;
+            let tuple = AstNode::new(TokenType::LeftParen,open.pos,Self::span_length(open.pos, &close));
+            return self.parse_array_suffix(DataType::Tuple(elements), tuple, marker);
+        }
+        let data_type_t = if self.expected(&TokenType::Identifier(String::new())) {
+            self.eat(&TokenType::Identifier(String::new()), "Expected a type")?
+        }
+        else {
+            self.eat(&TokenType::DataType(DataType::Void), "Expected a type")?
+        };
+        let mut data_type = match &data_type_t.node {
+            TokenType::DataType(t) => t.clone(),
+            TokenType::Identifier(name) => DataType::Struct(name.clone()),
+            _ => DataType::Void
         };
+        if let (DataType::Struct(name),Some(self_type)) = (&data_type,&self.self_type) {
+            if name == "Self" {
+                data_type = self_type.clone();
+            }
+        }
+        // Option<T> and Result<T, E> are the only types taking type arguments
+        if let (DataType::Struct(name),true) = (&data_type,self.expected(&TokenType::Less)) {
+            let expected_count = match name.as_str() {
+                "Option" => 1,
+                "Result" => 2,
+                _ => 0
+            };
+            if expected_count > 0 {
+                let name = name.clone();
+                // a '>>' closing a nested list as well is eaten by the nested list and
+                // closes this one right after it
+                let arguments_marker = self.start_node(SyntaxKind::TypeArgumentList);
+                self.advance();
+                let mut arguments = vec![];
+                let mut close = None;
+                loop {
+                    arguments.push(self.parse_data_type()?.node);
+                    if let Some(closed) = self.closed_type_arguments.take() {
+                        close = Some(closed);
+                        break;
+                    }
+                    if !self.expected(&TokenType::Comma) {
+                        break;
+                    }
+                    self.advance();
+                }
+                let close = match close {
+                    Some(close) => close,
+                    None if self.expected(&TokenType::ShiftRight) => {
+                        let close = self.eat(&TokenType::ShiftRight, "")?;
+                        self.closed_type_arguments = Some(close.clone());
+                        close
+                    },
+                    None => self.eat(&TokenType::More, "expected '>' after the type arguments")?
+                };
+                self.finish_node(arguments_marker);
+                let generic = AstNode::new(data_type_t.node.clone(),data_type_t.pos,Self::span_length(data_type_t.pos, &close));
+                if arguments.len() != expected_count {
+                    let message = format!("'{}' takes {} type argument(s) found {}",name,expected_count,arguments.len());
+                    self.report_error_at(message.as_str(), generic.pos, generic.length);
+                    return Err(AstNode::new(Node::ParserError(ParserError::new(ErrorType::SyntaxError, message.as_str(), generic.pos)),generic.pos,generic.length));
+                }
+                let mut arguments = arguments.into_iter();
+                data_type = match (arguments.next(),arguments.next()) {
+                    (Some(value),Some(error)) => DataType::Result(Box::new(value),Box::new(error)),
+                    (Some(value),None) => DataType::Option(Box::new(value)),
+                    _ => DataType::Void
+                };
+                return self.parse_array_suffix(data_type, generic, marker);
+            }
+        }
+        // m::Point names a type exported by an imported module
+        if let (DataType::Struct(module),true) = (&data_type,self.expected(&TokenType::ScopeResolution)) {
+            self.advance();
+            let type_name = self.eat(&TokenType::Identifier(String::new()), "expected a type name after '::'")?;
+            if let TokenType::Identifier(name) = &type_name.node {
+                data_type = DataType::Struct(format!("{}::{}",module,name));
+            }
+            let qualified = AstNode::new(data_type_t.node.clone(),data_type_t.pos,Self::span_length(data_type_t.pos, &type_name));
+            return self.parse_array_suffix(data_type, qualified, marker);
+        }
+        self.parse_array_suffix(data_type, data_type_t, marker)
+    }
+
+    // the [] [..] and [n] following the element type
+    fn parse_array_suffix(&mut self,element_type:DataType,data_type_t:AstNode<TokenType>,marker:usize) -> Result<AstNode<DataType>,AstNode<Node>> {
+        let mut data_type = element_type;
         //let mut array:Array = vec![];
         while self.expected(&TokenType::LeftBracket){
-            self.eat(&TokenType::LeftBracket, "");
+            self.advance();
+            if self.expected(&TokenType::RightBracket) {
+                self.advance();
+                data_type = DataType::Slice(Box::new(data_type));
+                continue;
+            }
+            if self.expected(&TokenType::Range) {
+                self.advance();
+                match self.eat(&TokenType::RightBracket, "expected closing bracket"){
+                    Ok(_) => (),
+                    Err(e) => return Err(e)
+                };
+                data_type = DataType::Range(Box::new(data_type));
+                continue;
+            }
             let size = match self.eat(&TokenType::IntLiteral(0), "expected fixed array size"){
                 Ok(node) => if let TokenType::IntLiteral(i) = node.node{
                     i
@@ -390,6 +1015,7 @@ impl<'source,'error_pipe,'tokenizer> ArkParser<'source,'error_pipe,'tokenizer> {
                 data_type: Box::from(data_type),
             });
         }
+        self.finish_node(marker);
         Ok(
             AstNode {
                 node: data_type,
@@ -400,21 +1026,61 @@ impl<'source,'error_pipe,'tokenizer> ArkParser<'source,'error_pipe,'tokenizer> {
     }
     fn parse_block(&mut self) -> Body {
         
+        let marker = self.start_node(SyntaxKind::Block);
         let mut scope_body = Body {
             instructions: vec![],
         };
-        let l_brace = self.eat(&TokenType::LeftBrace, "expected a block").unwrap();
+        let l_brace = match self.eat(&TokenType::LeftBrace, "expected a block") {
+            Ok(t) => t,
+            Err(_) => {
+                self.finish_node(marker);
+                return scope_body;
+            }
+        };
         while !self.expected(&TokenType::RightBrace) {
+            if self.expected(&TokenType::EOF) {
+                self.report_unclosed_brace(l_brace.pos);
+                // the body did not parse, so nothing is checked about how it ends
+                scope_body.instructions.push(AstNode::new(Node::ParserError(ParserError::new(ErrorType::SyntaxError, "unclosed brace", l_brace.pos)),l_brace.pos,1));
+                break;
+            }
+            let node = self.parse_statement(false);
+            scope_body.instructions.push(node);
+        }
+        
+        if self.expected(&TokenType::RightBrace) {
+            let _ = self.eat(&TokenType::RightBrace, "unclosed brace");
+        }
+        self.finish_node(marker);
+        return scope_body;
+    }
+
+    fn parse_body(&mut self,stop_token:TokenType) -> Body {
+        let mut scope_body = Body {
+            instructions: vec![],
+        };
+        
+        while !self.expected(&stop_token) && !self.expected(&TokenType::EOF) {
+            let node = self.parse_statement(true);
+            scope_body.instructions.push(node);
+        }
+        scope_body
+    }
+
+    fn parse_statement(&mut self,top_level:bool) -> AstNode<Node> {
+        let outer_errors = mem::replace(&mut self.statement_errors, 0);
+        let start = self.look_ahead.pos;
+        let node:AstNode<Node> = match self.look_ahead.token {
             
-            let node:AstNode<Node> = match self.look_ahead.token {
+            TokenType::Keyword(keyword) => {
                 
-                TokenType::Keyword(keyword) => {
-                    
-                    match keyword {
-                        KeyWords::FUNC => {
+                match keyword {
+                    KeyWords::FUNC => {
+                        if !top_level {
                             let er = self.raise_error(
                                 "only top-level function declaration is allowed",
                             );
+                            self.skip_block_after_header();
                             AstNode::new(
                                 Node::ParserError(
                                     er.clone()
@@ -422,70 +1088,19 @@ impl<'source,'error_pipe,'tokenizer> ArkParser<'source,'error_pipe,'tokenizer> {
                                 er.pos,
                                 0
                             )
-                            // let func = self.parse_function();
-                            // println!("{:#?}",&func);
-                            // func
-                        },
-                        KeyWords::IMPORT => {
-                            
-                            let mut res = self.parse_import();
-                            match self.eat(&TokenType::SemiColon,"missing semicolon") {
-                                Ok(_)=>(),
-                                Err(e) => {
-                                    res = e;
-                                }
-                            };
-                            res
-
-                        },
-                        KeyWords::AS => AstNode {
-                            node: Node::ParserError(
-                                self.raise_error(
-                                    "expected token 'import' before 'sources' token"
-                                )
-                            ),
-                            length:self.look_ahead.length,
-                            pos:self.look_ahead.pos
-                        },
-                        KeyWords::CONST => {
-                            let mut res = self.parse_iden_init(true);
-                            match self.eat(&TokenType::SemiColon,"missing semicolon") {
-                                Ok(_)=>(),
-                                Err(e) => {
-                                    res = e;
-                                }
-                            };
-                            res
-                        },
-                        KeyWords::LET => {
-                            let mut res = self.parse_iden_init(false);
-                            match self.eat(&TokenType::SemiColon,"missing semicolon") {
-                                Ok(_)=>(),
-                                Err(e) => {
-                                    res = e;
-                                }
-                            };
-                            res
-                        },
-                        KeyWords::RETURN => {
-                            let mut res = self.parse_return();
-                            match self.eat(&TokenType::SemiColon,"missing semicolon") {
-                                Ok(_)=>(),
-                                Err(e) => {
-                                    res = e;
-                                }
-                            };
-                            res
-                        },
-                        KeyWords::FOR => {
-                            self.parse_for()
-                        },
-                        KeyWords::WHILE => self.parse_while(),
-                        KeyWords::IF => self.parse_condition(),
-                        KeyWords::ELSEIF => {
+                        }
+                        else {
+                            let func = self.parse_function(FunctionKind::Plain);
+                            //println!("{:#?}",&func);
+                            func
+                        }
+                    },
+                    KeyWords::STRUCT => {
+                        if !top_level {
                             let er = self.raise_error(
-                                "expected token 'if' before 'else if' token",
+                                "only top-level struct declaration is allowed",
                             );
+                            self.skip_block_after_header();
                             AstNode::new(
                                 Node::ParserError(
                                     er.clone()
@@ -493,11 +1108,17 @@ impl<'source,'error_pipe,'tokenizer> ArkParser<'source,'error_pipe,'tokenizer> {
                                 er.pos,
                                 0
                             )
-                        },
-                        KeyWords::ELSE => {
+                        }
+                        else {
+                            self.parse_struct()
+                        }
+                    },
+                    KeyWords::ENUM => {
+                        if !top_level {
                             let er = self.raise_error(
-                                "expected token 'if' before 'else' token",
+                                "only top-level enum declaration is allowed",
                             );
+                            self.skip_block_after_header();
                             AstNode::new(
                                 Node::ParserError(
                                     er.clone()
@@ -505,11 +1126,46 @@ impl<'source,'error_pipe,'tokenizer> ArkParser<'source,'error_pipe,'tokenizer> {
                                 er.pos,
                                 0
                             )
-                        },
-                        KeyWords::IN => {
+                        }
+                        else {
+                            self.parse_enum()
+                        }
+                    },
+                    KeyWords::MATCH => {
+                        // a match used as a statement does not need a semicolon
+                        let res = self.parse_match();
+                        if self.expected(&TokenType::SemiColon) {
+                            self.advance();
+                        }
+                        res
+                    },
+                    KeyWords::IMPORT => {
+                        
+                        let res = self.parse_import();
+                        self.expect_semicolon(res)
+
+                    },
+                    KeyWords::AS => AstNode {
+                        node: Node::ParserError(
+                            self.raise_error(
+                                "expected token 'import' before 'sources' token"
+                            )
+                        ),
+                        length:self.look_ahead.length,
+                        pos:self.look_ahead.pos
+                    },
+                    KeyWords::CONST => {
+                        let res = self.parse_iden_init(true);
+                        self.expect_semicolon(res)
+                    },
+                    KeyWords::PUB => self.parse_pub(top_level),
+                    KeyWords::EXTERN => self.parse_extern(top_level),
+                    KeyWords::TRAIT | KeyWords::IMPL => {
+                        if !top_level {
                             let er = self.raise_error(
-                                "expected identifier or tuple of identifier before 'in' token",
+                                "only top-level trait and impl declarations are allowed",
                             );
+                            self.skip_block_after_header();
                             AstNode::new(
                                 Node::ParserError(
                                     er.clone()
@@ -517,270 +1173,258 @@ impl<'source,'error_pipe,'tokenizer> ArkParser<'source,'error_pipe,'tokenizer> {
                                 er.pos,
                                 0
                             )
-                        },
-                    }
-                },
-                TokenType::Identifier(_) => {
-                    
-                    let mut res = self.parse_iden();
-                    match self.eat(&TokenType::SemiColon,"missing semicolon") {
-                        Ok(_)=>(),
-                        Err(e) => {
-                            res = e;
                         }
-                    };
-                    res
-                },
-                TokenType::MultiplicationOperator => {todo!()},
-                TokenType::And => {
-                    let er = self.raise_error(
-                        "expected identifier or expression before '&&' token",
-                    );
-                    AstNode::new(
-                        Node::ParserError(
-                            er.clone()
-                        ),
-                        er.pos,
-                        0
-                    )
-                },
-                TokenType::Or => {
-                    let er = self.raise_error(
-                        "expected identifier or expression before '||' token",
-                    );
-                    AstNode::new(
-                        Node::ParserError(
-                            er.clone()
-                        ),
-                        er.pos,
-                        0
-                    )
-                },
-                TokenType::RightParen => {
-                    let er = self.raise_error(
-                        "unmatched parenthesis",
-                    );
-                    AstNode::new(
-                        Node::ParserError(
-                            er.clone()
-                        ),
-                        er.pos,
-                        0
-                    )
-                },
-                TokenType::RightBrace => {
-                    let er = self.raise_error(
-                        "unmatched brace",
-                    );
-                    AstNode::new(
-                        Node::ParserError(
-                            er.clone()
-                        ),
-                        er.pos,
-                        0
-                    )
-                },
-                TokenType::EOF => {
-                    //let _er = self.raise_error("unclosed brace");
-                    self.err_pipe.raise_error(crate::ErrorType::SyntaxError, "unclosed brace", l_brace.pos, 1);
-                    break;
-                },
-                _ => {
-                    //println!("{:#?}",self.look_ahead.token);
-                    todo!();
-                },
-            };
-            scope_body.instructions.push(node);
+                        else if keyword == KeyWords::TRAIT {
+                            self.parse_trait()
+                        }
+                        else {
+                            self.parse_impl()
+                        }
+                    },
+                    KeyWords::LET => {
+                        let res = self.parse_iden_init(false);
+                        self.expect_semicolon(res)
+                    },
+                    KeyWords::RETURN => {
+                        let res = self.parse_return();
+                        self.expect_semicolon(res)
+                    },
+                    KeyWords::FOR => {
+                        let checkpoint = self.checkpoint();
+                        self.parse_for(None,checkpoint)
+                    },
+                    KeyWords::WHILE => {
+                        let checkpoint = self.checkpoint();
+                        self.parse_while(None,checkpoint)
+                    },
+                    KeyWords::LOOP => {
+                        // like match, a loop statement does not need a semicolon
+                        let checkpoint = self.checkpoint();
+                        let res = self.parse_loop(None,checkpoint);
+                        if self.expected(&TokenType::SemiColon) {
+                            self.advance();
+                        }
+                        res
+                    },
+                    KeyWords::BREAK | KeyWords::CONTINUE => {
+                        let res = self.parse_loop_exit();
+                        self.expect_semicolon(res)
+                    },
+                    KeyWords::IF => self.parse_condition(),
+                    KeyWords::ELSEIF => {
+                        let er = self.raise_error(
+                            "expected token 'if' before 'else if' token",
+                        );
+                        AstNode::new(
+                            Node::ParserError(
+                                er.clone()
+                            ),
+                            er.pos,
+                            0
+                        )
+                    },
+                    KeyWords::ELSE => {
+                        let er = self.raise_error(
+                            "expected token 'if' before 'else' token",
+                        );
+                        AstNode::new(
+                            Node::ParserError(
+                                er.clone()
+                            ),
+                            er.pos,
+                            0
+                        )
+                    },
+                    KeyWords::IN => {
+                        let er = self.raise_error(
+                            "expected identifier or tuple of identifier before 'in' token",
+                        );
+                        AstNode::new(
+                            Node::ParserError(
+                                er.clone()
+                            ),
+                            er.pos,
+                            0
+                        )
+                    },
+                    KeyWords::MUT => {
+                        let er = self.raise_error(
+                            "expected '&' before 'mut' token",
+                        );
+                        AstNode::new(
+                            Node::ParserError(
+                                er.clone()
+                            ),
+                            er.pos,
+                            0
+                        )
+                    },
+                }
+            },
+            TokenType::Label(_) => {
+                let res = self.parse_labeled_loop(false);
+                if matches!(res.node,Node::Loop(_)) && self.expected(&TokenType::SemiColon) {
+                    self.advance();
+                }
+                res
+            },
+            TokenType::Identifier(_)
+            | TokenType::IntLiteral(_)
+            | TokenType::FloatLiteral(_)
+            | TokenType::StringLiteral(_)
+            | TokenType::CharLiteral(_)
+            | TokenType::BooleanLiteral(_)
+            | TokenType::LeftParen
+            | TokenType::SubtractionOperator
+            | TokenType::Not
+            | TokenType::BitwiseNot
+            | TokenType::BitwiseAnd
+            | TokenType::MultiplicationOperator => {
+                
+                let res = self.parse_iden();
+                self.expect_semicolon(res)
+            },
+            TokenType::And => {
+                let er = self.raise_error(
+                    "expected identifier or expression before '&&' token",
+                );
+                AstNode::new(
+                    Node::ParserError(
+                        er.clone()
+                    ),
+                    er.pos,
+                    0
+                )
+            },
+            TokenType::Or => {
+                let er = self.raise_error(
+                    "expected identifier or expression before '||' token",
+                );
+                AstNode::new(
+                    Node::ParserError(
+                        er.clone()
+                    ),
+                    er.pos,
+                    0
+                )
+            },
+            TokenType::RightParen => {
+                let er = self.raise_error(
+                    "unmatched parenthesis",
+                );
+                AstNode::new(
+                    Node::ParserError(
+                        er.clone()
+                    ),
+                    er.pos,
+                    0
+                )
+            },
+            TokenType::RightBrace => {
+                let er = self.raise_error(
+                    "unmatched brace",
+                );
+                AstNode::new(
+                    Node::ParserError(
+                        er.clone()
+                    ),
+                    er.pos,
+                    0
+                )
+            },
+            _ => {
+                let er = self.raise_error(
+                    "expected statement",
+                );
+                AstNode::new(
+                    Node::ParserError(
+                        er.clone()
+                    ),
+                    er.pos,
+                    0
+                )
+            },
+        };
+        if self.panic_mode {
+            self.synchronize();
         }
-        
-        if self.expected(&TokenType::RightBrace) {
-            let _ = self.eat(&TokenType::RightBrace, "unclosed brace");
+        // a statement that failed without consuming anything would otherwise be parsed forever
+        if self.look_ahead.pos == start && !self.expected(&TokenType::EOF) {
+            self.advance();
         }
-        return scope_body;
+        self.statement_errors = outer_errors;
+        node
     }
 
-    fn parse_body(&mut self,stop_token:TokenType) -> Body {
-        let mut scope_body = Body {
-            instructions: vec![],
+    // pub in front of a function, struct, enum or constant exports it from its module
+    fn parse_pub(&mut self,top_level:bool) -> AstNode<Node> {
+        let pub_keyword = match self.eat(&TokenType::Keyword(KeyWords::PUB),"") {
+            Ok(t)=>t,
+            Err(e) => {return e;}
         };
-        
-        while !self.expected(&stop_token) {
-            
-            let node:AstNode<Node> = match self.look_ahead.token {
-                
-                TokenType::Keyword(keyword) => {
-                    
-                    match keyword {
-                        KeyWords::FUNC => {
-
-                            let func = self.parse_function();
-                            //println!("{:#?}",&func);
-                            func
-                        },
-                        KeyWords::IMPORT => {
-                            
-                            let mut res = self.parse_import();
-                            match self.eat(&TokenType::SemiColon,"missing semicolon") {
-                                Ok(_)=>(),
-                                Err(e) => {
-                                    res = e;
-                                }
-                            };
-                            res
+        if !matches!(self.look_ahead.token,TokenType::Keyword(KeyWords::FUNC | KeyWords::STRUCT | KeyWords::ENUM | KeyWords::CONST | KeyWords::EXTERN | KeyWords::TRAIT)) {
+            let er = self.raise_error("expected 'func', 'struct', 'enum', 'trait', 'const' or 'extern' after 'pub'");
+            return AstNode::new(
+                Node::ParserError(
+                    er.clone()
+                ),
+                er.pos,
+                0
+            );
+        }
+        if !top_level && self.expected(&TokenType::Keyword(KeyWords::CONST)) {
+            self.report_error_at("only top-level items can be pub", pub_keyword.pos, pub_keyword.length);
+            self.panic_mode = false;
+        }
+        let mut item = self.parse_statement(top_level);
+        match &mut item.node {
+            Node::Function(func) => func.public = true,
+            Node::Struct(struct_def) => struct_def.public = true,
+            Node::Enum(enum_def) => enum_def.public = true,
+            Node::Trait(trait_def) => trait_def.public = true,
+            Node::Assignment(assignment) => {
+                if let Node::DeclareVar(var) = &mut assignment.left.node {
+                    var.public = top_level;
+                }
+            },
+            _ => ()
+        }
+        item
+    }
 
-                        },
-                        KeyWords::AS => AstNode {
-                            node: Node::ParserError(
-                                self.raise_error(
-                                    "expected token 'import' before 'sources' token"
-                                )
-                            ),
-                            length:self.look_ahead.length,
-                            pos:self.look_ahead.pos
-                        },
-                        KeyWords::CONST => {
-                            let mut res = self.parse_iden_init(true);
-                            match self.eat(&TokenType::SemiColon,"missing semicolon") {
-                                Ok(_)=>(),
-                                Err(e) => {
-                                    res = e;
-                                }
-                            };
-                            res
-                        },
-                        KeyWords::LET => {
-                            let mut res = self.parse_iden_init(false);
-                            match self.eat(&TokenType::SemiColon,"missing semicolon") {
-                                Ok(_)=>(),
-                                Err(e) => {
-                                    res = e;
-                                }
-                            };
-                            res
-                        },
-                        KeyWords::RETURN => {
-                            let mut res = self.parse_return();
-                            match self.eat(&TokenType::SemiColon,"missing semicolon") {
-                                Ok(_)=>(),
-                                Err(e) => {
-                                    res = e;
-                                }
-                            };
-                            res
-                        },
-                        KeyWords::FOR => {
-                            self.parse_for()
-                        },
-                        KeyWords::WHILE => self.parse_while(),
-                        KeyWords::IF => self.parse_condition(),
-                        KeyWords::ELSEIF => {
-                            let er = self.raise_error(
-                                "expected token 'if' before 'else if' token",
-                            );
-                            AstNode::new(
-                                Node::ParserError(
-                                    er.clone()
-                                ),
-                                er.pos,
-                                0
-                            )
-                        },
-                        KeyWords::ELSE => {
-                            let er = self.raise_error(
-                                "expected token 'if' before 'else' token",
-                            );
-                            AstNode::new(
-                                Node::ParserError(
-                                    er.clone()
-                                ),
-                                er.pos,
-                                0
-                            )
-                        },
-                        KeyWords::IN => {
-                            let er = self.raise_error(
-                                "expected identifier or tuple of identifier before 'in' token",
-                            );
-                            AstNode::new(
-                                Node::ParserError(
-                                    er.clone()
-                                ),
-                                er.pos,
-                                0
-                            )
-                        },
-                    }
-                },
-                TokenType::Identifier(_) => {
-                    
-                    let mut res = self.parse_iden();
-                    match self.eat(&TokenType::SemiColon,"missing semicolon") {
-                        Ok(_)=>(),
-                        Err(e) => {
-                            res = e;
-                        }
-                    };
-                    res
-                },
-                TokenType::MultiplicationOperator => {todo!()},
-                TokenType::And => {
-                    let er = self.raise_error(
-                        "expected identifier or expression before '&&' token",
-                    );
-                    AstNode::new(
-                        Node::ParserError(
-                            er.clone()
-                        ),
-                        er.pos,
-                        0
-                    )
-                },
-                TokenType::Or => {
-                    let er = self.raise_error(
-                        "expected identifier or expression before '||' token",
-                    );
-                    AstNode::new(
-                        Node::ParserError(
-                            er.clone()
-                        ),
-                        er.pos,
-                        0
-                    )
-                },
-                TokenType::RightParen => {
-                    let er = self.raise_error(
-                        "unmatched parenthesis",
-                    );
-                    AstNode::new(
-                        Node::ParserError(
-                            er.clone()
-                        ),
-                        er.pos,
-                        0
-                    )
-                },
-                TokenType::RightBrace => {
-                    let er = self.raise_error(
-                        "unmatched brace",
-                    );
-                    AstNode::new(
-                        Node::ParserError(
-                            er.clone()
-                        ),
-                        er.pos,
-                        0
-                    )
-                },
-                _ => {
-                    //println!("{:#?}",self.look_ahead.token);
-                    todo!();
-                },
-            };
-            scope_body.instructions.push(node);
+    // extern func declares a function of a linked C library, it has no body
+    fn parse_extern(&mut self,top_level:bool) -> AstNode<Node> {
+        let extern_keyword = match self.eat(&TokenType::Keyword(KeyWords::EXTERN),"") {
+            Ok(t)=>t,
+            Err(e) => {return e;}
+        };
+        if !self.expected(&TokenType::Keyword(KeyWords::FUNC)) {
+            let er = self.raise_error("expected 'func' after 'extern'");
+            return AstNode::new(
+                Node::ParserError(
+                    er.clone()
+                ),
+                er.pos,
+                0
+            );
         }
-        return scope_body;
+        if !top_level {
+            self.report_error_at("only top-level function declaration is allowed", extern_keyword.pos, extern_keyword.length);
+            // past func, otherwise synchronizing stops right at it
+            self.advance();
+            return AstNode::new(
+                Node::ParserError(
+                    ParserError::new(ErrorType::SyntaxError, "only top-level function declaration is allowed", extern_keyword.pos)
+                ),
+                extern_keyword.pos,
+                0
+            );
+        }
+        self.parse_function(FunctionKind::Extern)
     }
+
     fn parse_import(&mut self) -> AstNode<Node> {
+        let marker = self.start_node(SyntaxKind::Import);
         let import_keyword = match self.eat(&TokenType::Keyword(KeyWords::IMPORT),"") {
             Ok(t)=>t,
             Err(e) => {return e;}
@@ -809,6 +1453,7 @@ impl<'source,'error_pipe,'tokenizer> ArkParser<'source,'error_pipe,'tokenizer> {
             } else {
                 String::new()
             };
+            self.finish_node(marker);
             return AstNode {
                 node:
                 Node::Import(Import {
@@ -819,155 +1464,868 @@ impl<'source,'error_pipe,'tokenizer> ArkParser<'source,'error_pipe,'tokenizer> {
                 length:import_keyword.length,
             };
         }
-        return AstNode {
-            node:
-            Node::Import(Import {
-                import_name: AstNode { node: name, pos: import_name.pos, length: import_name.length },
-                alias: None
-            }),
-            pos:import_keyword.pos,
-            length:import_keyword.length,
+        self.finish_node(marker);
+        return AstNode {
+            node:
+            Node::Import(Import {
+                import_name: AstNode { node: name, pos: import_name.pos, length: import_name.length },
+                alias: None
+            }),
+            pos:import_keyword.pos,
+            length:import_keyword.length,
+        };
+    }
+    
+    fn parse_function(&mut self,kind:FunctionKind) -> AstNode<Node> {
+        let external = kind == FunctionKind::Extern;
+        let closure = kind == FunctionKind::Closure;
+        let marker = self.start_node(if closure { SyntaxKind::Closure } else { SyntaxKind::Function });
+        let func_keyword = match self.eat(&TokenType::Keyword(KeyWords::FUNC),"") {
+            Ok(t)=>t,
+            Err(e) => {return e;}
+        };
+        let func_name = if closure {
+            AstNode::new(TokenType::Identifier(String::new()),func_keyword.pos,func_keyword.length)
+        }
+        else {
+            match self.eat(&TokenType::Identifier(String::new()),"expect a function name") {
+                Ok(t)=>t,
+                Err(e) => {
+                    return self.recover_function(e,marker);
+                }
+            }
+        };
+        let function_name = if let TokenType::Identifier(id) = func_name.node {
+            id
+        } else {
+            String::new()
+        };
+        let mut type_parameters = vec![];
+        if !closure && self.expected(&TokenType::Less) {
+            let type_parameter_marker = self.start_node(SyntaxKind::TypeParameterList);
+            let less = match self.eat(&TokenType::Less,"") {
+                Ok(t)=>t,
+                Err(e) => {return self.recover_function(e,marker);}
+            };
+            if external {
+                self.report_error_at("an extern function cannot have type parameters", less.pos, less.length);
+                self.panic_mode = false;
+            }
+            loop {
+                let type_parameter = match self.eat(&TokenType::Identifier(String::new()),"expected a type parameter name") {
+                    Ok(t)=>t,
+                    Err(e) => {return self.recover_function(e,marker);}
+                };
+                if let TokenType::Identifier(name) = type_parameter.node {
+                    type_parameters.push(AstNode::new(name,type_parameter.pos,type_parameter.length));
+                }
+                if !self.expected(&TokenType::Comma) {
+                    break;
+                }
+                self.advance();
+            }
+            let _ = match self.eat(&TokenType::More,"expected '>' after the type parameters") {
+                Ok(t)=>t,
+                Err(e) => {return self.recover_function(e,marker);}
+            };
+            self.finish_node(type_parameter_marker);
+        }
+        let parameter_marker = self.start_node(SyntaxKind::ParameterList);
+        let _ = match self.eat(&TokenType::LeftParen,"expected function parameter") {
+            Ok(t)=>t,
+            Err(e) => {
+                return self.recover_function(e,marker);
+            }
+        };
+        let mut parameters: Vec<AstNode<Var>> = vec![];
+        while !self.expected(&TokenType::RightParen){
+            let para_marker = self.start_node(SyntaxKind::Parameter);
+            let para_name_t = match self.eat(&TokenType::Identifier(String::new()),"missing parameter name") {
+                Ok(t)=>t,
+                Err(e) => {
+                    return self.recover_function(e,marker);
+                }
+            };
+            let para_name = if let TokenType::Identifier(id) = para_name_t.node {
+                id
+            }
+            else{
+                String::new()
+            };
+            // self in a method takes the type of the trait or impl block without ': type'
+            let implicit_self = if para_name == "self" && !self.expected(&TokenType::Colon) {
+                self.self_type.clone()
+            }
+            else {
+                None
+            };
+            let para_type_t = match implicit_self {
+                Some(self_type) => AstNode::new(self_type,para_name_t.pos,para_name_t.length),
+                None => {
+                    match self.eat(&TokenType::Colon,"expected ':' for type declaration"){
+                        Ok(t) => t,
+                        Err(e) => {
+                            return self.recover_function(e,marker);
+                        }
+                    };
+                    if !self.at_data_type() {
+                        let e = AstNode::new(Node::ParserError(self.raise_error("missing parameter type")),self.look_ahead.pos,self.look_ahead.length);
+                        return self.recover_function(e,marker);
+                    }
+                    match self.parse_data_type() {
+                        Ok(t)=>t,
+                        Err(e) => {
+                            return self.recover_function(e,marker);
+                        }
+                    }
+                }
+            };
+            let para_type = para_type_t.node.clone();
+            parameters.push(
+                AstNode::new(
+                    Var {
+                        constant:None,
+                        name:AstNode::new(para_name, para_name_t.pos, para_name_t.length),
+                        var_type:Some(AstNode::new(para_type, para_type_t.pos, para_type_t.length)),
+                        public:false
+                    },
+                    para_type_t.pos,
+                    para_type_t.length + 1 + para_name_t.length
+                )
+                
+            );
+            self.finish_node(para_marker);
+            if self.expected(&TokenType::Comma) {
+                self.advance();
+            }
+        }
+        let r_paren = match self.eat(&TokenType::RightParen,"") {
+            Ok(t)=>t,
+            Err(e) => {return self.recover_function(e,marker);}
+        };
+        self.finish_node(parameter_marker);
+        let mut return_type_token :Option<AstNode<TokenType>> = None;
+        let mut return_type = DataType::Void;
+        if self.expected(&TokenType::Colon) {
+            let return_marker = self.start_node(SyntaxKind::ReturnType);
+            let _ = self.eat(&TokenType::Colon,"");
+            if !self.at_data_type() {
+                let e = AstNode::new(Node::ParserError(self.raise_error("expected function return type, omit ':' if type is void")),self.look_ahead.pos,self.look_ahead.length);
+                return self.recover_function(e,marker);
+            }
+            let return_type_t = match self.parse_data_type() {
+                Ok(t)=>t,
+                Err(e) => {return self.recover_function(e,marker);}
+            };
+            return_type = return_type_t.node.clone();
+            return_type_token = Some(AstNode::new(TokenType::DataType(return_type_t.node),return_type_t.pos,return_type_t.length));
+            self.finish_node(return_marker);
+        }
+        let runtime = kind == FunctionKind::Plain && self.runtime_declarations && self.expected(&TokenType::SemiColon);
+        let body = if runtime {
+            self.advance();
+            Body { instructions: vec![] }
+        }
+        else if external || kind == FunctionKind::Signature {
+            let message = if external { "an extern function has no body, expected ';'" } else { "a trait method has no body, expected ';'" };
+            match self.eat(&TokenType::SemiColon,message) {
+                Ok(_)=>(),
+                Err(e) => {return self.recover_function(e,marker);}
+            };
+            Body { instructions: vec![] }
+        }
+        else {
+            self.parse_block()
+        };
+        // a return statement that failed to parse would show up here as well
+        if !runtime
+            && (kind == FunctionKind::Plain || closure)
+            && return_type != DataType::Void
+            && !body.contains(&Node::Return(None))
+            && !body.contains_endless_loop()
+            && !body.contains(&Node::ParserError(ParserError::new(ErrorType::SyntaxError, "", (0,0))))
+        {
+            let ret_token = return_type_token.unwrap();
+            self.err_pipe.raise_error(ErrorType::SyntaxError, "missing return statement", ret_token.pos, ret_token.length);
+        };
+        self.finish_node(marker);
+        let func_def = FuncDef {
+            function_name:AstNode::new(
+                function_name,
+                func_name.pos,
+                func_name.length
+            ),
+            type_parameters,
+            body,
+            parameters,
+            return_type:AstNode::new(return_type.clone(),r_paren.pos,return_type.to_string().len() as u32),
+            public:false,
+            runtime,
+            external
+        };
+        if closure {
+            return AstNode::new(Node::Closure(func_def),func_keyword.pos,func_keyword.length);
+        }
+        AstNode::new(
+            Node::Function(func_def),
+            func_keyword.pos,
+            0
+        )
+
+    }
+    fn parse_struct(&mut self) -> AstNode<Node> {
+        let marker = self.start_node(SyntaxKind::Struct);
+        let struct_kw = match self.eat(&TokenType::Keyword(KeyWords::STRUCT),"") {
+            Ok(t)=>t,
+            Err(e) => {return e;}
+        };
+        let name_t = match self.eat(&TokenType::Identifier(String::new()),"expected a struct name") {
+            Ok(t)=>t,
+            Err(e) => {return e;}
+        };
+        let struct_name = if let TokenType::Identifier(id) = name_t.node {
+            id
+        } else {
+            String::new()
+        };
+        match self.eat(&TokenType::LeftBrace,"expected '{' after struct name") {
+            Ok(t)=>t,
+            Err(e) => {return e;}
+        };
+        let mut fields: Vec<AstNode<Var>> = vec![];
+        while !self.expected(&TokenType::RightBrace) && !self.expected(&TokenType::EOF) {
+            let field_marker = self.start_node(SyntaxKind::Field);
+            let field_name_t = match self.eat(&TokenType::Identifier(String::new()),"expected field name") {
+                Ok(t)=>t,
+                Err(e) => {return self.recover_declaration(e,marker);}
+            };
+            let field_name = if let TokenType::Identifier(id) = field_name_t.node {
+                id
+            } else {
+                String::new()
+            };
+            match self.eat(&TokenType::Colon,"expected ':' for type declaration") {
+                Ok(t)=>t,
+                Err(e) => {return self.recover_declaration(e,marker);}
+            };
+            let field_type = match self.parse_data_type() {
+                Ok(t)=>t,
+                Err(e) => {return self.recover_declaration(e,marker);}
+            };
+            fields.push(
+                AstNode::new(
+                    Var {
+                        constant:None,
+                        name:AstNode::new(field_name, field_name_t.pos, field_name_t.length),
+                        var_type:Some(field_type.clone()),
+                        public:false
+                    },
+                    field_name_t.pos,
+                    field_name_t.length
+                )
+            );
+            self.finish_node(field_marker);
+            if self.expected(&TokenType::Comma) {
+                self.advance();
+            }
+            else if !self.expected(&TokenType::RightBrace) {
+                let er = self.raise_error("expected ',' between struct fields");
+                return self.recover_declaration(AstNode::new(Node::ParserError(er.clone()),er.pos,0),marker);
+            }
+        }
+        let close = match self.eat(&TokenType::RightBrace,"unclosed brace") {
+            Ok(t)=>t,
+            Err(e) => {return e;}
+        };
+        self.finish_node(marker);
+        AstNode::new(
+            Node::Struct(
+                StructDef {
+                    struct_name:AstNode::new(struct_name, name_t.pos, name_t.length),
+                    fields,
+                    public:false
+                }
+            ),
+            struct_kw.pos,
+            Self::span_length(struct_kw.pos, &close)
+        )
+    }
+
+    fn parse_trait(&mut self) -> AstNode<Node> {
+        let marker = self.start_node(SyntaxKind::Trait);
+        let trait_kw = match self.eat(&TokenType::Keyword(KeyWords::TRAIT),"") {
+            Ok(t)=>t,
+            Err(e) => {return e;}
+        };
+        let name_t = match self.eat(&TokenType::Identifier(String::new()),"expected a trait name") {
+            Ok(t)=>t,
+            Err(e) => {return self.recover_declaration(e,marker);}
+        };
+        let trait_name = if let TokenType::Identifier(id) = name_t.node {
+            id
+        } else {
+            String::new()
+        };
+        match self.eat(&TokenType::LeftBrace,"expected '{' after trait name") {
+            Ok(t)=>t,
+            Err(e) => {return self.recover_declaration(e,marker);}
+        };
+        // Self stays a name in the signatures, every impl replaces it with its own type
+        let outer = self.self_type.replace(DataType::Struct(String::from("Self")));
+        let methods = self.parse_methods(true);
+        self.self_type = outer;
+        let close = match self.eat(&TokenType::RightBrace,"unclosed brace") {
+            Ok(t)=>t,
+            Err(e) => {return e;}
+        };
+        self.finish_node(marker);
+        AstNode::new(
+            Node::Trait(
+                TraitDef {
+                    trait_name:AstNode::new(trait_name, name_t.pos, name_t.length),
+                    methods,
+                    public:false
+                }
+            ),
+            trait_kw.pos,
+            Self::span_length(trait_kw.pos, &close)
+        )
+    }
+
+    fn parse_impl(&mut self) -> AstNode<Node> {
+        let marker = self.start_node(SyntaxKind::Impl);
+        let impl_kw = match self.eat(&TokenType::Keyword(KeyWords::IMPL),"") {
+            Ok(t)=>t,
+            Err(e) => {return e;}
+        };
+        let mut target = match self.parse_data_type() {
+            Ok(t)=>t,
+            Err(e) => {return self.recover_declaration(e,marker);}
+        };
+        let mut trait_name = None;
+        // impl Shape for Point, the first name was the trait
+        if self.expected(&TokenType::Keyword(KeyWords::FOR)) {
+            self.advance();
+            match &target.node {
+                DataType::Struct(name) => trait_name = Some(AstNode::new(name.clone(),target.pos,target.length)),
+                _ => {
+                    self.report_error_at("expected a trait name before 'for'", target.pos, target.length);
+                    self.panic_mode = false;
+                }
+            }
+            target = match self.parse_data_type() {
+                Ok(t)=>t,
+                Err(e) => {return self.recover_declaration(e,marker);}
+            };
+        }
+        match self.eat(&TokenType::LeftBrace,"expected '{' after impl type") {
+            Ok(t)=>t,
+            Err(e) => {return self.recover_declaration(e,marker);}
+        };
+        let outer = self.self_type.replace(target.node.clone());
+        let methods = self.parse_methods(false);
+        self.self_type = outer;
+        let close = match self.eat(&TokenType::RightBrace,"unclosed brace") {
+            Ok(t)=>t,
+            Err(e) => {return e;}
+        };
+        self.finish_node(marker);
+        AstNode::new(
+            Node::Impl(
+                ImplBlock {
+                    trait_name,
+                    target,
+                    methods
+                }
+            ),
+            impl_kw.pos,
+            Self::span_length(impl_kw.pos, &close)
+        )
+    }
+
+    // the members of a trait or impl block up to its closing brace, a trait only
+    // declares signatures and the methods of an impl can be pub
+    fn parse_methods(&mut self,signatures:bool) -> Vec<AstNode<Node>> {
+        let mut methods = vec![];
+        while !self.expected(&TokenType::RightBrace) && !self.expected(&TokenType::EOF) {
+            let outer_errors = mem::replace(&mut self.statement_errors, 0);
+            let start = self.look_ahead.pos;
+            let public = !signatures && self.expected(&TokenType::Keyword(KeyWords::PUB));
+            if public {
+                self.advance();
+            }
+            if self.expected(&TokenType::Keyword(KeyWords::FUNC)) {
+                let kind = if signatures { FunctionKind::Signature } else { FunctionKind::Plain };
+                let mut method = self.parse_function(kind);
+                if let Node::Function(func) = &mut method.node {
+                    func.public = public;
+                }
+                methods.push(method);
+            }
+            else if signatures {
+                self.raise_error("expected a method signature");
+            }
+            else {
+                self.raise_error("expected a method");
+            }
+            if self.panic_mode {
+                self.synchronize();
+            }
+            if self.look_ahead.pos == start && !self.expected(&TokenType::EOF) && !self.expected(&TokenType::RightBrace) {
+                self.advance();
+            }
+            self.statement_errors = outer_errors;
+        }
+        methods
+    }
+
+    // skips the rest of a broken struct or enum declaration, so its closing brace
+    // is not mistaken for the end of an enclosing block
+    fn recover_declaration(&mut self,error:AstNode<Node>,marker:usize) -> AstNode<Node> {
+        let checkpoint = self.checkpoint();
+        while !self.expected(&TokenType::RightBrace) && !self.expected(&TokenType::EOF) {
+            self.advance();
+        }
+        self.wrap_skipped(checkpoint);
+        if self.expected(&TokenType::RightBrace) {
+            self.advance();
+        }
+        self.finish_node(marker);
+        error
+    }
+
+    fn parse_struct_literal(&mut self,struct_name:AstNode<String>,checkpoint:usize) -> AstNode<Node> {
+        let marker = self.start_node_at(checkpoint, SyntaxKind::StructLiteral);
+        match self.eat(&TokenType::LeftBrace,"expected '{'") {
+            Ok(t)=>t,
+            Err(e) => {return e;}
+        };
+        let outer = mem::replace(&mut self.no_struct_literal, false);
+        let mut fields: Vec<(AstNode<String>,AstNode<Node>)> = vec![];
+        while !self.expected(&TokenType::RightBrace) && !self.expected(&TokenType::EOF) {
+            let field_marker = self.start_node(SyntaxKind::FieldInit);
+            let field_name_t = match self.eat(&TokenType::Identifier(String::new()),"expected field name") {
+                Ok(t)=>t,
+                Err(e) => {
+                    self.no_struct_literal = outer;
+                    return e;
+                }
+            };
+            let field_name = if let TokenType::Identifier(id) = field_name_t.node {
+                id
+            } else {
+                String::new()
+            };
+            match self.eat(&TokenType::Colon,"expected ':' after field name") {
+                Ok(t)=>t,
+                Err(e) => {
+                    self.no_struct_literal = outer;
+                    return e;
+                }
+            };
+            let value = self.parse_primary();
+            self.finish_node(field_marker);
+            fields.push((AstNode::new(field_name, field_name_t.pos, field_name_t.length),value));
+            if self.expected(&TokenType::Comma) {
+                self.advance();
+            }
+            else if !self.expected(&TokenType::RightBrace) {
+                self.no_struct_literal = outer;
+                let er = self.raise_error("expected ',' between struct fields");
+                return AstNode::new(Node::ParserError(er.clone()),er.pos,0);
+            }
+        }
+        self.no_struct_literal = outer;
+        let close = match self.eat(&TokenType::RightBrace,"unclosed brace") {
+            Ok(t)=>t,
+            Err(e) => {return e;}
+        };
+        self.finish_node(marker);
+        let pos = struct_name.pos;
+        AstNode::new(
+            Node::StructLiteral(
+                StructLiteral {
+                    struct_name,
+                    fields
+                }
+            ),
+            pos,
+            Self::span_length(pos, &close)
+        )
+    }
+
+    fn parse_enum(&mut self) -> AstNode<Node> {
+        let marker = self.start_node(SyntaxKind::Enum);
+        let enum_kw = match self.eat(&TokenType::Keyword(KeyWords::ENUM),"") {
+            Ok(t)=>t,
+            Err(e) => {return e;}
+        };
+        let name_t = match self.eat(&TokenType::Identifier(String::new()),"expected an enum name") {
+            Ok(t)=>t,
+            Err(e) => {return e;}
+        };
+        let enum_name = if let TokenType::Identifier(id) = name_t.node {
+            id
+        } else {
+            String::new()
+        };
+        match self.eat(&TokenType::LeftBrace,"expected '{' after enum name") {
+            Ok(t)=>t,
+            Err(e) => {return e;}
+        };
+        let mut variants: Vec<AstNode<EnumVariant>> = vec![];
+        while !self.expected(&TokenType::RightBrace) && !self.expected(&TokenType::EOF) {
+            let variant_marker = self.start_node(SyntaxKind::Variant);
+            let variant_t = match self.eat(&TokenType::Identifier(String::new()),"expected variant name") {
+                Ok(t)=>t,
+                Err(e) => {return self.recover_declaration(e,marker);}
+            };
+            let variant_name = if let TokenType::Identifier(id) = variant_t.node {
+                id
+            } else {
+                String::new()
+            };
+            let mut payload: Vec<AstNode<DataType>> = vec![];
+            if self.expected(&TokenType::LeftParen) {
+                self.advance();
+                while !self.expected(&TokenType::RightParen) && !self.expected(&TokenType::EOF) {
+                    let payload_type = match self.parse_data_type() {
+                        Ok(t)=>t,
+                        Err(e) => {return self.recover_declaration(e,marker);}
+                    };
+                    payload.push(payload_type);
+                    if self.expected(&TokenType::Comma) {
+                        self.advance();
+                    }
+                    else if !self.expected(&TokenType::RightParen) {
+                        let er = self.raise_error("expected ',' between variant fields");
+                        return self.recover_declaration(AstNode::new(Node::ParserError(er.clone()),er.pos,0),marker);
+                    }
+                }
+                match self.eat(&TokenType::RightParen,"unclosed parenthesis") {
+                    Ok(t)=>t,
+                    Err(e) => {return self.recover_declaration(e,marker);}
+                };
+            }
+            self.finish_node(variant_marker);
+            variants.push(
+                AstNode::new(
+                    EnumVariant {
+                        name:AstNode::new(variant_name, variant_t.pos, variant_t.length),
+                        payload
+                    },
+                    variant_t.pos,
+                    variant_t.length
+                )
+            );
+            if self.expected(&TokenType::Comma) {
+                self.advance();
+            }
+            else if !self.expected(&TokenType::RightBrace) {
+                let er = self.raise_error("expected ',' between enum variants");
+                return self.recover_declaration(AstNode::new(Node::ParserError(er.clone()),er.pos,0),marker);
+            }
+        }
+        let close = match self.eat(&TokenType::RightBrace,"unclosed brace") {
+            Ok(t)=>t,
+            Err(e) => {return e;}
         };
+        self.finish_node(marker);
+        AstNode::new(
+            Node::Enum(
+                EnumDef {
+                    enum_name:AstNode::new(enum_name, name_t.pos, name_t.length),
+                    variants,
+                    public:false
+                }
+            ),
+            enum_kw.pos,
+            Self::span_length(enum_kw.pos, &close)
+        )
     }
-    
-    fn parse_function(&mut self) -> AstNode<Node> {
-        let func_keyword = match self.eat(&TokenType::Keyword(KeyWords::FUNC),"") {
+
+    fn parse_enum_value(&mut self,mut enum_name:AstNode<String>,checkpoint:usize) -> AstNode<Node> {
+        match self.eat(&TokenType::ScopeResolution,"expected '::'") {
             Ok(t)=>t,
             Err(e) => {return e;}
         };
-        let func_name = match self.eat(&TokenType::Identifier(String::new()),"expect a function name") {
+        let mut variant_t = match self.eat(&TokenType::Identifier(String::new()),"expected variant name after '::'") {
             Ok(t)=>t,
-            Err(e) => {
-                self.skip_until(&TokenType::LeftBrace);
-                self.skip_block();
-                return e;
-            }
+            Err(e) => {return e;}
         };
-        let function_name = if let TokenType::Identifier(id) = func_name.node {
+        // m::Shape::Square(..) and m::Point { .. } name a type of an imported module
+        if self.expected(&TokenType::LeftBrace) && !self.no_struct_literal {
+            let type_name = Self::qualified_path(&enum_name, &variant_t);
+            return self.parse_struct_literal(type_name,checkpoint);
+        }
+        if self.expected(&TokenType::ScopeResolution) {
+            enum_name = Self::qualified_path(&enum_name, &variant_t);
+            self.advance();
+            variant_t = match self.eat(&TokenType::Identifier(String::new()),"expected variant name after '::'") {
+                Ok(t)=>t,
+                Err(e) => {return e;}
+            };
+        }
+        let marker = self.start_node_at(checkpoint, SyntaxKind::EnumValue);
+        let variant = if let TokenType::Identifier(id) = variant_t.node.clone() {
             id
         } else {
             String::new()
         };
-        let _ = match self.eat(&TokenType::LeftParen,"expected function parameter") {
-            Ok(t)=>t,
-            Err(e) => {
-                self.skip_until(&TokenType::LeftBrace);
-                self.skip_block();
-                return e;
+        let mut end = variant_t.clone();
+        let mut arguments = vec![];
+        if self.expected(&TokenType::LeftParen) {
+            let (args,close) = match self.parse_arguments() {
+                Ok(args) => args,
+                Err(e) => {return e;}
+            };
+            arguments = args;
+            end = close;
+        }
+        self.finish_node(marker);
+        let pos = enum_name.pos;
+        AstNode::new(
+            Node::EnumValue(
+                EnumValue {
+                    enum_name,
+                    variant:AstNode::new(variant, variant_t.pos, variant_t.length),
+                    arguments
+                }
+            ),
+            pos,
+            Self::span_length(pos, &end)
+        )
+    }
+
+    // module::Type as a single name spanning both parts
+    fn qualified_path(module:&AstNode<String>,type_t:&AstNode<TokenType>) -> AstNode<String> {
+        let type_name = match &type_t.node {
+            TokenType::Identifier(id) => id.as_str(),
+            _ => ""
+        };
+        AstNode::new(format!("{}::{}",module.node,type_name), module.pos, Self::span_length(module.pos, type_t))
+    }
+
+    fn parse_pattern_literal(&mut self) -> Result<AstNode<LiteralValue>,AstNode<Node>> {
+        let negative = if self.expected(&TokenType::SubtractionOperator) {
+            Some(self.eat(&TokenType::SubtractionOperator,"").unwrap())
+        }
+        else {
+            None
+        };
+        let literal_t = match &self.look_ahead.token {
+            TokenType::IntLiteral(_) => self.eat(&TokenType::IntLiteral(0),""),
+            TokenType::FloatLiteral(_) => self.eat(&TokenType::FloatLiteral(0.0),""),
+            TokenType::StringLiteral(_) if negative.is_none() => self.eat(&TokenType::StringLiteral(String::new()),""),
+            TokenType::BooleanLiteral(_) if negative.is_none() => self.eat(&TokenType::BooleanLiteral(true),""),
+            _ => {
+                let er = self.raise_error("expected pattern");
+                return Err(AstNode::new(Node::ParserError(er.clone()),er.pos,0));
             }
+        }?;
+        let literal = match (&literal_t.node,&negative) {
+            (TokenType::IntLiteral(i),None) => LiteralValue::Int(*i),
+            (TokenType::IntLiteral(i),Some(_)) => LiteralValue::Int(-i),
+            (TokenType::FloatLiteral(f),None) => LiteralValue::Float(*f),
+            (TokenType::FloatLiteral(f),Some(_)) => LiteralValue::Float(-f),
+            (TokenType::StringLiteral(st),_) => LiteralValue::Str(st.clone()),
+            (TokenType::BooleanLiteral(b),_) => LiteralValue::Bool(*b),
+            _ => LiteralValue::Int(0)
         };
-        let mut parameters: Vec<AstNode<Var>> = vec![];
-        while !self.expected(&TokenType::RightParen){
-            
-            let para_name_t = match self.eat(&TokenType::Identifier(String::new()),"missing parameter name") {
-                Ok(t)=>t,
-                Err(e) => {
-                    self.skip_until(&TokenType::LeftBrace);
-                    
-                    self.skip_block();
-                    return e;
+        let pos = match &negative {
+            Some(minus) => minus.pos,
+            None => literal_t.pos
+        };
+        Ok(AstNode::new(literal, pos, Self::span_length(pos, &literal_t)))
+    }
+
+    fn parse_pattern(&mut self) -> Result<AstNode<Pattern>,AstNode<Node>> {
+        let marker = self.start_node(SyntaxKind::Pattern);
+        let start = self.look_ahead.pos;
+        let pattern = match &self.look_ahead.token.clone() {
+            TokenType::Identifier(id) if id == "_" => {
+                let wildcard = self.eat(&TokenType::Identifier(String::new()),"")?;
+                self.finish_node(marker);
+                return Ok(AstNode::new(Pattern::Wildcard, wildcard.pos, wildcard.length));
+            },
+            TokenType::Identifier(enum_name) => {
+                let enum_t = self.eat(&TokenType::Identifier(String::new()),"")?;
+                let mut enum_name = AstNode::new(enum_name.clone(), enum_t.pos, enum_t.length);
+                self.eat(&TokenType::ScopeResolution,"expected '::' after enum name in pattern")?;
+                let mut variant_t = self.eat(&TokenType::Identifier(String::new()),"expected variant name after '::'")?;
+                if self.expected(&TokenType::ScopeResolution) {
+                    enum_name = Self::qualified_path(&enum_name, &variant_t);
+                    self.advance();
+                    variant_t = self.eat(&TokenType::Identifier(String::new()),"expected variant name after '::'")?;
+                }
+                let variant = if let TokenType::Identifier(id) = variant_t.node.clone() {
+                    id
+                } else {
+                    String::new()
+                };
+                let mut end = variant_t.clone();
+                let mut bindings = vec![];
+                if self.expected(&TokenType::LeftParen) {
+                    self.advance();
+                    while !self.expected(&TokenType::RightParen) && !self.expected(&TokenType::EOF) {
+                        let binding_t = self.eat(&TokenType::Identifier(String::new()),"expected a name to bind the variant field to")?;
+                        if let TokenType::Identifier(binding) = binding_t.node {
+                            bindings.push(AstNode::new(binding, binding_t.pos, binding_t.length));
+                        }
+                        if self.expected(&TokenType::Comma) {
+                            self.advance();
+                        }
+                        else if !self.expected(&TokenType::RightParen) {
+                            let er = self.raise_error("expected ',' or ')' after binding");
+                            return Err(AstNode::new(Node::ParserError(er.clone()),er.pos,0));
+                        }
+                    }
+                    end = self.eat(&TokenType::RightParen,"unclosed parenthesis")?;
+                }
+                AstNode::new(
+                    Pattern::Variant(VariantPattern {
+                        enum_name,
+                        variant:AstNode::new(variant, variant_t.pos, variant_t.length),
+                        bindings
+                    }),
+                    start,
+                    Self::span_length(start, &end)
+                )
+            },
+            _ => {
+                let low = self.parse_pattern_literal()?;
+                if self.expected(&TokenType::Range) || self.expected(&TokenType::RangeInclusive) {
+                    let inclusive = self.expected(&TokenType::RangeInclusive);
+                    self.advance();
+                    let high = self.parse_pattern_literal()?;
+                    let length = Self::span_length(start, &AstNode::new(TokenType::Range, high.pos, high.length));
+                    AstNode::new(Pattern::Range(low.node, high.node, inclusive), start, length)
+                }
+                else {
+                    AstNode::new(Pattern::Literal(low.node), low.pos, low.length)
                 }
-            };
-            let para_name = if let TokenType::Identifier(id) = para_name_t.node {
-                id
             }
-            else{
-                String::new()
-            };
-            match self.eat(&TokenType::Colon,"expected ':' for type declaration"){
-                Ok(t) => t,
+        };
+        self.finish_node(marker);
+        Ok(pattern)
+    }
+
+    fn parse_match(&mut self) -> AstNode<Node> {
+        let marker = self.start_node(SyntaxKind::Match);
+        let match_kw = match self.eat(&TokenType::Keyword(KeyWords::MATCH),"") {
+            Ok(t)=>t,
+            Err(e) => {return e;}
+        };
+        let scrutinee = self.parse_condition_expression();
+        match self.eat(&TokenType::LeftBrace,"expected '{' after match expression") {
+            Ok(t)=>t,
+            Err(e) => {return e;}
+        };
+        let outer = mem::replace(&mut self.no_struct_literal, false);
+        let mut arms: Vec<MatchArm> = vec![];
+        while !self.expected(&TokenType::RightBrace) && !self.expected(&TokenType::EOF) {
+            let arm_marker = self.start_node(SyntaxKind::MatchArm);
+            let pattern = match self.parse_pattern() {
+                Ok(p) => p,
                 Err(e) => {
-                    self.skip_until(&TokenType::LeftBrace);
-                    self.skip_block();
-                    return e;
+                    self.no_struct_literal = outer;
+                    return self.recover_match(e,marker);
                 }
             };
-            let para_type_t = match self.eat(&TokenType::DataType(DataType::Void),"missing parameter type") {
+            match self.eat(&TokenType::FatArrow,"expected '=>' after pattern") {
                 Ok(t)=>t,
                 Err(e) => {
-                    self.skip_until(&TokenType::LeftBrace);
-                    self.skip_block();
-                    return e;
+                    self.no_struct_literal = outer;
+                    return self.recover_match(e,marker);
                 }
             };
-            let para_type = if let TokenType::DataType(data_type) = para_type_t.node {
-                data_type
+            let is_block = self.expected(&TokenType::LeftBrace);
+            let body = if is_block {
+                let pos = self.look_ahead.pos;
+                let block = self.parse_block();
+                AstNode::new(Node::Body(block),pos,1)
             }
-            else{
-                DataType::Void
+            else {
+                self.parse_primary()
             };
-            parameters.push(
-                AstNode::new(
-                    Var {
-                        constant:None,
-                        name:AstNode::new(para_name, para_name_t.pos, para_name_t.length),
-                        var_type:AstNode::new(para_type, para_type_t.pos, para_type_t.length)
-                    },
-                    para_type_t.pos,
-                    para_type_t.length + 1 + para_name_t.length
-                )
-                
-            );
+            self.finish_node(arm_marker);
+            arms.push(MatchArm { pattern, body: Box::new(body) });
             if self.expected(&TokenType::Comma) {
-                self.eat(&TokenType::Comma,"");
+                self.advance();
+            }
+            else if !is_block && !self.expected(&TokenType::RightBrace) {
+                self.no_struct_literal = outer;
+                let er = self.raise_error("expected ',' between match arms");
+                return self.recover_match(AstNode::new(Node::ParserError(er.clone()),er.pos,0),marker);
             }
         }
-        let r_paren = match self.eat(&TokenType::RightParen,"") {
+        self.no_struct_literal = outer;
+        let close = match self.eat(&TokenType::RightBrace,"unclosed brace") {
             Ok(t)=>t,
             Err(e) => {return e;}
         };
-        let mut return_type_token :Option<AstNode<TokenType>> = None;
-        let mut return_type = DataType::Void;
-        if self.expected(&TokenType::Colon) {
-            let _ = self.eat(&TokenType::Colon,"");
-            return_type_token = match self.eat(&TokenType::DataType(DataType::Void),"expected function return type, omit ':' if type is void") {
-                Ok(t)=> Some(t),
-                Err(e) => {return e;}
-            };
-            return_type = if let TokenType::DataType(data_type) = return_type_token.clone().unwrap().node {
-                data_type
-            }
-            else{
-                DataType::Void
-            };
-        }
-        let body = self.parse_block();
-        if return_type != DataType::Void && !body.contains(&Node::Return(None)){
-            let ret_token = return_type_token.unwrap();
-            self.err_pipe.raise_error(ErrorType::SyntaxError, "missing return statement", ret_token.pos, ret_token.length);
-        };
-        return AstNode::new(
-            Node::Function(
-                FuncDef {
-                    function_name:AstNode::new(
-                        function_name,
-                        func_name.pos,
-                        func_name.length
-                    ),
-                    body,
-                    parameters,
-                    return_type:AstNode::new(return_type.clone(),r_paren.pos,return_type.to_string().len() as u32)
+        self.finish_node(marker);
+        AstNode::new(
+            Node::Match(
+                MatchExp {
+                    scrutinee: Box::new(scrutinee),
+                    arms
                 }
             ),
-            func_keyword.pos,
-            0
-        );
+            match_kw.pos,
+            Self::span_length(match_kw.pos, &close)
+        )
+    }
 
+    // skips to the brace closing the match, arms may contain blocks of their own
+    fn recover_match(&mut self,error:AstNode<Node>,marker:usize) -> AstNode<Node> {
+        let checkpoint = self.checkpoint();
+        let mut depth = 1;
+        while !self.expected(&TokenType::EOF) {
+            if self.expected(&TokenType::LeftBrace) {
+                depth += 1;
+            }
+            else if self.expected(&TokenType::RightBrace) {
+                depth -= 1;
+                if depth == 0 {
+                    break;
+                }
+            }
+            self.advance();
+        }
+        self.wrap_skipped(checkpoint);
+        if self.expected(&TokenType::RightBrace) {
+            self.advance();
+        }
+        self.finish_node(marker);
+        error
     }
+
     fn parse_return(&mut self) -> AstNode<Node> {
+        let marker = self.start_node(SyntaxKind::Return);
         let ret_kw = match self.eat(&TokenType::Keyword(KeyWords::RETURN),"") {
             Ok(t)=>t,
             Err(e) => {return e;}
         };
         if self.expected(&TokenType::SemiColon) {
+            self.finish_node(marker);
             return AstNode::new(Node::Return(None),ret_kw.pos,ret_kw.length+1);
         }
         let exp = self.parse_primary();
+        self.finish_node(marker);
         let val = AstNode::new(Node::Return(Some(Box::from(exp.clone()))),ret_kw.pos,ret_kw.length + 1 + exp.length);
         val
     }
 
+    fn parse_condition_expression(&mut self) -> AstNode<Node> {
+        let outer = mem::replace(&mut self.no_struct_literal, true);
+        let condition = self.parse_primary();
+        self.no_struct_literal = outer;
+        condition
+    }
+
     fn parse_condition(&mut self) -> AstNode<Node>{
+        let marker = self.start_node(SyntaxKind::Conditional);
         let if_condition;
         let if_kw =match self.eat(&TokenType::Keyword(KeyWords::IF),"") {
             Ok(t)=>t,
@@ -981,7 +2339,7 @@ impl<'source,'error_pipe,'tokenizer> ArkParser<'source,'error_pipe,'tokenizer> {
         // }
         //else{
 
-        if_condition = self.parse_primary();
+        if_condition = self.parse_condition_expression();
         //}
         // match self.eat(&TokenType::LeftBrace) {
         //     Ok(t)=>t,
@@ -1007,7 +2365,7 @@ impl<'source,'error_pipe,'tokenizer> ArkParser<'source,'error_pipe,'tokenizer> {
                 
             // }
             // else{
-            elif_condition = self.parse_primary();
+            elif_condition = self.parse_condition_expression();
             //}
             // match self.eat(&TokenType::LeftBrace) {
             //     Ok(t)=>t,
@@ -1041,6 +2399,7 @@ impl<'source,'error_pipe,'tokenizer> ArkParser<'source,'error_pipe,'tokenizer> {
         else{
             None
         };
+        self.finish_node(marker);
         AstNode::new(
             Node::Conditional(
                 ConditionalBlock {
@@ -1054,7 +2413,8 @@ impl<'source,'error_pipe,'tokenizer> ArkParser<'source,'error_pipe,'tokenizer> {
         )
         
     }
-    fn parse_for(&mut self) -> AstNode<Node>{
+    fn parse_for(&mut self,label:Option<AstNode<String>>,checkpoint:usize) -> AstNode<Node>{
+        let marker = self.start_node_at(checkpoint,SyntaxKind::ForLoop);
         let for_kw = match self.eat(&TokenType::Keyword(KeyWords::FOR),"") {
             Ok(t)=>t,
             Err(e) => {return e;}
@@ -1079,22 +2439,38 @@ impl<'source,'error_pipe,'tokenizer> ArkParser<'source,'error_pipe,'tokenizer> {
             Ok(t)=>t,
             Err(e) => {return e;}
         };
-        let range = self.parse_range();
-
-        // match self.eat(&TokenType::LeftBrace,"") {
-        //     Ok(t)=>t,
-        //     Err(e) => {return e;}
-        // };
-        let for_body = self.parse_block();//self.parse_body(TokenType::RightBrace);
-        // match self.eat(&TokenType::RightBrace) {
-        //     Ok(t)=>t,
-        //     Err(e) => {return e;}
-        // };
+        let outer = mem::replace(&mut self.no_struct_literal, true);
+        let mut range = self.parse_primary();
+        // a trailing .rev() is part of the loop rather than a method of the range
+        let reverse = match range.node {
+            Node::MethodCall(MethodCall { caller:Some(caller), method_name, arguments }) if method_name.node == "rev" && arguments.is_empty() => {
+                range = *caller;
+                Some(method_name)
+            },
+            node => {
+                range.node = node;
+                None
+            }
+        };
+        // step is only a keyword right after the range
+        let step = if matches!(&self.look_ahead.token,TokenType::Identifier(id) if id == "step") {
+            self.advance();
+            Some(Box::new(self.parse_primary()))
+        }
+        else {
+            None
+        };
+        self.no_struct_literal = outer;
+        let for_body = self.parse_block();
+        self.finish_node(marker);
         AstNode::new(
             Node::For(ForLoop {
+                label,
                 body:for_body ,
                 var:Some(Box::new(iden)),
-                range:Box::new(range)
+                range:Box::new(range),
+                step,
+                reverse
             }),
             for_kw.pos,
             for_kw.length
@@ -1103,32 +2479,13 @@ impl<'source,'error_pipe,'tokenizer> ArkParser<'source,'error_pipe,'tokenizer> {
         
     }
 
-    fn parse_range(&mut self) -> AstNode<Node>{
-        
-        let left = self.parse_primary();
-        if self.expected(&TokenType::Range) {
-            match self.eat(&TokenType::Range,"expected range") {
-                Ok(t)=>t,
-                Err(e) => {return e;}
-            };
-            let right = self.parse_primary();
-            return AstNode::new(Node::Range(Range {start:Box::new(left.clone()),end:Box::new(right.clone())}),left.pos,left.length+2+right.length);
-        }
-        if let Node::Range(r) = &left.node {
-            left
-        }
-        else{
-            let er =self.raise_error("expected token 'range' after 'in' keyword");
-            AstNode::new(Node::ParserError(er.clone()),er.pos,0)
-        }
-    }
-
-    fn parse_while(&mut self) -> AstNode<Node> {
+    fn parse_while(&mut self,label:Option<AstNode<String>>,checkpoint:usize) -> AstNode<Node> {
+        let marker = self.start_node_at(checkpoint,SyntaxKind::WhileLoop);
         let while_kw = match self.eat(&TokenType::Keyword(KeyWords::WHILE),"") {
             Ok(t)=>t,
             Err(e) => {return e;}
         };
-        let loop_condition = self.parse_primary();
+        let loop_condition = self.parse_condition_expression();
         // if self.expected(&TokenType::LeftParen){
         //     self.eat(&TokenType::LeftParen,"");
         //     loop_condition = self.parse_primary();
@@ -1150,9 +2507,11 @@ impl<'source,'error_pipe,'tokenizer> ArkParser<'source,'error_pipe,'tokenizer> {
         //     Ok(t)=>t,
         //     Err(e) => {return e;}
         // };
+        self.finish_node(marker);
         AstNode::new(
             Node::While(
                 WhileLoop {
+                    label,
                     condition: Box::new(loop_condition),
                     body: loop_body 
                 }
@@ -1162,28 +2521,237 @@ impl<'source,'error_pipe,'tokenizer> ArkParser<'source,'error_pipe,'tokenizer> {
         )
     }
 
+    // a labeled loop used as a value has to be a `loop`
+    fn parse_labeled_loop(&mut self,as_value:bool) -> AstNode<Node> {
+        let checkpoint = self.checkpoint();
+        let label_t = match self.eat(&TokenType::Label(String::new()),"expected loop label") {
+            Ok(t)=>t,
+            Err(e) => {return e;}
+        };
+        let name = if let TokenType::Label(name) = label_t.node {
+            name
+        }
+        else {
+            String::new()
+        };
+        let label = AstNode::new(name,label_t.pos,label_t.length);
+        match self.eat(&TokenType::Colon,"expected ':' after loop label") {
+            Ok(t)=>t,
+            Err(e) => {return e;}
+        };
+        if self.expected(&TokenType::Keyword(KeyWords::LOOP)) {
+            self.parse_loop(Some(label),checkpoint)
+        }
+        else if as_value {
+            let er = self.raise_error("expected 'loop' after loop label");
+            AstNode::new(Node::ParserError(er.clone()),er.pos,0)
+        }
+        else if self.expected(&TokenType::Keyword(KeyWords::WHILE)) {
+            self.parse_while(Some(label),checkpoint)
+        }
+        else if self.expected(&TokenType::Keyword(KeyWords::FOR)) {
+            self.parse_for(Some(label),checkpoint)
+        }
+        else {
+            let er = self.raise_error("expected 'while', 'for' or 'loop' after loop label");
+            AstNode::new(Node::ParserError(er.clone()),er.pos,0)
+        }
+    }
+
+    fn parse_loop(&mut self,label:Option<AstNode<String>>,checkpoint:usize) -> AstNode<Node> {
+        let marker = self.start_node_at(checkpoint,SyntaxKind::Loop);
+        let loop_kw = match self.eat(&TokenType::Keyword(KeyWords::LOOP),"") {
+            Ok(t)=>t,
+            Err(e) => {return e;}
+        };
+        let body = self.parse_block();
+        self.finish_node(marker);
+        AstNode::new(
+            Node::Loop(
+                InfiniteLoop {
+                    label,
+                    body
+                }
+            ),
+            loop_kw.pos,
+            loop_kw.length
+        )
+    }
+
+    fn parse_loop_exit(&mut self) -> AstNode<Node> {
+        let is_break = self.expected(&TokenType::Keyword(KeyWords::BREAK));
+        let marker = self.start_node(if is_break {SyntaxKind::Break} else {SyntaxKind::Continue});
+        let keyword = self.look_ahead.token.clone();
+        let kw = match self.eat(&keyword,"") {
+            Ok(t)=>t,
+            Err(e) => {return e;}
+        };
+        let mut length = kw.length;
+        let label = if let TokenType::Label(name) = self.look_ahead.token.clone() {
+            let label_t = match self.eat(&TokenType::Label(String::new()),"expected loop label") {
+                Ok(t)=>t,
+                Err(e) => {return e;}
+            };
+            length = label_t.pos.1 + label_t.length - kw.pos.1;
+            Some(AstNode::new(name,label_t.pos,label_t.length))
+        }
+        else {
+            None
+        };
+        let value = if is_break && !self.expected(&TokenType::SemiColon) && !self.expected(&TokenType::RightBrace) && !self.expected(&TokenType::EOF) {
+            let value = self.parse_primary();
+            if value.pos.0 == kw.pos.0 {
+                length = value.pos.1 + value.length - kw.pos.1;
+            }
+            Some(Box::new(value))
+        }
+        else {
+            None
+        };
+        self.finish_node(marker);
+        let exit = LoopExit { label, value };
+        if is_break {
+            AstNode::new(Node::Break(exit),kw.pos,length)
+        }
+        else {
+            AstNode::new(Node::Continue(exit),kw.pos,length)
+        }
+    }
+
+    // let (a, b) = value; declares a variable for every element of a tuple, _ skips one
+    fn parse_destructure(&mut self,declaration_token:AstNode<TokenType>,marker:usize) -> AstNode<Node> {
+        match self.eat(&TokenType::LeftParen,"") {
+            Ok(t)=>t,
+            Err(e) => {return e;}
+        };
+        let mut names = vec![];
+        while !self.expected(&TokenType::RightParen) && !self.expected(&TokenType::EOF) {
+            let name_token = match self.eat(&TokenType::Identifier(String::new()),"expected an identifier") {
+                Ok(t)=>t,
+                Err(e) => {return e;}
+            };
+            if let TokenType::Identifier(name) = name_token.node {
+                names.push(AstNode::new(name,name_token.pos,name_token.length));
+            }
+            if self.expected(&TokenType::Comma) {
+                self.advance();
+            }
+            else {
+                break;
+            }
+        }
+        let close = match self.eat(&TokenType::RightParen,"expected ')' after the names") {
+            Ok(t)=>t,
+            Err(e) => {return e;}
+        };
+        let var_type = if self.expected(&TokenType::Colon) {
+            self.advance();
+            match self.parse_data_type() {
+                Ok(t)=>Some(t),
+                Err(e) => {return e;}
+            }
+        }
+        else {
+            None
+        };
+        match self.eat(&TokenType::AssignmentOperator,"expected '=' after a destructuring let") {
+            Ok(t)=>t,
+            Err(e) => {return e;}
+        };
+        let value = self.parse_primary();
+        self.finish_node(marker);
+        AstNode::new(
+            Node::Destructure(Destructure {
+                names,
+                var_type,
+                value: Box::new(value)
+            }),
+            declaration_token.pos,
+            Self::span_length(declaration_token.pos, &close)
+        )
+    }
+
     fn parse_paren(&mut self) -> AstNode<Node>{
-        self.eat(&TokenType::LeftParen,"");
+        let marker = self.start_node(SyntaxKind::Paren);
+        let open = match self.eat(&TokenType::LeftParen,"expected '('") {
+            Ok(t)=>t,
+            Err(e) => {return e;}
+        };
         let mut items:Vec<AstNode<Node>> = vec![];
-        while !self.expected(&TokenType::RightParen) {
+        let mut comma = false;
+        let outer = mem::replace(&mut self.no_struct_literal, false);
+        while !self.expected(&TokenType::RightParen) && !self.expected(&TokenType::EOF) {
             items.push(self.parse_primary());
             if self.expected(&TokenType::Comma) {
-                self.eat(&TokenType::Comma,"");
+                self.advance();
+                comma = true;
+            }
+            else if !self.expected(&TokenType::RightParen) {
+                break;
             }
         }
+        self.no_struct_literal = outer;
         let close = match self.eat(&TokenType::RightParen,"unclosed parenthesis"){
             Ok(t)=>t,
             Err(e) => {return e;}
         };
-        if items.len() == 1 {
+        self.finish_node(marker);
+        // (x) is just x, (x,) is a tuple of one element
+        if items.len() == 1 && !comma {
             return items[0].clone();
         }
         else{
-            return AstNode::new(Node::Tuple(TupleBody { members: items.clone() }),items.first().unwrap().pos,close.length);
+            AstNode::new(Node::Tuple(TupleBody { members: items.clone() }),open.pos,Self::span_length(open.pos, &close))
         }
     }
 
+    fn parse_array_literal(&mut self) -> AstNode<Node>{
+        let marker = self.start_node(SyntaxKind::ArrayLiteral);
+        let open = match self.eat(&TokenType::LeftBracket,"expected '['") {
+            Ok(t)=>t,
+            Err(e) => {return e;}
+        };
+        let outer = mem::replace(&mut self.no_struct_literal, false);
+        let mut elements:Vec<AstNode<Node>> = vec![];
+        let mut repeat:Option<AstNode<u32>> = None;
+        while !self.expected(&TokenType::RightBracket) && !self.expected(&TokenType::EOF) {
+            elements.push(self.parse_primary());
+            if elements.len() == 1 && self.expected(&TokenType::SemiColon) {
+                self.advance();
+                let count = match self.eat(&TokenType::IntLiteral(0),"expected fixed array length") {
+                    Ok(t)=>t,
+                    Err(e) => {
+                        self.no_struct_literal = outer;
+                        return e;
+                    }
+                };
+                if let TokenType::IntLiteral(i) = count.node {
+                    repeat = Some(AstNode::new(i as u32,count.pos,count.length));
+                }
+                break;
+            }
+            if self.expected(&TokenType::Comma) {
+                self.advance();
+            }
+            else if !self.expected(&TokenType::RightBracket) {
+                break;
+            }
+        }
+        self.no_struct_literal = outer;
+        let close = match self.eat(&TokenType::RightBracket,"expected closing bracket"){
+            Ok(t)=>t,
+            Err(e) => {return e;}
+        };
+        self.finish_node(marker);
+        let literal = match repeat {
+            Some(count) => ArrayLiteral::Repeat(Box::new(elements.remove(0)),count),
+            None => ArrayLiteral::Elements(elements)
+        };
+        AstNode::new(Node::ArrayLiteral(literal),open.pos,Self::span_length(open.pos, &close))
+    }
+
     fn parse_iden_init(&mut self,is_const : bool) -> AstNode<Node>{
+        let marker = self.start_node(SyntaxKind::VarDeclaration);
         let declaration_token: AstNode<TokenType>;
         //let start_pos : (u32,u32);
         if is_const {
@@ -1199,7 +2767,9 @@ impl<'source,'error_pipe,'tokenizer> ArkParser<'source,'error_pipe,'tokenizer> {
             };
         }
 
-        
+        if !is_const && self.expected(&TokenType::LeftParen) {
+            return self.parse_destructure(declaration_token,marker);
+        }
         let var_name_token = match self.eat(&TokenType::Identifier(String::new()),"expected an identifier") {
             Ok(t)=>t,
             Err(e) => {return e;}
@@ -1212,13 +2782,15 @@ impl<'source,'error_pipe,'tokenizer> ArkParser<'source,'error_pipe,'tokenizer> {
         };
         
 
-        match self.eat(&TokenType::Colon,"expected ':' for type declaration"){
-            Ok(t) => t,
-            Err(e) => {return e;}
-        };
-        let data_type = match self.parse_data_type() {
-            Ok(t)=>t,
-            Err(e) => {return e;}
+        let data_type = if self.expected(&TokenType::Colon) {
+            self.advance();
+            match self.parse_data_type() {
+                Ok(t)=>Some(t),
+                Err(e) => {return e;}
+            }
+        }
+        else {
+            None
         };
 
         let mut v = Var{
@@ -1228,11 +2800,8 @@ impl<'source,'error_pipe,'tokenizer> ArkParser<'source,'error_pipe,'tokenizer> {
                 var_name_token.pos,
                 var_name_token.length
             ),
-            var_type:AstNode::new(
-                data_type.node.clone(),
-                data_type.pos,
-                data_type.length
-            ),
+            var_type:data_type,
+            public:false,
         };
         if self.expected(&TokenType::AssignmentOperator){
             let operator = match self.eat(&TokenType::AssignmentOperator,"") {
@@ -1261,6 +2830,7 @@ impl<'source,'error_pipe,'tokenizer> ArkParser<'source,'error_pipe,'tokenizer> {
                     operator:operator,
                 }
             );
+            self.finish_node(marker);
             return AstNode::new(out,match &v.constant {
                 Some(c)=> c.pos, 
                 None => v.name.pos,
@@ -1271,6 +2841,7 @@ impl<'source,'error_pipe,'tokenizer> ArkParser<'source,'error_pipe,'tokenizer> {
             })
             
         }
+        self.finish_node(marker);
         return AstNode::new(
             Node::DeclareVar(v.clone()),
             match &v.constant {
@@ -1285,321 +2856,378 @@ impl<'source,'error_pipe,'tokenizer> ArkParser<'source,'error_pipe,'tokenizer> {
     }
 
     fn parse_iden(&mut self) -> AstNode<Node>{
-        let iden_token = match self.eat(&TokenType::Identifier(String::new()),"") {
-            Ok(t) => t,
-            Err(e) => {return e;}
-        };
-        
-        let iden = if let TokenType::Identifier(id) = iden_token.node {
-            id
-        }
-        else{
-            String::new()
-        };
+        let checkpoint = self.checkpoint();
+        let target = self.parse_primary();
         match &self.look_ahead.token{
-            TokenType::LeftParen => {
-                let mut func_call = FuncCall {function_name:AstNode::new(iden,iden_token.pos,iden_token.length), arguments:vec![]};
-                let open_p = match self.eat(&TokenType::LeftParen,"") {
+            TokenType::AssignmentOperator => {
+                let marker = self.start_node_at(checkpoint, SyntaxKind::Assignment);
+                let operator = match self.eat(&TokenType::AssignmentOperator,"") {
                     Ok(t)=>t,
                     Err(e) => {return e;}
                 };
+
+                let right = self.parse_primary();
+                self.finish_node(marker);
+                let out = Node::Assignment(BinExp {
+                    left: Box::from(target.clone()),
+                    right: Box::from(right.clone()), 
+                    operator:operator.clone(),
+                });
                 
-                while !self.expected(&TokenType::RightParen) {
-                    if self.expected(&TokenType::EOF) {
-                        //let er = self.raise_error("unclosed parenthesis");
-                        self.err_pipe.raise_error(crate::ErrorType::SyntaxError, "unclosed parenthesis", open_p.pos, 1);
-                        return AstNode::new(Node::ParserError(
-                            ParserError::new(
-                                ErrorType::SyntaxError,
-                                "unclosed parenthesis",
-                                open_p.pos
-                            )
-                        ),open_p.pos,1);
-                    }
-                    func_call.arguments.push(self.parse_primary());
-                    if self.expected(&TokenType::Comma) {
-                        self.eat(&TokenType::Comma,"");
-                    }
-                }
-                match self.eat(&TokenType::RightParen,"") {
-                    Ok(t)=>t,
-                    Err(e) => {return e;}
-                };
-                AstNode::new(Node::FunctionCall(func_call),iden_token.pos,iden_token.length+3)
-                // match self.var_map.get(&func_call.function_name){
-                //     Some(_) => Node::FunctionCall(func_call),
-                //     None => Node::ParserError(
-                //         self.raise_error(ErrorType::SemanticError, format!("use of undeclared function {}",&func_call.function_name).as_str())
-                //     ),
-                // }
+                AstNode::new(out,target.pos,target.length + operator.length + right.length)
             },
-            TokenType::AssignmentOperator => {
-                // let var_info = match self.var_map.get(&iden){
-                //     Some(info) => {
-                //         info
-                //     },
-                //     None => {
-                //         return  Node::ParserError(self.raise_error(ErrorType::SemanticError, format!("reassignment of undeclared variable {}",&iden).as_str()))
-                //     },
-                // };
-                let operator = match self.eat(&TokenType::AssignmentOperator,"") {
+            TokenType::AdditionAssign
+            | TokenType::SubtractionAssign
+            | TokenType::MultiplicationAssign
+            | TokenType::DivisionAssign
+            | TokenType::ModuloAssign
+            | TokenType::BitwiseAndAssign
+            | TokenType::BitwiseOrAssign
+            | TokenType::BitwiseXorAssign
+            | TokenType::ShiftLeftAssign
+            | TokenType::ShiftRightAssign => {
+                let marker = self.start_node_at(checkpoint, SyntaxKind::Assignment);
+                let operator = match self.eat(&self.look_ahead.token.clone(),"") {
                     Ok(t)=>t,
                     Err(e) => {return e;}
                 };
 
                 let right = self.parse_primary();
-                let out = Node::Assignment(BinExp {
-                    left: Box::from(AstNode::new(Node::Variable(iden),iden_token.pos,iden_token.length)),
+                self.finish_node(marker);
+                let out = Node::CompoundAssignment(BinExp {
+                    left: Box::from(target.clone()),
                     right: Box::from(right.clone()), 
                     operator:operator.clone(),
                 });
                 
-                AstNode::new(out,iden_token.pos,iden_token.length + operator.length + right.length)
+                AstNode::new(out,target.pos,target.length + operator.length + right.length)
             },
-            TokenType::Dot => {
-                // let mut caller = Node::Variable(match self.var_map.get(&iden) {
-                //     Some(var) => UseVar {var_ref:var},
-                //     None => return Node::ParserError(self.raise_error(ErrorType::SemanticError, format!("use of undeclared variable '{}'",&iden).as_str()))
-                // });
-                let mut caller = AstNode::new(Node::Variable(iden),iden_token.pos,iden_token.length);
-                while self.expected(&TokenType::Dot) {
-                    self.eat(&TokenType::Dot,"");
-                    let method_token = match self.eat(&TokenType::Identifier(String::new()),"expected field name or method") {
-                        Ok(t)=>t,
-                        Err(e) => {return e;}
-                    };
-                    let method_iden = if let TokenType::Identifier(id) = method_token.node {
-                        id
-                    }
-                    else{
-                        String::new()
-                    };
-                    let mut method_call = MethodCall { caller: None, method_name: AstNode::new(method_iden,method_token.pos,method_token.length), arguments: vec![] };
-                    match self.eat(&TokenType::LeftParen,"expected arguments") {
-                        Ok(t)=>t,
-                        Err(e) => {return e;}
-                    };
-                    while !self.expected(&TokenType::RightParen) {
-                        if self.expected(&TokenType::EOF){
-                            self.raise_error("unclosed parenthesis");
-                        }
-                        method_call.arguments.push(self.parse_primary());
-                        if self.expected(&TokenType::Comma) {
-                            self.eat(&TokenType::Comma,"");
-                        }
-                    }
-                    match self.eat(&TokenType::RightParen,"") {
-                        Ok(t)=>t,
-                        Err(e) => {return e;}
-                    };
-                    method_call.caller = Some(Box::from(caller));
-                    caller = AstNode::new(Node::MethodCall(method_call),method_token.pos,method_token.length);
-                    
-                }
-                caller
+            TokenType::Increment | TokenType::Decrement => {
+                let marker = self.start_node_at(checkpoint, SyntaxKind::Assignment);
+                let operator = match self.eat(&self.look_ahead.token.clone(),"") {
+                    Ok(t)=>t,
+                    Err(e) => {return e;}
+                };
+                self.finish_node(marker);
+                let out = Node::CompoundAssignment(BinExp {
+                    left: Box::from(target.clone()),
+                    right: Box::from(AstNode::new(Node::Literal(LiteralValue::Int(1)),operator.pos,operator.length)), 
+                    operator:operator.clone(),
+                });
+                
+                AstNode::new(out,target.pos,target.length + operator.length)
             },
             _ => {
-                AstNode::new(Node::Variable(iden),iden_token.pos,iden_token.length)
+                target
             },
         }
     }
-    fn parse_primary(&mut self) -> AstNode<Node> {
-        let mut left = self.parse_compose();
-        match &self.look_ahead.token{
-            TokenType::Or =>{
-                while self.expected(&TokenType::Or){
-                    let operator = match self.eat(&TokenType::Or,"") {
-                        Ok(t)=>t,
-                        Err(e) => {return e;}
-                    };
-                    let right = self.parse_compose();
-                    left = AstNode::new(
-                        Node::BinaryExpression(
-                            BinExp {
-                                left:Box::from(left.clone()),
-                                right:Box::from(right.clone()),
-                                operator:operator.clone()
-                            }
-                        ),
-                        left.pos,
-                        left.length + operator.length + right.length
-                    );
-                }
-            },
-            _ => {
 
-            }
-        };
-        return left;
+    fn prefix_binding_power(operator:&TokenType) -> Option<u8> {
+        match operator {
+            TokenType::SubtractionOperator | TokenType::Not | TokenType::BitwiseNot => Some(23),
+            // &x takes the address of x and *r reads through a reference
+            TokenType::BitwiseAnd | TokenType::MultiplicationOperator => Some(23),
+            _ => None
+        }
     }
 
-    fn parse_compose(&mut self) -> AstNode<Node> {
-        let mut left = self.parse_boolean_expression();
-        match &self.look_ahead.token{
-            TokenType::And =>{
-                while self.expected(&TokenType::And){
-                    let operator = match self.eat(&TokenType::And,"") {
-                        Ok(t)=>t,
-                        Err(e) => {return e;}
-                    };
-                    let right = self.parse_boolean_expression();
-                    left = AstNode::new(
-                        Node::BinaryExpression(
-                            BinExp {
-                                left:Box::from(left.clone()),
-                                right:Box::from(right.clone()),
-                                operator:operator.clone()
-                            }
-                        ),
-                        left.pos,
-                        left.length + operator.length + right.length
-                    );
-                }
-            },
-            _ => {
+    fn infix_binding_power(operator:&TokenType) -> Option<(u8,u8)> {
+        match operator {
+            TokenType::Range | TokenType::RangeInclusive => Some((1,2)),
+            TokenType::Or => Some((3,4)),
+            TokenType::And => Some((5,6)),
+            TokenType::BitwiseOr => Some((7,8)),
+            TokenType::BitwiseXor => Some((9,10)),
+            TokenType::BitwiseAnd => Some((11,12)),
+            TokenType::Equal | TokenType::NotEqual => Some((13,14)),
+            TokenType::Less | TokenType::LessEqual | TokenType::More | TokenType::MoreEqual => Some((15,16)),
+            TokenType::ShiftLeft | TokenType::ShiftRight => Some((17,18)),
+            TokenType::AdditionOperator | TokenType::SubtractionOperator => Some((19,20)),
+            TokenType::MultiplicationOperator | TokenType::DivisionOperator | TokenType::ModuloOperator => Some((21,22)),
+            _ => None
+        }
+    }
 
-            }
-        };
-        return left;
+    fn postfix_binding_power(operator:&TokenType) -> Option<u8> {
+        match operator {
+            TokenType::LeftParen | TokenType::LeftBracket | TokenType::Dot | TokenType::QuestionMark => Some(25),
+            // binds tighter than * but not as tight as a prefix operator, -x as i64 casts -x
+            TokenType::Keyword(KeyWords::AS) => Some(22),
+            _ => None
+        }
     }
 
-    fn parse_boolean_expression(&mut self) -> AstNode<Node> {
-        let mut left = self.parse_arithmatic();
-        loop{
-            match &self.look_ahead.token{
-                TokenType::More | TokenType::MoreEqual | TokenType::Equal | TokenType::Less | TokenType::LessEqual =>{
-                        let operator = match self.eat(&self.look_ahead.token.clone(),"") {
-                            Ok(t)=>t,
-                            Err(e) => {return e;}
-                        };
-                        let right = self.parse_arithmatic();
-                        left = AstNode::new(
-                            Node::BinaryExpression(
-                                BinExp {
-                                    left:Box::from(left.clone()),
-                                    right:Box::from(right.clone()),
-                                    operator:operator.clone()
-                                }
-                            ),
-                            left.pos,
-                            left.length + operator.length + right.length
-                        );
-                },
-                _ => {
+    fn span_length(start:(u32,u32),end:&AstNode<TokenType>) -> u32 {
+        if start.0 == end.pos.0 && end.pos.1 >= start.1 {
+            end.pos.1 + end.length - start.1
+        }
+        else {
+            end.length
+        }
+    }
+
+    fn parse_primary(&mut self) -> AstNode<Node> {
+        self.parse_expression(0)
+    }
+
+    fn parse_expression(&mut self,min_bp:u8) -> AstNode<Node> {
+        let checkpoint = self.checkpoint();
+        let mut left = self.parse_prefix();
+        loop {
+            let operator = self.look_ahead.token.clone();
+            if let Some(bp) = Self::postfix_binding_power(&operator) {
+                if bp < min_bp {
                     break;
                 }
+                left = self.parse_postfix(left, checkpoint);
+                continue;
+            }
+            let (left_bp,right_bp) = match Self::infix_binding_power(&operator) {
+                Some(bp) => bp,
+                None => break
+            };
+            if left_bp < min_bp {
+                break;
+            }
+            let is_range = matches!(operator,TokenType::Range | TokenType::RangeInclusive);
+            let kind = if is_range { SyntaxKind::Range } else { SyntaxKind::BinaryExpression };
+            let marker = self.start_node_at(checkpoint, kind);
+            let operator = match self.eat(&operator,"") {
+                Ok(t)=>t,
+                Err(e) => {return e;}
+            };
+            let right = self.parse_expression(right_bp);
+            self.finish_node(marker);
+            let length = left.length + operator.length + right.length;
+            left = if is_range {
+                AstNode::new(
+                    Node::Range(
+                        Range {
+                            start:Box::from(left.clone()),
+                            end:Box::from(right),
+                            inclusive:operator.node == TokenType::RangeInclusive,
+                        }
+                    ),
+                    left.pos,
+                    length
+                )
+            }
+            else {
+                AstNode::new(
+                    Node::BinaryExpression(
+                        BinExp {
+                            left:Box::from(left.clone()),
+                            right:Box::from(right),
+                            operator
+                        }
+                    ),
+                    left.pos,
+                    length
+                )
             };
         }
-        
-        return left;
+        left
     }
 
-    fn parse_arithmatic(&mut self) -> AstNode<Node> {
-        let mut left = self.parse_term();
-        match &self.look_ahead.token{
-            TokenType::Range =>{
-                if self.expected(&TokenType::Range){
-                    let operator = match self.eat(&TokenType::Range,"") {
-                        Ok(t)=>t,
-                        Err(e) => {return e;}
-                    };
-                    let right = self.parse_term();
-                    left = AstNode::new(
-                        Node::Range(
-                            Range {
-                                start:Box::from(left.clone()),
-                                end:Box::from(right.clone()),
-                            }
-                        ),
-                        left.pos,
-                        left.length + operator.length + right.length
-                    );
-                }
-            },
-            TokenType::AdditionOperator | TokenType::SubtractionOperator =>{
-                while self.expected(&TokenType::AdditionOperator) || self.expected(&TokenType::SubtractionOperator){
-                    let operator = match self.eat(&self.look_ahead.token.clone(),"") {
-                        Ok(t)=>t,
-                        Err(e) => {return e;}
-                    };
-                    let right = self.parse_term();
-                    left = AstNode::new(
-                        Node::BinaryExpression(
-                            BinExp {
-                                left:Box::from(left.clone()),
-                                right:Box::from(right.clone()),
-                                operator:operator.clone()
-                            }
-                        ),
-                        left.pos,
-                        left.length + operator.length + right.length
-                    );
-                }
-            },
-            _ => {
+    fn parse_arguments(&mut self) -> Result<Arguments,AstNode<Node>> {
+        let outer = mem::replace(&mut self.no_struct_literal, false);
+        let arguments = self.parse_argument_list();
+        self.no_struct_literal = outer;
+        arguments
+    }
 
+    fn parse_argument_list(&mut self) -> Result<Arguments,AstNode<Node>> {
+        let marker = self.start_node(SyntaxKind::ArgumentList);
+        let open_p = match self.eat(&TokenType::LeftParen,"expected arguments") {
+            Ok(t)=>t,
+            Err(e) => {return Err(e);}
+        };
+        let mut arguments = vec![];
+        while !self.expected(&TokenType::RightParen) {
+            if self.expected(&TokenType::EOF) {
+                self.report_error_at("unclosed parenthesis", open_p.pos, 1);
+                return Err(AstNode::new(Node::ParserError(
+                    ParserError::new(
+                        ErrorType::SyntaxError,
+                        "unclosed parenthesis",
+                        open_p.pos
+                    )
+                ),open_p.pos,1));
+            }
+            arguments.push(self.parse_primary());
+            if self.expected(&TokenType::Comma) {
+                self.advance();
+            }
+            else if !self.expected(&TokenType::RightParen) {
+                let er = self.raise_error("expected ',' or ')' after argument");
+                return Err(AstNode::new(Node::ParserError(er.clone()),er.pos,0));
             }
-
         }
-        
-        return left;
+        let close = match self.eat(&TokenType::RightParen,"unclosed parenthesis") {
+            Ok(t)=>t,
+            Err(e) => {return Err(e);}
+        };
+        self.finish_node(marker);
+        Ok((arguments,close))
     }
 
-    fn parse_term(&mut self) -> AstNode<Node> {
-        let mut left = self.parse_factor();
-        match self.look_ahead.token{
-            TokenType::MultiplicationOperator | TokenType::DivisionOperator | TokenType::ModuloOperator =>{
-                while self.expected(&TokenType::MultiplicationOperator) || self.expected(&TokenType::DivisionOperator) || self.expected(&TokenType::ModuloOperator){
-                    let operator = match self.eat(&self.look_ahead.token.clone(),"") {
-                        Ok(t)=>t,
+    fn parse_postfix(&mut self,left:AstNode<Node>,checkpoint:usize) -> AstNode<Node> {
+        match &self.look_ahead.token {
+            TokenType::LeftParen => {
+                let marker = self.start_node_at(checkpoint, SyntaxKind::FunctionCall);
+                if !matches!(left.node,Node::Variable(_)) {
+                    if let Some(tree) = &mut self.tree {
+                        tree.set_kind(SyntaxKind::ClosureCall);
+                    }
+                }
+                let (arguments,close) = match self.parse_arguments() {
+                    Ok(args) => args,
+                    Err(e) => {return e;}
+                };
+                self.finish_node(marker);
+                match left.node {
+                    Node::Variable(name) => {
+                        let func_call = FuncCall {
+                            function_name:AstNode::new(name,left.pos,left.length),
+                            arguments
+                        };
+                        AstNode::new(Node::FunctionCall(func_call),left.pos,Self::span_length(left.pos, &close))
+                    },
+                    _ => {
+                        let length = Self::span_length(left.pos, &close);
+                        let pos = left.pos;
+                        let closure_call = ClosureCall {
+                            callee:Box::new(left),
+                            arguments
+                        };
+                        AstNode::new(Node::ClosureCall(closure_call),pos,length)
+                    }
+                }
+            },
+            TokenType::Dot => {
+                let marker = self.start_node_at(checkpoint, SyntaxKind::FieldAccess);
+                self.advance();
+                // t.0 is the first element of a tuple, t.0.1 is read as the float 0.1
+                if let TokenType::IntLiteral(_) | TokenType::FloatLiteral(_) = &self.look_ahead.token {
+                    let index_token = self.look_ahead.clone();
+                    self.advance();
+                    self.finish_node(marker);
+                    let mut target = left;
+                    for index in index_token.text.split('.').map(String::from) {
+                        target = AstNode::new(
+                            Node::FieldAccess(FieldAccess {
+                                target: Box::from(target.clone()),
+                                field: AstNode::new(index,index_token.pos,index_token.length)
+                            }),
+                            target.pos,
+                            Self::span_length(target.pos, &AstNode::new(TokenType::Dot,index_token.pos,index_token.length))
+                        );
+                    }
+                    return target;
+                }
+                let member_token = match self.eat(&TokenType::Identifier(String::new()),"expected field name or method") {
+                    Ok(t)=>t,
+                    Err(e) => {return e;}
+                };
+                let member = if let TokenType::Identifier(id) = member_token.node {
+                    id
+                }
+                else{
+                    String::new()
+                };
+                if self.expected(&TokenType::LeftParen) {
+                    if let Some(tree) = &mut self.tree {
+                        tree.set_kind(SyntaxKind::MethodCall);
+                    }
+                    let (arguments,close) = match self.parse_arguments() {
+                        Ok(args) => args,
                         Err(e) => {return e;}
                     };
-                    let right = self.parse_factor();
-                    left = AstNode::new(
-                        Node::BinaryExpression(
-                            BinExp {
-                                left:Box::from(left.clone()),
-                                right:Box::from(right.clone()),
-                                operator:operator.clone()
-                            }
-                        ),
-                        left.pos,
-                        left.length + operator.length + right.length
-                    );
+                    self.finish_node(marker);
+                    let method_call = MethodCall {
+                        caller: Some(Box::from(left.clone())),
+                        method_name: AstNode::new(member,member_token.pos,member_token.length),
+                        arguments
+                    };
+                    return AstNode::new(Node::MethodCall(method_call),left.pos,Self::span_length(left.pos, &close));
                 }
+                self.finish_node(marker);
+                AstNode::new(
+                    Node::FieldAccess(FieldAccess {
+                        target: Box::from(left.clone()),
+                        field: AstNode::new(member,member_token.pos,member_token.length)
+                    }),
+                    left.pos,
+                    Self::span_length(left.pos, &AstNode::new(TokenType::Dot,member_token.pos,member_token.length))
+                )
             },
-            _ => {
-
-            }
-
+            TokenType::QuestionMark => {
+                let marker = self.start_node_at(checkpoint, SyntaxKind::Try);
+                let question = match self.eat(&TokenType::QuestionMark,"") {
+                    Ok(t)=>t,
+                    Err(e) => {return e;}
+                };
+                self.finish_node(marker);
+                let length = Self::span_length(left.pos, &question);
+                let pos = left.pos;
+                AstNode::new(Node::Try(NotExp { exp: Box::from(left) }),pos,length)
+            },
+            TokenType::Keyword(KeyWords::AS) => {
+                let marker = self.start_node_at(checkpoint, SyntaxKind::Cast);
+                self.advance();
+                let target = match self.parse_data_type() {
+                    Ok(t)=>t,
+                    Err(e) => {return e;}
+                };
+                self.finish_node(marker);
+                let length = Self::span_length(left.pos, &AstNode::new(TokenType::Keyword(KeyWords::AS),target.pos,target.length));
+                AstNode::new(
+                    Node::Cast(CastExp {
+                        value: Box::from(left.clone()),
+                        target
+                    }),
+                    left.pos,
+                    length
+                )
+            },
+            TokenType::LeftBracket => {
+                let marker = self.start_node_at(checkpoint, SyntaxKind::Index);
+                self.advance();
+                let index = self.parse_primary();
+                let close = match self.eat(&TokenType::RightBracket,"expected closing bracket") {
+                    Ok(t)=>t,
+                    Err(e) => {return e;}
+                };
+                self.finish_node(marker);
+                AstNode::new(
+                    Node::Index(IndexExp {
+                        target: Box::from(left.clone()),
+                        index: Box::from(index)
+                    }),
+                    left.pos,
+                    Self::span_length(left.pos, &close)
+                )
+            },
+            _ => left
         }
-        
-        return left;
-        
     }
 
-    fn parse_factor(&mut self) -> AstNode<Node> {
+    fn parse_prefix(&mut self) -> AstNode<Node> {
         let node = match &self.look_ahead.token.clone() {
             TokenType::Identifier(id) => {
-                // let var_info = match self.var_map.get(&id){
-                //     Some(info) => {
-                //         match info {
-                //             Node::DeclareVar(var) => {
-                //                 var
-                //             }
-                //             _ => {
-
-                //             }
-                //         }
-                //         println!("found {:#?}",info);
-                //         info
-                //     },
-                //     None => {
-                //         return (Node::ParserError(self.raise_error(ErrorType::SemanticError, format!("use of undeclared variable '{}'",&id).as_str())),DataType::Void)
-                //     },
-                // };
-                self.parse_iden()
-                //self.eat(&TokenType::Identifier(String::new()),"");
+                let checkpoint = self.checkpoint();
+                let iden = match self.eat(&TokenType::Identifier(String::new()),"") {
+                    Ok(t)=>t,
+                    Err(e) => {return e;}
+                };
+                if self.expected(&TokenType::LeftBrace) && !self.no_struct_literal {
+                    return self.parse_struct_literal(AstNode::new(id.clone(),iden.pos,iden.length),checkpoint);
+                }
+                if self.expected(&TokenType::ScopeResolution) {
+                    return self.parse_enum_value(AstNode::new(id.clone(),iden.pos,iden.length),checkpoint);
+                }
+                AstNode::new(Node::Variable(id.clone()),iden.pos,iden.length)
             },
             TokenType::IntLiteral(i) => {
                 let int = match self.eat(&TokenType::IntLiteral(0),"") {
@@ -1631,53 +3259,46 @@ impl<'source,'error_pipe,'tokenizer> ArkParser<'source,'error_pipe,'tokenizer> {
                 AstNode::new(Node::Literal(LiteralValue::Bool(*b)),boolean.pos,boolean.length)
             },
             TokenType::LeftParen =>{
-                self.eat(&TokenType::LeftParen,"");
-                let out = self.parse_primary();
-                match self.eat(&TokenType::RightParen,"unclosed parenthesis") {
+                self.parse_paren()
+            },
+            TokenType::LeftBracket => {
+                self.parse_array_literal()
+            },
+            TokenType::Keyword(KeyWords::MATCH) => {
+                self.parse_match()
+            },
+            TokenType::Keyword(KeyWords::LOOP) => {
+                let checkpoint = self.checkpoint();
+                self.parse_loop(None,checkpoint)
+            },
+            TokenType::Keyword(KeyWords::FUNC) => self.parse_function(FunctionKind::Closure),
+            TokenType::Label(_) => self.parse_labeled_loop(true),
+            operator if Self::prefix_binding_power(operator).is_some() => {
+                let bp = Self::prefix_binding_power(operator).unwrap();
+                let marker = self.start_node(SyntaxKind::UnaryExpression);
+                let operator_token = match self.eat(operator,"") {
                     Ok(t)=>t,
                     Err(e) => {return e;}
                 };
-                out
-            },
-            TokenType::Not =>{
-                self.eat(&TokenType::Not,"");
-                let out = self.parse_primary();
-                
-                AstNode::new(Node::BooleanNot(NotExp { exp: Box::new(out.clone()) }), out.pos, out.length)
-            },
-            TokenType::SubtractionOperator => {
-                self.eat(&TokenType::SubtractionOperator,"");
-                match self.look_ahead.token{
-                    TokenType::IntLiteral(i) => {
-                        let int = match self.eat(&TokenType::IntLiteral(0),"") {
-                            Ok(t)=>t,
-                            Err(e) => {return e;}
-                        };
-                        AstNode::new(Node::Literal(LiteralValue::Int(i*-1)),int.pos,int.length)
-                    },
-                    TokenType::FloatLiteral(f) => {
-                        let flt = match self.eat(&TokenType::FloatLiteral(0.0),"") {
-                            Ok(t)=>t,
-                            Err(e) => {return e;}
-                        };
-                        AstNode::new(Node::Literal(LiteralValue::Float(f*-1.0)),flt.pos,flt.length)
-                    },
-                    
-                    _ => {
-                        let er = self.raise_error("expected expression after '-' token");
-                        
-                        AstNode::new(
-                            Node::ParserError(
-                                er.clone()
-                            ),
-                            er.pos,
-                            0
-                        )
-                    }
-                }
-                
+                let mutable = *operator == TokenType::BitwiseAnd && self.expected(&TokenType::Keyword(KeyWords::MUT)) && self.eat(&TokenType::Keyword(KeyWords::MUT),"").is_ok();
+                let operand = self.parse_expression(bp);
+                self.finish_node(marker);
+                let length = match mutable {
+                    // &mut x spans up to the end of x
+                    true => Self::span_length(operator_token.pos, &AstNode::new(operator.clone(),operand.pos,operand.length)),
+                    false => operator_token.length + operand.length
+                };
+                let unary = match (operator,&operand.node) {
+                    (TokenType::SubtractionOperator,Node::Literal(LiteralValue::Int(i))) => Node::Literal(LiteralValue::Int(-i)),
+                    (TokenType::SubtractionOperator,Node::Literal(LiteralValue::Float(f))) => Node::Literal(LiteralValue::Float(-f)),
+                    (TokenType::SubtractionOperator,_) => Node::Negation(NotExp { exp: Box::new(operand) }),
+                    (TokenType::Not,_) => Node::BooleanNot(NotExp { exp: Box::new(operand) }),
+                    (TokenType::BitwiseAnd,_) => Node::Reference(Reference { target: Box::new(operand), mutable }),
+                    (TokenType::MultiplicationOperator,_) => Node::Deref(NotExp { exp: Box::new(operand) }),
+                    _ => Node::BitwiseNot(NotExp { exp: Box::new(operand) }),
+                };
+                AstNode::new(unary,operator_token.pos,length)
             },
-            
             _ => {
                 let er = self.raise_error("expected expression");
     
@@ -1699,5 +3320,17 @@ impl<'source,'error_pipe,'tokenizer> ArkParser<'source,'error_pipe,'tokenizer> {
         program
     }
 
+    // parses the program while also recording every consumed token, the tokenizer
+    // has to emit trivia for the resulting tree to reproduce the source exactly.
+    // the ast is still built by the parser next to the tree, deriving it from the
+    // tree is left for later
+    pub fn parse_lossless(&mut self) -> (Body,SyntaxNode) {
+        self.tree = Some(TreeBuilder::new());
+        let program = self.parse_body(TokenType::EOF);
+        self.advance();
+        let tree = self.tree.take().unwrap().finish();
+        (program,tree)
+    }
+
 }
 
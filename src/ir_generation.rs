@@ -1,62 +1,256 @@
+use std::cell::RefCell;
 use std::fmt::format;
 use std::rc::Rc;
 use std::str::FromStr;
-use crate::symbol_table::{self, SymbolTable, VarAttribute};
-use crate::tokenizer::{Token, TokenType};
-use crate::arkparser::{AstNode, Body, LiteralValue, Node};
+use crate::symbol_table::{CallingConvention, FuncAttribute, ModuleAttribute, Scope, SymbolTable, VarAttribute};
+use crate::tokenizer::{Array, DataType, TokenType};
+use crate::arkparser::{ArrayLiteral, AstNode, BinExp, Body, CastExp, ConditionalBlock, Destructure, FieldAccess, ForLoop, ImplBlock, IndexExp, InfiniteLoop, LiteralValue, MatchExp, Node, Pattern, Range, WhileLoop};
+
+// a property of a range that is either known while compiling or only held in a temporary
+enum RangeFlag {
+    Known(bool),
+    Runtime(String),
+}
 
 pub struct IRGenerator {
     symbol_table:Rc<SymbolTable>,
     code:String,
+    bounds_checks:bool,
+    // prefix of the functions of the module being generated, empty for the main file
+    module_name:String,
+    // the instances of the generic functions of the module
+    instances:Vec<AstNode<Node>>,
+    // closures and the adapters of named functions used as values, generated after the module
+    // since a function cannot be written inside another one
+    closures:RefCell<String>,
+    // named functions that already have an adapter
+    function_values:RefCell<Vec<String>>,
     //ir_table:IRBlock
 }
 
 impl IRGenerator{
     pub fn new(symbol_table:Rc<SymbolTable>) -> Self {
-        return IRGenerator { symbol_table, code: String::new() }
+        IRGenerator { symbol_table, code: String::new(), bounds_checks: false, module_name: String::new(), instances: vec![], closures: RefCell::new(String::new()), function_values: RefCell::new(vec![]) }
+    }
+    pub fn instances(&mut self,instances:Vec<AstNode<Node>>) -> &mut Self {
+        self.instances = instances;
+        self
+    }
+    pub fn module_name(&mut self,name:String) -> &mut Self {
+        self.module_name = name;
+        self
+    }
+    pub fn emit_bounds_checks(&mut self,enabled:bool) -> &mut Self {
+        self.bounds_checks = enabled;
+        self
     }
     pub fn get_intermediate_representation(&self,ast:&Body) -> String{
         let mut ir_code = String::new();
         for instruction in &ast.instructions {
-            ir_code += Self::gen_ir(&instruction,self.symbol_table.clone()).as_str();
+            ir_code += self.gen_ir(instruction,self.symbol_table.clone()).as_str();
+        }
+        for instance in &self.instances {
+            ir_code += self.gen_ir(instance,self.symbol_table.clone()).as_str();
         }
+        ir_code += self.closures.borrow().as_str();
         ir_code
     }
 
-    fn gen_ir(node:&AstNode<Node>,symbol_table:Rc<SymbolTable>) -> String {
+    fn gen_ir(&self,node:&AstNode<Node>,symbol_table:Rc<SymbolTable>) -> String {
         let mut code = String::new();
         match &node.node {
             Node::Body(_) => {
 
+            },
+            Node::Variable(v) if symbol_table.lookup_var(v.clone()).is_none() => {
+                if let Some((function,_)) = symbol_table.lookup_func(v.clone()) {
+                    code += self.gen_function_value(&function, &symbol_table).as_str();
+                }
+            },
+            Node::Variable(v) if Self::scalar_in_memory(v, &symbol_table) => {
+                let address = format!("{}{}",v,symbol_table.get_var_version(v.clone()));
+                code += format!("{} = load {}, 0\n",Self::new_temp(&symbol_table),address).as_str();
             },
             Node::Variable(v) => {
-                code += (v.clone() + symbol_table.get_var_version(v.clone()).to_string().as_str()).as_str()
+                code += (self.global_name(v, &symbol_table) + symbol_table.get_var_version(v.clone()).to_string().as_str()).as_str()
+            },
+            Node::Closure(func) => {
+                let name = symbol_table.closure_name(node.pos);
+                let mut function = func.clone();
+                function.function_name.node = name.clone();
+                let closure_code = self.gen_ir(&AstNode::new(Node::Function(function),node.pos,node.length), symbol_table.clone());
+                self.closures.borrow_mut().push_str(&closure_code);
+                let captures = symbol_table.lookup_func(name.clone()).map(|(f,_)| f.captures).unwrap_or_default();
+                code += self.gen_closure_record(&Self::qualified_name(&self.module_name, &name), &captures, &symbol_table).as_str();
+            },
+            Node::ClosureCall(call) => {
+                let function_type = Self::type_of(&call.callee, symbol_table.clone()).unwrap_or(DataType::Void);
+                code += self.gen_value_call(&call.callee, &call.arguments, &function_type, &symbol_table).as_str();
             },
             Node::DeclareVar(var) => {
-                code += (var.name.node.clone() + symbol_table.get_var_version(var.name.node.clone()).to_string().as_str()).as_str()
+                let var_type = symbol_table.lookup_var(var.name.node.clone()).and_then(|(var,_)| var.data_type);
+                if let Some(var_type) = var_type.filter(|t| Self::is_aggregate(t, &symbol_table) || Self::scalar_in_memory(&var.name.node, &symbol_table)) {
+                    let size = symbol_table.size_of(&var_type);
+                    code += format!("{}{} = alloc {}\n",var.name.node.clone(),symbol_table.consume_var_version(var.name.node.clone()),size).as_str();
+                    return code;
+                }
+                code += (self.global_name(&var.name.node, &symbol_table) + symbol_table.get_var_version(var.name.node.clone()).to_string().as_str()).as_str()
             },
             Node::Assignment(exp) => {
-                let right_expression = Self::gen_ir(&exp.right, symbol_table.clone());
-                let right = if right_expression.starts_with("tac_temp"){
+                let left_type = Self::type_of(&exp.left, symbol_table.clone());
+                let right_expression = self.gen_value(&exp.right, left_type.as_ref(), symbol_table.clone());
+                let mut right = if right_expression.starts_with("tac_temp"){
                     code += right_expression.as_str();
                     "tac_temp".to_string() + &symbol_table.get_var_version(String::from("tac_temp")).to_string()
                 }
                 else{
                     right_expression
                 };
+                // structs and arrays are values, assigning one that lives somewhere else copies it
+                let fresh_slice = matches!(&exp.right.node,Node::Index(index) if matches!(index.index.node,Node::Range(_)));
+                if let (Node::Variable(_) | Node::FieldAccess(_) | Node::Index(_) | Node::Deref(_),false) = (&exp.right.node,fresh_slice) {
+                    if let Some(aggregate) = Self::type_of(&exp.right, symbol_table.clone()).filter(|t| Self::is_aggregate(t, &symbol_table)) {
+                        if symbol_table.lookup_var(String::from("tac_temp")).is_none(){
+                            symbol_table.insert_var(String::from("tac_temp"));
+                        }
+                        let size = symbol_table.size_of(&aggregate);
+                        let copy = format!("tac_temp{}",symbol_table.consume_var_version(String::from("tac_temp")));
+                        code += format!("{} = alloc {}\ncopy {}, 0, {}, {}\n",copy,size,copy,right,size).as_str();
+                        right = copy;
+                    }
+                }
                 match &exp.left.node {
+                    Node::FieldAccess(access) => {
+                        let (address_code,base,offset,field_type) = match self.field_address(access, symbol_table.clone()) {
+                            Some(address) => address,
+                            None => return code
+                        };
+                        code += address_code.as_str();
+                        if Self::is_aggregate(&field_type, &symbol_table) {
+                            code += format!("copy {}, {}, {}, {}\n",base,offset,right,symbol_table.size_of(&field_type)).as_str();
+                        }
+                        else {
+                            code += format!("store {}, {}, {}\n",base,offset,right).as_str();
+                        }
+                    },
+                    Node::Index(index) => {
+                        let (address_code,base,offset,element_type) = match self.element_address(index, symbol_table.clone()) {
+                            Some(address) => address,
+                            None => return code
+                        };
+                        code += address_code.as_str();
+                        if Self::is_aggregate(&element_type, &symbol_table) {
+                            code += format!("copy {}, {}, {}, {}\n",base,offset,right,symbol_table.size_of(&element_type)).as_str();
+                        }
+                        else {
+                            code += format!("store {}, {}, {}\n",base,offset,right).as_str();
+                        }
+                    },
+                    Node::Deref(target) => {
+                        let reference_expression = self.gen_ir(&target.exp, symbol_table.clone());
+                        let reference = if reference_expression.starts_with("tac_temp"){
+                            code += reference_expression.as_str();
+                            "tac_temp".to_string() + &symbol_table.get_var_version(String::from("tac_temp")).to_string()
+                        }
+                        else {
+                            reference_expression
+                        };
+                        match left_type.filter(|t| Self::is_aggregate(t, &symbol_table)) {
+                            Some(aggregate) => code += format!("copy {}, 0, {}, {}\n",reference,right,symbol_table.size_of(&aggregate)).as_str(),
+                            None => code += format!("store {}, 0, {}\n",reference,right).as_str()
+                        }
+                    },
+                    // a variable a reference is taken to keeps its memory, the value is written into it
+                    Node::Variable(v) if symbol_table.lookup_var(v.clone()).is_some_and(|(var,_)| var.in_memory) => {
+                        let address = format!("{}{}",v,symbol_table.get_var_version(v.clone()));
+                        match left_type.filter(|t| Self::is_aggregate(t, &symbol_table)) {
+                            Some(aggregate) => code += format!("copy {}, 0, {}, {}\n",address,right,symbol_table.size_of(&aggregate)).as_str(),
+                            None => code += format!("store {}, 0, {}\n",address,right).as_str()
+                        }
+                    },
                     Node::Variable(v) => {
                         //let current_version = symbol_table.get_var_version(v.clone()).to_string().as_str();
-                        code += format!("{}{} = {}\n",v.clone(),symbol_table.consume_var_version(v.clone()),right).as_str()
+                        code += format!("{}{} = {}\n",self.global_name(v, &symbol_table),symbol_table.consume_var_version(v.clone()),right).as_str()
 
                     },
+                    Node::DeclareVar(var) if Self::scalar_in_memory(&var.name.node, &symbol_table) => {
+                        let address = format!("{}{}",var.name.node,symbol_table.consume_var_version(var.name.node.clone()));
+                        let size = left_type.as_ref().map(|t| symbol_table.size_of(t)).unwrap_or_default();
+                        code += format!("{} = alloc {}\nstore {}, 0, {}\n",address,size,address,right).as_str();
+                    },
                     Node::DeclareVar(var) => {
                         //let current_version = symbol_table.get_var_version(var.name.node.clone()).to_string().as_str();
-                        code += format!("{}{} = {}\n",var.name.node.clone(),symbol_table.consume_var_version(var.name.node.clone()),right).as_str();
+                        code += format!("{}{} = {}\n",self.global_name(&var.name.node, &symbol_table),symbol_table.consume_var_version(var.name.node.clone()),right).as_str();
                     },
                     _ => {}
                 }
             },
+            Node::CompoundAssignment(exp) => {
+                let operator = match exp.operator.node.compound_operator() {
+                    Some(op) => op,
+                    None => return code
+                };
+                // the place is resolved once, its address is loaded from and stored to,
+                // so an index or target with side effects runs a single time
+                let (address_code,base,offset) = match &exp.left.node {
+                    Node::FieldAccess(access) => match self.field_address(access, symbol_table.clone()) {
+                        Some((address_code,base,offset,_)) => (address_code,base,offset.to_string()),
+                        None => return code
+                    },
+                    Node::Index(index) => match self.element_address(index, symbol_table.clone()) {
+                        Some((address_code,base,offset,_)) => (address_code,base,offset),
+                        None => return code
+                    },
+                    Node::Deref(target) => {
+                        let reference_expression = self.gen_ir(&target.exp, symbol_table.clone());
+                        if reference_expression.starts_with("tac_temp"){
+                            let reference = "tac_temp".to_string() + &symbol_table.get_var_version(String::from("tac_temp")).to_string();
+                            (reference_expression,reference,"0".to_string())
+                        }
+                        else {
+                            (String::new(),reference_expression,"0".to_string())
+                        }
+                    },
+                    Node::Variable(v) if symbol_table.lookup_var(v.clone()).is_some_and(|(var,_)| var.in_memory) => {
+                        (String::new(),format!("{}{}",v,symbol_table.get_var_version(v.clone())),"0".to_string())
+                    },
+                    _ => {
+                        let read_modify_write = AstNode::new(
+                            Node::Assignment(BinExp {
+                                left: exp.left.clone(),
+                                right: Box::new(AstNode::new(
+                                    Node::BinaryExpression(BinExp {
+                                        left: exp.left.clone(),
+                                        right: exp.right.clone(),
+                                        operator: AstNode::new(operator, exp.operator.pos, exp.operator.length)
+                                    }),
+                                    node.pos,
+                                    node.length
+                                )),
+                                operator: AstNode::new(TokenType::AssignmentOperator, exp.operator.pos, exp.operator.length)
+                            }),
+                            node.pos,
+                            node.length
+                        );
+                        code += self.gen_ir(&read_modify_write, symbol_table.clone()).as_str();
+                        return code;
+                    }
+                };
+                code += address_code.as_str();
+                let current = Self::new_temp(&symbol_table);
+                code += format!("{} = load {}, {}\n",current,base,offset).as_str();
+                let right_expression = self.gen_ir(&exp.right, symbol_table.clone());
+                let right = if right_expression.starts_with("tac_temp"){
+                    code += right_expression.as_str();
+                    "tac_temp".to_string() + &symbol_table.get_var_version(String::from("tac_temp")).to_string()
+                }
+                else {
+                    right_expression
+                };
+                let result = Self::new_temp(&symbol_table);
+                let op = Self::binary_op(&operator, Self::type_of(&exp.left, symbol_table.clone()));
+                code += format!("{} = {} {}, {}\nstore {}, {}, {}\n",result,op,current,right,base,offset,result).as_str();
+            },
             Node::Literal(l) => {
                 match l {
                     LiteralValue::Int(i) => {
@@ -66,7 +260,8 @@ impl IRGenerator{
                         code += &f.to_string();
                     },
                     LiteralValue::Str(s) => {
-                        code += s;
+                        // a string literal is a slice of bytes in static memory
+                        code += format!("{} = str \"{}\"\n",Self::new_temp(&symbol_table),s).as_str();
                     },
                     LiteralValue::Bool(b) => {
                         code += &b.to_string();
@@ -74,7 +269,7 @@ impl IRGenerator{
                 }
             },
             Node::BinaryExpression(binexp) => {
-                let left_expression = IRGenerator::gen_ir(&binexp.left, symbol_table.clone());
+                let left_expression = self.gen_ir(&binexp.left, symbol_table.clone());
                 let left = if left_expression.starts_with("tac_temp"){
                     code = left_expression;
                     "tac_temp".to_string() + &symbol_table.get_var_version(String::from("tac_temp")).to_string()
@@ -82,7 +277,7 @@ impl IRGenerator{
                 else {
                     left_expression
                 };
-                let right_expression = IRGenerator::gen_ir(&binexp.right, symbol_table.clone());
+                let right_expression = self.gen_ir(&binexp.right, symbol_table.clone());
                 let right = if right_expression.starts_with("tac_temp"){
                     code += right_expression.as_str();
                     "tac_temp".to_string() + &symbol_table.get_var_version(String::from("tac_temp")).to_string()
@@ -93,57 +288,167 @@ impl IRGenerator{
                 if symbol_table.lookup_var(String::from("tac_temp")).is_none(){
                     symbol_table.insert_var(String::from("tac_temp"));
                 }
-                let op = match &binexp.operator.node {
-                    TokenType::AdditionOperator => "add",
-                    TokenType::SubtractionOperator => "sub",
-                    TokenType::MultiplicationOperator => "mul",
-                    TokenType::DivisionOperator => "div",  // Valid division operator
-                    TokenType::ModuloOperator => "mod",  // Valid modulo operator
-                    TokenType::Equal => "equ",
-                    TokenType::Less => "l",
-                    TokenType::LessEqual => "le",
-                    TokenType::More => "m",
-                    TokenType::MoreEqual => "me",
-                    TokenType::And => "and",
-                    TokenType::Or => "or",
-                    TokenType::Not => "not",
-                    _ => {""}
-                };
+                let op = Self::binary_op(&binexp.operator.node, Self::type_of(&binexp.left, symbol_table.clone()));
                 code += format!("tac_temp{} = {} {}, {}\n",symbol_table.consume_var_version(String::from("tac_temp")),op, left,right).as_str();
             },
+            Node::Function(func) if func.external => {
+                // a symbol of a linked C library, called with the C convention under its own name
+                code += format!("@extern \"C\" {} {}({})\n",func.return_type.node.to_string(),func.function_name.node,func.parameters.iter().map(|p| format!("{} {}",p.node.var_type.as_ref().map(|t| t.node.to_string()).unwrap_or_default(),p.node.name.node)).collect::<Vec<String>>().join(", ")).as_str();
+            },
+            // generated once for every instance instead
+            Node::Function(func) if !func.type_parameters.is_empty() => (),
+            // a trait only has signatures, the methods of an impl block are functions named Type::method
+            Node::Trait(_) => (),
+            Node::Impl(block) => {
+                let target = symbol_table.source_type(&block.target.node);
+                for method in &block.methods {
+                    let Node::Function(func) = &method.node else { continue };
+                    let mut function = func.clone();
+                    function.function_name.node = ImplBlock::method_name(&target, &func.function_name.node);
+                    code += self.gen_ir(&AstNode::new(Node::Function(function),method.pos,method.length), symbol_table.clone()).as_str();
+                }
+            },
             Node::Function(func) => {
-                code += format!("@defined {} {}({}):\n",func.return_type.node.to_string(),func.function_name.node.clone(),func.parameters.iter().map(|p| format!("{} {}",p.node.var_type.node.to_string(),p.node.name.node)).collect::<Vec<String>>().join(", ")).as_str();
+                // the backend links these against its runtime
+                let header = if func.runtime { "@runtime" } else { "@defined" };
+                let function = symbol_table.lookup_func(func.function_name.node.clone()).map(|(f,_)| f);
+                let bindings = function.as_ref().map(|f| f.type_bindings.clone()).unwrap_or_default();
+                let mut parameters = func.parameters.iter().map(|p| format!("{} {}",p.node.var_type.as_ref().map(|t| t.node.substitute(&bindings).to_string()).unwrap_or_default(),p.node.name.node)).collect::<Vec<String>>();
+                // only a closure is declared inside another function, it gets its record as $env
+                let closure = symbol_table.scope != Scope::Global;
+                if let (true,Some(function)) = (closure,&function) {
+                    parameters.insert(0, format!("{} $env",Self::function_type(function).to_string()));
+                }
+                code += format!("{} {} {}({}){}\n",header,func.return_type.node.substitute(&bindings).to_string(),Self::qualified_name(&self.module_name, &func.function_name.node),parameters.join(", "),if func.runtime { "" } else { ":" }).as_str();
+                if func.runtime {
+                    return code;
+                }
                 let func_sym = symbol_table.lookup_func(func.function_name.node.clone()).unwrap().0.func_table.clone();
+                // counters live in the function scope so the block scopes inside share them,
+                // a closure counts on its own
+                for counter in ["tac_temp","tac_label","match_result"] {
+                    if !matches!(func_sym.lookup_var(String::from(counter)),Some((_,scope)) if scope == func_sym.scope){
+                        func_sym.insert_var(String::from(counter));
+                    }
+                }
+                // the captured values are read out of the record first
+                if let (true,Some(function)) = (closure,&function) {
+                    let (offsets,_) = Self::capture_layout(&function.captures, &symbol_table);
+                    for ((capture_type,capture_name),offset) in function.captures.iter().zip(offsets) {
+                        // a copy a reference is taken to stays in the record
+                        let operation = if Self::is_aggregate(capture_type, &symbol_table) || Self::scalar_in_memory(capture_name, &func_sym) { "add" } else { "load" };
+                        code += format!("    {}{} = {} $env, {}\n",capture_name,func_sym.consume_var_version(capture_name.clone()),operation,offset).as_str();
+                    }
+                }
+                // parameters a reference is taken to are moved into memory
+                for param in &func.parameters {
+                    let name = &param.node.name.node;
+                    if !Self::scalar_in_memory(name, &func_sym) {
+                        continue;
+                    }
+                    let size = func_sym.lookup_var(name.clone()).and_then(|(var,_)| var.size).unwrap_or_default();
+                    let address = format!("{}{}",name,func_sym.consume_var_version(name.clone()));
+                    code += format!("    {} = alloc {}\n    store {}, 0, {}0\n",address,size,address,name).as_str();
+                }
                 for instruction in &func.body.instructions{
-                    code += (Self::gen_ir(instruction, func_sym.clone()).lines().map(|i| "    ".to_string() + i).collect::<Vec<String>>().join("\n") + "\n").as_str();
+                    code += (self.gen_ir(instruction, func_sym.clone()).lines().map(|i| "    ".to_string() + i).collect::<Vec<String>>().join("\n") + "\n").as_str();
                 }
             },
-            Node::FunctionCall(fun) => {
-                let mut arguments:Vec<String> = vec![];
-                for arg in &fun.arguments{
-                    let argument_expression = Self::gen_ir(arg, symbol_table.clone());
-                    let argument = if argument_expression.starts_with("tac_temp"){
-                        code += argument_expression.as_str();
-                        
-                        "tac_temp".to_string() + &symbol_table.get_var_version(String::from("tac_temp")).to_string()
-                    }
-                    else {
-                        argument_expression
-                    };
-                    arguments.push(argument);
+            Node::FunctionCall(fun) if fun.function_name.node == "len" && symbol_table.lookup_func(fun.function_name.node.clone()).is_none() => {
+                let argument = match fun.arguments.first() {
+                    Some(argument) => argument,
+                    None => return code
+                };
+                // the length of an array is known while compiling
+                if let Some(DataType::Array(arr)) = Self::type_of(argument, symbol_table.clone()) {
+                    code += &arr.length.to_string();
+                    return code;
                 }
-                
-                if symbol_table.lookup_var(String::from("tac_temp")).is_none(){
-                    symbol_table.insert_var(String::from("tac_temp"));
+                let slice_expression = self.gen_ir(argument, symbol_table.clone());
+                let slice = if slice_expression.starts_with("tac_temp"){
+                    code += slice_expression.as_str();
+                    "tac_temp".to_string() + &symbol_table.get_var_version(String::from("tac_temp")).to_string()
+                }
+                else {
+                    slice_expression
+                };
+                code += format!("{} = load {}, {}\n",Self::new_temp(&symbol_table),slice,DataType::U64.get_size_in_bytes()).as_str();
+            },
+            Node::FunctionCall(fun) if Self::is_heap_call(&fun.function_name.node, &symbol_table) => {
+                let argument = match fun.arguments.first() {
+                    Some(argument) => argument,
+                    None => return code
+                };
+                let value_expression = self.gen_value(argument, None, symbol_table.clone());
+                let value = if value_expression.starts_with("tac_temp"){
+                    code += value_expression.as_str();
+                    "tac_temp".to_string() + &symbol_table.get_var_version(String::from("tac_temp")).to_string()
+                }
+                else {
+                    value_expression
+                };
+                if fun.function_name.node == "free" {
+                    code += format!("free {}\n",value).as_str();
+                    return code;
+                }
+                // the value is copied into a block of the heap that lives until it is freed
+                let value_type = Self::type_of(argument, symbol_table.clone()).unwrap_or(DataType::Void);
+                let size = symbol_table.size_of(&value_type);
+                let address = Self::new_temp(&symbol_table);
+                code += format!("{} = malloc {}\n",address,size).as_str();
+                if Self::is_aggregate(&value_type, &symbol_table) {
+                    code += format!("copy {}, 0, {}, {}\n",address,value,size).as_str();
+                }
+                else {
+                    code += format!("store {}, 0, {}\n",address,value).as_str();
+                }
+            },
+            Node::FunctionCall(fun) if Self::holds_function(&fun.function_name.node, &symbol_table) => {
+                let callee = AstNode::new(Node::Variable(fun.function_name.node.clone()),fun.function_name.pos,fun.function_name.length);
+                let function_type = Self::type_of(&callee, symbol_table.clone()).unwrap_or(DataType::Void);
+                code += self.gen_value_call(&callee, &fun.arguments, &function_type, &symbol_table).as_str();
+            },
+            Node::FunctionCall(fun) => {
+                let function_name = symbol_table.lookup_generic_call(fun.function_name.pos).unwrap_or(fun.function_name.node.clone());
+                let function = symbol_table.lookup_func(function_name.clone()).map(|(f,_)| f);
+                let parameters = function.as_ref().map(|f| f.parameter.clone()).unwrap_or_default();
+                let name = match function {
+                    Some(f) if f.calling_convention == CallingConvention::C => f.func_name,
+                    _ => Self::qualified_name(&self.module_name, &function_name)
+                };
+                code += self.gen_call(&name, &fun.arguments, &parameters, symbol_table.clone()).as_str();
+            },
+            Node::MethodCall(call) => {
+                let caller = match &call.caller {
+                    Some(caller) => caller,
+                    None => return code
                 };
-                code += format!("{}{} = call {} {}\n","tac_temp",symbol_table.consume_var_version(String::from("tac_temp")),fun.function_name.node,arguments.join(", ")).as_str()
+                let receiver = match Self::type_of(caller, symbol_table.clone()) {
+                    Some(t) => t,
+                    None => return code
+                };
+                let (function,module) = match Self::method_function(&receiver, &call.method_name.node, &symbol_table) {
+                    Some(f) => f,
+                    None => return code
+                };
+                let (name,parameters) = match &module {
+                    Some(module) => (
+                        Self::qualified_name(&module.module_name, &function.func_name),
+                        function.parameter.iter().map(|(t,n)| (symbol_table.import_type(module, t),n.clone())).collect::<Vec<(DataType,String)>>()
+                    ),
+                    None => (Self::qualified_name(&self.module_name, &function.func_name),function.parameter.clone())
+                };
+                // the receiver is passed as the first argument
+                let arguments = [caller.as_ref().clone()].into_iter().chain(call.arguments.iter().cloned()).collect::<Vec<AstNode<Node>>>();
+                code += self.gen_call(&name, &arguments, &parameters, symbol_table.clone()).as_str();
             },
-            Node::MethodCall(_) => todo!(),
-            Node::Import(_) => todo!(),
+            // the imported module is generated on its own
+            Node::Import(_) => (),
             Node::Return(exp) => {
                 match exp {
                     Some(ex) => {
-                        let ret_expression = IRGenerator::gen_ir(&ex, symbol_table.clone());
+                        let return_type = symbol_table.get_current_func_info().map(|f| f.return_type);
+                        let ret_expression = self.gen_value(ex, return_type.as_ref(), symbol_table.clone());
                         let ret_value = if ret_expression.starts_with("tac_temp"){
                             code = ret_expression;
                             "tac_temp".to_string() + &symbol_table.get_var_version(String::from("tac_temp")).to_string()
@@ -158,17 +463,1669 @@ impl IRGenerator{
                     }
                 };
             },
-            Node::Conditional(_) => todo!(),
-            Node::For(_) => todo!(),
-            Node::While(_) => todo!(),
-            Node::BooleanNot(_) => todo!(),
-            Node::Tuple(_) => todo!(),
-            Node::Range(_) => todo!(),
+            Node::Conditional(con) => {
+                code += self.gen_conditional(con, symbol_table.clone()).as_str();
+            },
+            Node::For(f) => {
+                code += self.gen_for(f, symbol_table.clone()).as_str();
+            },
+            Node::While(w) => {
+                code += self.gen_while(w, symbol_table.clone()).as_str();
+            },
+            Node::Loop(l) => {
+                code += self.gen_loop(l, symbol_table.clone()).as_str();
+            },
+            Node::Break(exit) | Node::Continue(exit) => {
+                let loop_attribute = match symbol_table.lookup_loop(exit.label.as_ref().map(|l| l.node.clone())) {
+                    Some(loop_attribute) => loop_attribute,
+                    None => return code
+                };
+                let target = if let Node::Break(_) = &node.node {
+                    loop_attribute.exit_label
+                }
+                else {
+                    loop_attribute.latch_label
+                };
+                if let (Some(value),Some(result)) = (&exit.value,&loop_attribute.result) {
+                    let value_expression = self.gen_value(value, loop_attribute.value_type.as_ref(), symbol_table.clone());
+                    let value = if value_expression.starts_with("tac_temp"){
+                        code += value_expression.as_str();
+                        "tac_temp".to_string() + &symbol_table.get_var_version(String::from("tac_temp")).to_string()
+                    }
+                    else {
+                        value_expression
+                    };
+                    code += format!("{} = {}\n",result,value).as_str();
+                }
+                code += Self::gen_sync(&loop_attribute.versions, &symbol_table).as_str();
+                code += format!("goto {}\n",target.unwrap_or_default()).as_str();
+            },
+            Node::BooleanNot(exp) | Node::BitwiseNot(exp) | Node::Negation(exp) => {
+                let operand_expression = self.gen_ir(&exp.exp, symbol_table.clone());
+                let operand = if operand_expression.starts_with("tac_temp"){
+                    code += operand_expression.as_str();
+                    "tac_temp".to_string() + &symbol_table.get_var_version(String::from("tac_temp")).to_string()
+                }
+                else {
+                    operand_expression
+                };
+                if symbol_table.lookup_var(String::from("tac_temp")).is_none(){
+                    symbol_table.insert_var(String::from("tac_temp"));
+                }
+                let op = match &node.node {
+                    Node::BooleanNot(_) => "not",
+                    Node::BitwiseNot(_) => "bnot",
+                    _ => "neg"
+                };
+                code += format!("tac_temp{} = {} {}\n",symbol_table.consume_var_version(String::from("tac_temp")),op,operand).as_str();
+            },
+            // &*r is r itself
+            Node::Reference(reference) if matches!(reference.target.node,Node::Deref(_)) => {
+                if let Node::Deref(target) = &reference.target.node {
+                    code += self.gen_ir(&target.exp, symbol_table.clone()).as_str();
+                }
+            },
+            Node::Reference(reference) => {
+                let (address_code,base,offset) = match &reference.target.node {
+                    Node::Variable(v) => (String::new(),format!("{}{}",v,symbol_table.get_var_version(v.clone())),String::from("0")),
+                    Node::FieldAccess(access) => match self.field_address(access, symbol_table.clone()) {
+                        Some((address_code,base,offset,_)) => (address_code,base,offset.to_string()),
+                        None => return code
+                    },
+                    Node::Index(index) => match self.element_address(index, symbol_table.clone()) {
+                        Some((address_code,base,offset,_)) => (address_code,base,offset),
+                        None => return code
+                    },
+                    _ => return code
+                };
+                code += address_code.as_str();
+                code += format!("{} = addr {}, {}\n",Self::new_temp(&symbol_table),base,offset).as_str();
+            },
+            // a reference to a struct or array is its address already, a scalar is loaded
+            Node::Deref(exp) => {
+                let reference_expression = self.gen_ir(&exp.exp, symbol_table.clone());
+                if Self::type_of(node, symbol_table.clone()).is_some_and(|t| Self::is_aggregate(&t, &symbol_table)) {
+                    code += reference_expression.as_str();
+                    return code;
+                }
+                let reference = if reference_expression.starts_with("tac_temp"){
+                    code += reference_expression.as_str();
+                    "tac_temp".to_string() + &symbol_table.get_var_version(String::from("tac_temp")).to_string()
+                }
+                else {
+                    reference_expression
+                };
+                code += format!("{} = load {}, 0\n",Self::new_temp(&symbol_table),reference).as_str();
+            },
+            Node::FieldAccess(access) => {
+                let (address_code,base,offset,field_type) = match self.field_address(access, symbol_table.clone()) {
+                    Some(address) => address,
+                    None => return code
+                };
+                code += address_code.as_str();
+                if symbol_table.lookup_var(String::from("tac_temp")).is_none(){
+                    symbol_table.insert_var(String::from("tac_temp"));
+                }
+                // a nested struct or array is stored inline, reading it gives its address
+                let op = if Self::is_aggregate(&field_type, &symbol_table) {
+                    "add"
+                }
+                else {
+                    "load"
+                };
+                code += format!("tac_temp{} = {} {}, {}\n",symbol_table.consume_var_version(String::from("tac_temp")),op,base,offset).as_str();
+            },
+            Node::Struct(def) => {
+                let struct_attribute = match symbol_table.lookup_struct(def.struct_name.node.clone()) {
+                    Some(s) => s,
+                    None => return code
+                };
+                code += format!("@struct {} {}:\n",Self::qualified_name(&self.module_name, &struct_attribute.struct_name),struct_attribute.size).as_str();
+                for (field_type,field_name,offset) in &struct_attribute.fields {
+                    code += format!("    {} {} @{}\n",field_type.to_string(),field_name,offset).as_str();
+                }
+            },
+            Node::StructLiteral(literal) => {
+                let struct_attribute = match symbol_table.lookup_struct(literal.struct_name.node.clone()) {
+                    Some(s) => s,
+                    None => return code
+                };
+                // field values are computed before the allocation so the allocated
+                // address stays the latest temporary
+                let mut stores:Vec<(u32,String,Option<u32>)> = vec![];
+                for (field_type,field_name,offset) in &struct_attribute.fields {
+                    let value = match literal.fields.iter().find(|(name,_)| &name.node == field_name) {
+                        Some((_,value)) => value,
+                        None => continue
+                    };
+                    let value_expression = self.gen_value(value, Some(field_type), symbol_table.clone());
+                    let value = if value_expression.starts_with("tac_temp"){
+                        code += value_expression.as_str();
+                        "tac_temp".to_string() + &symbol_table.get_var_version(String::from("tac_temp")).to_string()
+                    }
+                    else {
+                        value_expression
+                    };
+                    let copy_size = if Self::is_aggregate(field_type, &symbol_table) {
+                        Some(symbol_table.size_of(field_type))
+                    }
+                    else {
+                        None
+                    };
+                    stores.push((*offset,value,copy_size));
+                }
+                if symbol_table.lookup_var(String::from("tac_temp")).is_none(){
+                    symbol_table.insert_var(String::from("tac_temp"));
+                }
+                let address = format!("tac_temp{}",symbol_table.consume_var_version(String::from("tac_temp")));
+                code += format!("{} = alloc {}\n",address,struct_attribute.size).as_str();
+                for (offset,value,copy_size) in stores {
+                    match copy_size {
+                        Some(size) => code += format!("copy {}, {}, {}, {}\n",address,offset,value,size).as_str(),
+                        None => code += format!("store {}, {}, {}\n",address,offset,value).as_str()
+                    }
+                }
+            },
+            Node::Enum(def) => {
+                let enum_attribute = match symbol_table.lookup_enum(def.enum_name.node.clone()) {
+                    Some(e) => e,
+                    None => return code
+                };
+                code += format!("@enum {} {}:\n",enum_attribute.enum_name,symbol_table.size_of(&DataType::Enum(enum_attribute.enum_name.clone()))).as_str();
+                for (tag,(variant,payload)) in enum_attribute.variants.iter().enumerate() {
+                    if payload.is_empty() {
+                        code += format!("    {} {}\n",tag,variant).as_str();
+                    }
+                    else {
+                        code += format!("    {} {}({})\n",tag,variant,payload.iter().map(|t| t.to_string()).collect::<Vec<String>>().join(", ")).as_str();
+                    }
+                }
+            },
+            Node::EnumValue(value) => {
+                if let Some(module) = symbol_table.lookup_module(value.enum_name.node.clone()) {
+                    let module_table = match &module.module_table {
+                        Some(t) => t,
+                        None => return code
+                    };
+                    let name = Self::qualified_name(&module.module_name, &value.variant.node);
+                    // the constants of a module are its globals, generated before this module
+                    let function = match module_table.lookup_func(value.variant.node.clone()) {
+                        Some((f,_)) => f,
+                        None => {
+                            code += format!("{}{}",name,module_table.get_var_version(value.variant.node.clone())).as_str();
+                            return code;
+                        }
+                    };
+                    let parameters = function.parameter.iter()
+                        .map(|(param_type,param_name)| (symbol_table.import_type(&module, param_type),param_name.clone()))
+                        .collect::<Vec<(DataType,String)>>();
+                    let name = if function.calling_convention == CallingConvention::C { function.func_name } else { name };
+                    code += self.gen_call(&name, &value.arguments, &parameters, symbol_table.clone()).as_str();
+                    return code;
+                }
+                if let Some(function) = Self::associated_function(value, &symbol_table) {
+                    let name = Self::qualified_name(&self.module_name, &function.func_name);
+                    code += self.gen_call(&name, &value.arguments, &function.parameter, symbol_table.clone()).as_str();
+                    return code;
+                }
+                // Option and Result take their layout from the type the analyzer settled on
+                let enum_attribute = match symbol_table.lookup_enum(value.enum_name.node.clone()).or_else(|| symbol_table.enum_of(&symbol_table.lookup_variant_type(node.pos)?)) {
+                    Some(e) => e,
+                    None => return code
+                };
+                let (tag,payload) = match enum_attribute.get_variant(&value.variant.node) {
+                    Some(v) => v,
+                    None => return code
+                };
+                // an enum without payloads is just its tag
+                if !enum_attribute.has_payload() {
+                    code += &tag.to_string();
+                    return code;
+                }
+                let mut stores:Vec<(u32,String,Option<u32>)> = vec![];
+                let mut offset = DataType::I32.get_size_in_bytes();
+                for (payload_type,argument) in payload.iter().zip(value.arguments.iter()) {
+                    let argument_expression = self.gen_value(argument, Some(payload_type), symbol_table.clone());
+                    let argument = if argument_expression.starts_with("tac_temp"){
+                        code += argument_expression.as_str();
+                        "tac_temp".to_string() + &symbol_table.get_var_version(String::from("tac_temp")).to_string()
+                    }
+                    else {
+                        argument_expression
+                    };
+                    let size = symbol_table.size_of(payload_type);
+                    let copy_size = if Self::is_aggregate(payload_type, &symbol_table) {
+                        Some(size)
+                    }
+                    else {
+                        None
+                    };
+                    stores.push((offset,argument,copy_size));
+                    offset += size;
+                }
+                if symbol_table.lookup_var(String::from("tac_temp")).is_none(){
+                    symbol_table.insert_var(String::from("tac_temp"));
+                }
+                let address = format!("tac_temp{}",symbol_table.consume_var_version(String::from("tac_temp")));
+                code += format!("{} = alloc {}\nstore {}, 0, {}\n",address,enum_attribute.size,address,tag).as_str();
+                for (offset,value,copy_size) in stores {
+                    match copy_size {
+                        Some(size) => code += format!("copy {}, {}, {}, {}\n",address,offset,value,size).as_str(),
+                        None => code += format!("store {}, {}, {}\n",address,offset,value).as_str()
+                    }
+                }
+            },
+            Node::Match(m) => {
+                code += self.gen_match(m, symbol_table.clone()).as_str();
+            },
+            Node::Index(index) if matches!(index.index.node,Node::Range(_)) => {
+                code += self.gen_slice(index, symbol_table.clone()).as_str();
+            },
+            Node::Index(index) => {
+                let (address_code,base,offset,element_type) = match self.element_address(index, symbol_table.clone()) {
+                    Some(address) => address,
+                    None => return code
+                };
+                code += address_code.as_str();
+                let op = if Self::is_aggregate(&element_type, &symbol_table) { "add" } else { "load" };
+                code += format!("{} = {} {}, {}\n",Self::new_temp(&symbol_table),op,base,offset).as_str();
+            },
+            Node::ArrayLiteral(literal) => {
+                code += self.gen_array_literal(literal, None, symbol_table.clone()).as_str();
+            },
+            Node::Cast(cast) => {
+                code += self.gen_cast(cast, symbol_table.clone()).as_str();
+            },
+            Node::Try(t) => {
+                code += self.gen_try(&t.exp, symbol_table.clone()).as_str();
+            },
+            Node::Tuple(tuple) => {
+                code += self.gen_tuple(&tuple.members, None, symbol_table.clone()).as_str();
+            },
+            Node::Destructure(d) => {
+                code += self.gen_destructure(d, symbol_table.clone()).as_str();
+            },
+            Node::Range(range) => {
+                let element_type = Self::range_element(range, symbol_table.clone());
+                code += self.gen_range(range, &element_type, symbol_table.clone()).as_str();
+            },
             Node::ParserError(_) => todo!(),
         }
         code
     }
 
+    fn gen_call(&self,name:&str,arguments:&[AstNode<Node>],parameters:&[(DataType,String)],symbol_table:Rc<SymbolTable>) -> String {
+        let (mut code,values) = self.gen_arguments(arguments, parameters, &symbol_table);
+        code += format!("{} = call {} {}\n",Self::new_temp(&symbol_table),name,values.join(", ")).as_str();
+        code
+    }
+
+    fn gen_arguments(&self,arguments:&[AstNode<Node>],parameters:&[(DataType,String)],symbol_table:&Rc<SymbolTable>) -> (String,Vec<String>) {
+        let mut code = String::new();
+        let mut values:Vec<String> = vec![];
+        for (i,arg) in arguments.iter().enumerate(){
+            let argument_expression = self.gen_value(arg, parameters.get(i).map(|(t,_)| t), symbol_table.clone());
+            let argument = if argument_expression.starts_with("tac_temp"){
+                code += argument_expression.as_str();
+                "tac_temp".to_string() + &symbol_table.get_var_version(String::from("tac_temp")).to_string()
+            }
+            else {
+                argument_expression
+            };
+            values.push(argument);
+        }
+        (code,values)
+    }
+
+    // a function value points to a record of the function address followed by the captured
+    // values, the function is called with the record as its first argument
+    fn gen_value_call(&self,callee:&AstNode<Node>,arguments:&[AstNode<Node>],function_type:&DataType,symbol_table:&Rc<SymbolTable>) -> String {
+        let mut code = String::new();
+        let callee_expression = self.gen_ir(callee, symbol_table.clone());
+        let record = if callee_expression.starts_with("tac_temp"){
+            code += callee_expression.as_str();
+            "tac_temp".to_string() + &symbol_table.get_var_version(String::from("tac_temp")).to_string()
+        }
+        else {
+            callee_expression
+        };
+        let parameters = match function_type {
+            DataType::Function(parameters,_) => parameters.iter().map(|t| (t.clone(),String::new())).collect::<Vec<(DataType,String)>>(),
+            _ => vec![]
+        };
+        let (argument_code,values) = self.gen_arguments(arguments, &parameters, symbol_table);
+        code += argument_code.as_str();
+        let address = Self::new_temp(symbol_table);
+        code += format!("{} = load {}, 0\n",address,record).as_str();
+        let values = [record].into_iter().chain(values).collect::<Vec<String>>();
+        code += format!("{} = call_indirect {}, {}\n",Self::new_temp(symbol_table),address,values.join(", ")).as_str();
+        code
+    }
+
+    // the captured values are laid out after the function address like the fields of a struct
+    fn capture_layout(captures:&[(DataType,String)],symbol_table:&Rc<SymbolTable>) -> (Vec<u32>,u32) {
+        let mut offsets = vec![];
+        let mut size = DataType::U64.get_size_in_bytes();
+        for (capture_type,_) in captures {
+            offsets.push(size);
+            size += symbol_table.size_of(capture_type);
+        }
+        (offsets,size)
+    }
+
+    fn gen_closure_record(&self,function_name:&str,captures:&[(DataType,String)],symbol_table:&Rc<SymbolTable>) -> String {
+        let mut code = String::new();
+        let (offsets,size) = Self::capture_layout(captures, symbol_table);
+        // a variable a reference is taken to is read out of its memory
+        let mut values = vec![];
+        for (_,capture_name) in captures {
+            let value = format!("{}{}",capture_name,symbol_table.get_var_version(capture_name.clone()));
+            if Self::scalar_in_memory(capture_name, symbol_table) {
+                let loaded = Self::new_temp(symbol_table);
+                code += format!("{} = load {}, 0\n",loaded,value).as_str();
+                values.push(loaded);
+                continue;
+            }
+            values.push(value);
+        }
+        let address = Self::new_temp(symbol_table);
+        code += format!("{} = funcaddr {}\n",address,function_name).as_str();
+        // allocated last so the record is the latest temporary
+        let record = Self::new_temp(symbol_table);
+        code += format!("{} = alloc {}\nstore {}, 0, {}\n",record,size,record,address).as_str();
+        for (((capture_type,_),offset),value) in captures.iter().zip(offsets).zip(values) {
+            if Self::is_aggregate(capture_type, symbol_table) {
+                code += format!("copy {}, {}, {}, {}\n",record,offset,value,symbol_table.size_of(capture_type)).as_str();
+            }
+            else {
+                code += format!("store {}, {}, {}\n",record,offset,value).as_str();
+            }
+        }
+        code
+    }
+
+    // a named function used as a value is wrapped once in an adapter taking the record
+    // like a closure does
+    fn gen_function_value(&self,function:&FuncAttribute,symbol_table:&Rc<SymbolTable>) -> String {
+        let callee = match function.calling_convention {
+            CallingConvention::C => function.func_name.clone(),
+            CallingConvention::Ark => Self::qualified_name(&self.module_name, &function.func_name)
+        };
+        let adapter = format!("{}$ref",callee);
+        if !self.function_values.borrow().contains(&adapter) {
+            self.function_values.borrow_mut().push(adapter.clone());
+            let parameters = [format!("{} $env",Self::function_type(function).to_string())].into_iter()
+                .chain(function.parameter.iter().map(|(t,name)| format!("{} {}",t.to_string(),name)))
+                .collect::<Vec<String>>();
+            let arguments = function.parameter.iter().map(|(_,name)| format!("{}0",name)).collect::<Vec<String>>();
+            let ret = if function.return_type == DataType::Void { "$void" } else { "tac_temp1" };
+            *self.closures.borrow_mut() += format!(
+                "@defined {} {}({}):\n    tac_temp1 = call {} {}\n    ret {}\n",
+                function.return_type.to_string(),adapter,parameters.join(", "),callee,arguments.join(", "),ret
+            ).as_str();
+        }
+        self.gen_closure_record(&adapter, &[], symbol_table)
+    }
+
+    fn function_type(function:&FuncAttribute) -> DataType {
+        DataType::Function(function.parameter.iter().map(|(t,_)| t.clone()).collect(),Box::new(function.return_type.clone()))
+    }
+
+    // alloc and free are built in unless the program declares its own
+    fn is_heap_call(name:&str,symbol_table:&Rc<SymbolTable>) -> bool {
+        (name == "alloc" || name == "free") && symbol_table.lookup_func(name.to_string()).is_none()
+    }
+
+    // a variable a reference is taken to lives in memory, a scalar one is loaded and
+    // stored instead of getting a new version
+    fn scalar_in_memory(name:&str,symbol_table:&Rc<SymbolTable>) -> bool {
+        matches!(symbol_table.lookup_var(name.to_string()),Some((var,_)) if var.in_memory && !var.data_type.as_ref().is_some_and(|t| Self::is_aggregate(t, symbol_table)))
+    }
+
+    // fields and elements are reached through one reference without writing *
+    fn dereferenced(data_type:DataType) -> DataType {
+        match data_type {
+            DataType::Reference(target,_) => *target,
+            other => other
+        }
+    }
+
+    // a call through a variable holding a function value rather than of a function
+    fn holds_function(name:&str,symbol_table:&Rc<SymbolTable>) -> bool {
+        matches!(symbol_table.lookup_var(name.to_string()),Some((VarAttribute { data_type: Some(DataType::Function(_,_)), .. },_)))
+    }
+
+    // the function of an impl block called by x.method(..), with the module it is declared in
+    // when the type of x comes from an imported module
+    fn method_function(receiver:&DataType,method:&str,symbol_table:&Rc<SymbolTable>) -> Option<(FuncAttribute,Option<ModuleAttribute>)> {
+        match receiver {
+            DataType::Struct(name) | DataType::Enum(name) if name.contains("::") => {
+                let (alias,type_name) = name.split_once("::")?;
+                let module = symbol_table.lookup_module(alias.to_string())?;
+                let function = module.module_table.as_ref()?.lookup_func(ImplBlock::method_name(&DataType::Struct(type_name.to_string()), method))?.0;
+                Some((function,Some(module)))
+            },
+            _ => Some((symbol_table.lookup_func(ImplBlock::method_name(receiver, method))?.0,None))
+        }
+    }
+
+    // Type::name(..) that is not a variant of an enum calls a function of an impl block
+    fn associated_function(value:&crate::arkparser::EnumValue,symbol_table:&Rc<SymbolTable>) -> Option<FuncAttribute> {
+        if symbol_table.lookup_enum(value.enum_name.node.clone()).is_some_and(|e| e.get_variant(&value.variant.node).is_some()) {
+            return None;
+        }
+        Some(symbol_table.lookup_func(ImplBlock::method_name(&DataType::Struct(value.enum_name.node.clone()), &value.variant.node))?.0)
+    }
+
+    // globals of an imported module are prefixed like its functions so the modules
+    // importing it can read its constants
+    fn global_name(&self,name:&str,symbol_table:&Rc<SymbolTable>) -> String {
+        match symbol_table.lookup_var(name.to_string()) {
+            Some((_,Scope::Global)) => Self::qualified_name(&self.module_name, name),
+            _ => name.to_string()
+        }
+    }
+
+    // functions of an imported module are prefixed with the name of the module
+    fn qualified_name(module_name:&str,name:&str) -> String {
+        if module_name.is_empty() {
+            return name.to_string();
+        }
+        format!("{}.{}",module_name,name)
+    }
+
+    // the instruction computing a binary operator, the type of the left operand
+    // picks between the signed and unsigned form
+    fn binary_op(operator:&TokenType,left_type:Option<DataType>) -> &'static str {
+        match operator {
+            TokenType::AdditionOperator => "add",
+            TokenType::SubtractionOperator => "sub",
+            TokenType::MultiplicationOperator => "mul",
+            TokenType::DivisionOperator => "div",  // Valid division operator
+            TokenType::ModuloOperator => "mod",  // Valid modulo operator
+            TokenType::Equal => "equ",
+            TokenType::NotEqual => "ne",
+            TokenType::Less => "l",
+            TokenType::LessEqual => "le",
+            TokenType::More => "m",
+            TokenType::MoreEqual => "me",
+            TokenType::And => "and",
+            TokenType::Or => "or",
+            TokenType::Not => "not",
+            TokenType::BitwiseAnd => "band",
+            TokenType::BitwiseOr => "bor",
+            TokenType::BitwiseXor => "bxor",
+            TokenType::ShiftLeft => "shl",
+            // the sign bit is copied into the vacated bits of a signed value
+            TokenType::ShiftRight if left_type.is_none_or(|t| Self::is_signed(&t)) => "sar",
+            TokenType::ShiftRight => "shr",
+            _ => {""}
+        }
+    }
+
+    fn new_temp(symbol_table:&Rc<SymbolTable>) -> String {
+        if symbol_table.lookup_var(String::from("tac_temp")).is_none(){
+            symbol_table.insert_var(String::from("tac_temp"));
+        }
+        format!("tac_temp{}",symbol_table.consume_var_version(String::from("tac_temp")))
+    }
+
+    fn new_label(symbol_table:&Rc<SymbolTable>) -> String {
+        if symbol_table.lookup_var(String::from("tac_label")).is_none(){
+            symbol_table.insert_var(String::from("tac_label"));
+        }
+        format!("L{}",symbol_table.consume_var_version(String::from("tac_label")))
+    }
+
+    // the scrutinee is copied into a temporary first, a match whose arms are all
+    // variant or wildcard patterns jumps through a table indexed by the tag, any
+    // other match tests the arms one after another, like the branches of an if
+    // every arm starts from the versions before the match and syncs them at its end
+    fn gen_match(&self,m:&MatchExp,symbol_table:Rc<SymbolTable>) -> String {
+        let mut code = String::new();
+        let scrutinee_expression = self.gen_ir(&m.scrutinee, symbol_table.clone());
+        let scrutinee = if scrutinee_expression.starts_with("tac_temp"){
+            code += scrutinee_expression.as_str();
+            "tac_temp".to_string() + &symbol_table.get_var_version(String::from("tac_temp")).to_string()
+        }
+        else {
+            let temp = Self::new_temp(&symbol_table);
+            code += format!("{} = {}\n",temp,scrutinee_expression).as_str();
+            temp
+        };
+        let enum_attribute = m.arms.iter().find_map(|arm| match &arm.pattern.node {
+            Pattern::Variant(v) => symbol_table.lookup_enum(v.enum_name.node.clone()),
+            _ => None
+        }).or_else(|| {
+            m.arms.iter().find(|arm| matches!(arm.pattern.node,Pattern::Variant(_)))?;
+            symbol_table.enum_of(&Self::type_of(&m.scrutinee, symbol_table.clone())?)
+        });
+        let tag = match &enum_attribute {
+            Some(e) if e.has_payload() => {
+                let temp = Self::new_temp(&symbol_table);
+                code += format!("{} = load {}, 0\n",temp,scrutinee).as_str();
+                temp
+            },
+            _ => scrutinee.clone()
+        };
+        let as_value = m.arms.iter().all(|arm| !matches!(arm.body.node,Node::Body(_)));
+        let result = if as_value {
+            if symbol_table.lookup_var(String::from("match_result")).is_none(){
+                symbol_table.insert_var(String::from("match_result"));
+            }
+            Some(format!("match_result{}",symbol_table.consume_var_version(String::from("match_result"))))
+        }
+        else {
+            None
+        };
+        let end_label = Self::new_label(&symbol_table);
+        let use_table = enum_attribute.is_some() && m.arms.iter().all(|arm| matches!(arm.pattern.node,Pattern::Variant(_) | Pattern::Wildcard));
+        let arm_labels = if use_table {
+            m.arms.iter().map(|_| Self::new_label(&symbol_table)).collect::<Vec<String>>()
+        }
+        else {
+            vec![]
+        };
+        if let (true,Some(e)) = (use_table,&enum_attribute) {
+            let targets = e.variants.iter().map(|(variant,_)| {
+                m.arms.iter().position(|arm| match &arm.pattern.node {
+                    Pattern::Variant(v) => &v.variant.node == variant,
+                    _ => true
+                }).map(|index| arm_labels[index].clone()).unwrap_or(end_label.clone())
+            }).collect::<Vec<String>>();
+            code += format!("jumptable {}, {}\n",tag,targets.join(", ")).as_str();
+        }
+        let versions = Self::var_versions(&symbol_table);
+        for (index,arm) in m.arms.iter().enumerate() {
+            let arm_scope = symbol_table.next_block_scope();
+            Self::restore_versions(&versions, &symbol_table);
+            if let Some(label) = arm_labels.get(index) {
+                code += format!("{}:\n",label).as_str();
+            }
+            let mut tests:Vec<(&str,String,String)> = vec![];
+            match &arm.pattern.node {
+                Pattern::Wildcard => (),
+                Pattern::Literal(literal) => tests.push(("equ",scrutinee.clone(),Self::literal_text(literal))),
+                Pattern::Range(start,end,inclusive) => {
+                    tests.push(("me",scrutinee.clone(),Self::literal_text(start)));
+                    tests.push((if *inclusive { "le" } else { "l" },scrutinee.clone(),Self::literal_text(end)));
+                },
+                Pattern::Variant(v) => {
+                    if let Some((variant_tag,_)) = enum_attribute.as_ref().and_then(|e| e.get_variant(&v.variant.node)) {
+                        tests.push(("equ",tag.clone(),variant_tag.to_string()));
+                    }
+                },
+            }
+            let next_label = if use_table || tests.is_empty() { None } else { Some(Self::new_label(&symbol_table)) };
+            if let Some(next) = &next_label {
+                for (op,left,right) in tests {
+                    let temp = Self::new_temp(&symbol_table);
+                    code += format!("{} = {} {}, {}\nifnot {} goto {}\n",temp,op,left,right,temp,next).as_str();
+                }
+            }
+            if let (Pattern::Variant(v),Some(e)) = (&arm.pattern.node,&enum_attribute) {
+                let payload = e.get_variant(&v.variant.node).map(|(_,p)| p).unwrap_or_default();
+                let mut offset = DataType::I32.get_size_in_bytes();
+                for (binding,binding_type) in v.bindings.iter().zip(payload.iter()) {
+                    if binding.node != "_" {
+                        let op = if Self::is_aggregate(binding_type, &symbol_table) { "add" } else { "load" };
+                        code += format!("{}{} = {} {}, {}\n",binding.node,arm_scope.consume_var_version(binding.node.clone()),op,scrutinee,offset).as_str();
+                    }
+                    offset += symbol_table.size_of(binding_type);
+                }
+            }
+            match &arm.body.node {
+                Node::Body(bd) => {
+                    for instruction in &bd.instructions {
+                        let instruction_code = self.gen_ir(instruction, arm_scope.clone());
+                        code += instruction_code.as_str();
+                        if !instruction_code.is_empty() && !instruction_code.ends_with('\n') {
+                            code += "\n";
+                        }
+                    }
+                    if Self::diverges(bd) {
+                        if let Some(next) = next_label {
+                            code += format!("{}:\n",next).as_str();
+                        }
+                        continue;
+                    }
+                },
+                _ => {
+                    let value_expression = self.gen_ir(&arm.body, arm_scope.clone());
+                    let value = if value_expression.starts_with("tac_temp"){
+                        code += value_expression.as_str();
+                        "tac_temp".to_string() + &symbol_table.get_var_version(String::from("tac_temp")).to_string()
+                    }
+                    else {
+                        value_expression
+                    };
+                    if let Some(result) = &result {
+                        code += format!("{} = {}\n",result,value).as_str();
+                    }
+                }
+            }
+            code += Self::gen_sync(&versions, &symbol_table).as_str();
+            code += format!("goto {}\n",end_label).as_str();
+            if let Some(next) = next_label {
+                code += format!("{}:\n",next).as_str();
+            }
+        }
+        code += format!("{}:\n",end_label).as_str();
+        Self::restore_versions(&versions, &symbol_table);
+        if let Some(result) = result {
+            let temp = Self::new_temp(&symbol_table);
+            code += format!("{} = {}\n",temp,result).as_str();
+        }
+        code
+    }
+
+    fn gen_body(&self,body:&Body,symbol_table:Rc<SymbolTable>) -> String {
+        let mut code = String::new();
+        for instruction in &body.instructions {
+            let instruction_code = self.gen_ir(instruction, symbol_table.clone());
+            if instruction_code.is_empty() {
+                continue;
+            }
+            code += instruction_code.as_str();
+            if !code.ends_with('\n') {
+                code.push('\n');
+            }
+        }
+        code
+    }
+
+    // versions of the user variables visible from a scope, the counters of the
+    // generator are left out
+    fn var_versions(symbol_table:&Rc<SymbolTable>) -> Vec<(String,u32)> {
+        symbol_table.var_versions().into_iter().filter(|(name,_)| !["tac_temp","tac_label","match_result"].contains(&name.as_str())).collect()
+    }
+
+    // a variable assigned inside a branch or a loop body gets a new version, before
+    // control flow joins again it is copied back to the version it had before, so
+    // the code after the join reads the same name whichever path was taken
+    fn gen_sync(versions:&[(String,u32)],symbol_table:&Rc<SymbolTable>) -> String {
+        let mut code = String::new();
+        for (name,version) in versions {
+            let current = symbol_table.get_var_version(name.clone());
+            if current != *version {
+                code += format!("{}{} = {}{}\n",name,version,name,current).as_str();
+            }
+        }
+        code
+    }
+
+    // a body ending in a jump never reaches the code after it
+    fn diverges(body:&Body) -> bool {
+        matches!(body.instructions.last().map(|i| &i.node),Some(Node::Break(_) | Node::Continue(_) | Node::Return(_)))
+    }
+
+    fn restore_versions(versions:&[(String,u32)],symbol_table:&Rc<SymbolTable>) {
+        for (name,version) in versions {
+            symbol_table.set_var_version(name.clone(), *version);
+        }
+    }
+
+    // every branch jumps past the others once its body is done, a failed condition
+    // falls through to the next branch
+    fn gen_conditional(&self,con:&ConditionalBlock,symbol_table:Rc<SymbolTable>) -> String {
+        let mut code = String::new();
+        let versions = Self::var_versions(&symbol_table);
+        let end_label = Self::new_label(&symbol_table);
+        let mut branches = vec![(Some(con.if_block.0.as_ref()),&con.if_block.1)];
+        branches.extend(con.elif_block.iter().map(|(condition,body)| (Some(condition),body)));
+        if let Some(body) = &con.else_block {
+            branches.push((None,body));
+        }
+        let branch_count = branches.len();
+        for (index,(condition,body)) in branches.into_iter().enumerate() {
+            // the analyzer opens the scope of a branch before checking its condition
+            let branch_scope = symbol_table.next_block_scope();
+            Self::restore_versions(&versions, &symbol_table);
+            let next_label = match condition {
+                Some(condition) => {
+                    let condition_expression = self.gen_ir(condition, symbol_table.clone());
+                    let condition_value = if condition_expression.starts_with("tac_temp"){
+                        code += condition_expression.as_str();
+                        "tac_temp".to_string() + &symbol_table.get_var_version(String::from("tac_temp")).to_string()
+                    }
+                    else {
+                        condition_expression
+                    };
+                    let next = if index + 1 < branch_count { Self::new_label(&symbol_table) } else { end_label.clone() };
+                    code += format!("ifnot {} goto {}\n",condition_value,next).as_str();
+                    Some(next).filter(|next| *next != end_label)
+                },
+                None => None
+            };
+            code += self.gen_body(body, branch_scope).as_str();
+            if !Self::diverges(body) {
+                code += Self::gen_sync(&versions, &symbol_table).as_str();
+                if next_label.is_some() {
+                    code += format!("goto {}\n",end_label).as_str();
+                }
+            }
+            if let Some(next) = next_label {
+                code += format!("{}:\n",next).as_str();
+            }
+        }
+        code += format!("{}:\n",end_label).as_str();
+        Self::restore_versions(&versions, &symbol_table);
+        code
+    }
+
+    // the condition is tested at the header, continue jumps to the latch which
+    // goes back to the header and break jumps to the exit
+    fn gen_while(&self,w:&WhileLoop,symbol_table:Rc<SymbolTable>) -> String {
+        let mut code = String::new();
+        let loop_scope = symbol_table.next_block_scope();
+        let versions = Self::var_versions(&symbol_table);
+        let header_label = Self::new_label(&symbol_table);
+        let latch_label = Self::new_label(&symbol_table);
+        let exit_label = Self::new_label(&symbol_table);
+        loop_scope.update_loop(exit_label.clone(), latch_label.clone(), None, versions.clone());
+        code += format!("{}:\n",header_label).as_str();
+        let condition_expression = self.gen_ir(&w.condition, symbol_table.clone());
+        let condition = if condition_expression.starts_with("tac_temp"){
+            code += condition_expression.as_str();
+            "tac_temp".to_string() + &symbol_table.get_var_version(String::from("tac_temp")).to_string()
+        }
+        else {
+            condition_expression
+        };
+        code += format!("ifnot {} goto {}\n",condition,exit_label).as_str();
+        code += self.gen_body(&w.body, loop_scope).as_str();
+        if !Self::diverges(&w.body) {
+            code += Self::gen_sync(&versions, &symbol_table).as_str();
+        }
+        code += format!("{}:\ngoto {}\n{}:\n",latch_label,header_label,exit_label).as_str();
+        Self::restore_versions(&versions, &symbol_table);
+        code
+    }
+
+    // only break leaves a `loop`, when it is used as a value every break stores its
+    // value in a temporary that starts out as 0 so the code begins with a temporary
+    // like any other value
+    fn gen_loop(&self,l:&InfiniteLoop,symbol_table:Rc<SymbolTable>) -> String {
+        let mut code = String::new();
+        let loop_scope = symbol_table.next_block_scope();
+        let as_value = loop_scope.lookup_loop(None).is_some_and(|loop_attribute| loop_attribute.as_value);
+        let result = if as_value {
+            let temp = Self::new_temp(&symbol_table);
+            code += format!("{} = 0\n",temp).as_str();
+            Some(temp)
+        }
+        else {
+            None
+        };
+        let versions = Self::var_versions(&symbol_table);
+        let header_label = Self::new_label(&symbol_table);
+        let latch_label = Self::new_label(&symbol_table);
+        let exit_label = Self::new_label(&symbol_table);
+        loop_scope.update_loop(exit_label.clone(), latch_label.clone(), result.clone(), versions.clone());
+        code += format!("{}:\n",header_label).as_str();
+        code += self.gen_body(&l.body, loop_scope).as_str();
+        if !Self::diverges(&l.body) {
+            code += Self::gen_sync(&versions, &symbol_table).as_str();
+        }
+        code += format!("{}:\ngoto {}\n{}:\n",latch_label,header_label,exit_label).as_str();
+        Self::restore_versions(&versions, &symbol_table);
+        if let Some(result) = result {
+            code += format!("{} = {}\n",Self::new_temp(&symbol_table),result).as_str();
+        }
+        code
+    }
+
+    // the bounds and the step are evaluated once before the loop, the latch only steps
+    // when the distance left to the last element allows it so the loop variable never
+    // overflows, a range counts up unless the loop reverses it with .rev(),
+    // arrays, slices and strs are visited through a hidden index
+    fn gen_for(&self,f:&ForLoop,symbol_table:Rc<SymbolTable>) -> String {
+        let mut code = String::new();
+        let loop_scope = symbol_table.next_block_scope();
+        let var = match f.var.as_ref() {
+            Some(var) => var,
+            None => return code
+        };
+        let is_range = matches!(f.range.node,Node::Range(_)) || matches!(Self::type_of(&f.range, symbol_table.clone()),Some(DataType::Range(_)));
+        if !is_range {
+            return self.gen_for_elements(f, var, loop_scope, symbol_table);
+        }
+        let var = match &var.node {
+            Node::Variable(var) => var,
+            _ => return code
+        };
+        let (start,end,inclusive,known_empty) = match &f.range.node {
+            Node::Range(range) => {
+                let mut bounds = vec![];
+                for bound in [&range.start,&range.end] {
+                    let bound_expression = self.gen_ir(bound, symbol_table.clone());
+                    bounds.push(if bound_expression.starts_with("tac_temp"){
+                        code += bound_expression.as_str();
+                        "tac_temp".to_string() + &symbol_table.get_var_version(String::from("tac_temp")).to_string()
+                    }
+                    else {
+                        bound_expression
+                    });
+                }
+                let known_empty = match (&range.start.node,&range.end.node) {
+                    (Node::Literal(LiteralValue::Int(start)),Node::Literal(LiteralValue::Int(end))) => Some(start >= &(end + range.inclusive as i64)),
+                    _ => None
+                };
+                (bounds[0].clone(),bounds[1].clone(),RangeFlag::Known(range.inclusive),known_empty)
+            },
+            _ => {
+                let element_size = match Self::type_of(&f.range, symbol_table.clone()) {
+                    Some(DataType::Range(element)) => symbol_table.size_of(&element),
+                    _ => return code
+                };
+                let range_expression = self.gen_ir(&f.range, symbol_table.clone());
+                let range = if range_expression.starts_with("tac_temp"){
+                    code += range_expression.as_str();
+                    "tac_temp".to_string() + &symbol_table.get_var_version(String::from("tac_temp")).to_string()
+                }
+                else {
+                    range_expression
+                };
+                let mut fields = vec![];
+                for offset in [0,element_size,2 * element_size] {
+                    let field = Self::new_temp(&symbol_table);
+                    code += format!("{} = load {}, {}\n",field,range,offset).as_str();
+                    fields.push(field);
+                }
+                (fields[0].clone(),fields[1].clone(),RangeFlag::Runtime(fields[2].clone()),None)
+            }
+        };
+        let step = match &f.step {
+            Some(step) => {
+                let step_expression = self.gen_ir(step, symbol_table.clone());
+                if step_expression.starts_with("tac_temp"){
+                    code += step_expression.as_str();
+                    "tac_temp".to_string() + &symbol_table.get_var_version(String::from("tac_temp")).to_string()
+                }
+                else {
+                    step_expression
+                }
+            },
+            None => "1".to_string()
+        };
+        let body_label = Self::new_label(&symbol_table);
+        let latch_label = Self::new_label(&symbol_table);
+        let exit_label = Self::new_label(&symbol_table);
+        // a range has a first element when its start is below its end, or equal to
+        // it when the end is included
+        if known_empty != Some(false) {
+            match &inclusive {
+                RangeFlag::Known(inclusive) => {
+                    let not_empty = Self::new_temp(&symbol_table);
+                    code += format!("{} = {} {}, {}\nifnot {} goto {}\n",not_empty,if *inclusive { "le" } else { "l" },start,end,not_empty,exit_label).as_str();
+                },
+                RangeFlag::Runtime(inclusive) => {
+                    let below = Self::new_temp(&symbol_table);
+                    let same = Self::new_temp(&symbol_table);
+                    let same_included = Self::new_temp(&symbol_table);
+                    let not_empty = Self::new_temp(&symbol_table);
+                    code += format!("{} = l {}, {}\n{} = equ {}, {}\n{} = and {}, {}\n",below,start,end,same,start,end,same_included,same,inclusive).as_str();
+                    code += format!("{} = or {}, {}\nifnot {} goto {}\n",not_empty,below,same_included,not_empty,exit_label).as_str();
+                }
+            }
+        }
+        // a reversed range starts at its last element and counts down to its start
+        let first = match (&f.reverse,&inclusive) {
+            (None,_) => start.clone(),
+            (Some(_),RangeFlag::Known(true)) => end.clone(),
+            (Some(_),RangeFlag::Known(false)) => {
+                let last = Self::new_temp(&symbol_table);
+                code += format!("{} = sub {}, 1\n",last,end).as_str();
+                last
+            },
+            (Some(_),RangeFlag::Runtime(inclusive)) => {
+                let excluded = Self::new_temp(&symbol_table);
+                let last = Self::new_temp(&symbol_table);
+                code += format!("{} = equ {}, 0\n{} = sub {}, {}\n",excluded,inclusive,last,end,excluded).as_str();
+                last
+            }
+        };
+        let counter = format!("{}{}",var,loop_scope.consume_var_version(var.clone()));
+        code += format!("{} = {}\n",counter,first).as_str();
+        let versions = Self::var_versions(&loop_scope);
+        loop_scope.update_loop(exit_label.clone(), latch_label.clone(), None, versions.clone());
+        code += format!("{}:\n",body_label).as_str();
+        code += self.gen_body(&f.body, loop_scope.clone()).as_str();
+        if !Self::diverges(&f.body) {
+            code += Self::gen_sync(&versions, &loop_scope).as_str();
+        }
+        code += format!("{}:\n",latch_label).as_str();
+        code += match &f.reverse {
+            None => Self::gen_range_step(&counter, &end, &step, false, &inclusive, &body_label, &exit_label, &symbol_table),
+            Some(_) => Self::gen_range_step(&counter, &start, &step, true, &RangeFlag::Known(true), &body_label, &exit_label, &symbol_table)
+        }.as_str();
+        code += format!("{}:\n",exit_label).as_str();
+        Self::restore_versions(&versions, &loop_scope);
+        code
+    }
+
+    // leaves the loop unless the distance left to the end is more than the step,
+    // or exactly the step when the end is included
+    fn gen_range_step(counter:&str,end:&str,step:&str,descending:bool,inclusive:&RangeFlag,body_label:&str,exit_label:&str,symbol_table:&Rc<SymbolTable>) -> String {
+        let distance = Self::new_temp(symbol_table);
+        let (op,left,right) = if descending { ("sub",counter,end) } else { ("add",end,counter) };
+        let mut code = format!("{} = sub {}, {}\n",distance,left,right);
+        let more = Self::new_temp(symbol_table);
+        match inclusive {
+            RangeFlag::Known(inclusive) => {
+                code += format!("{} = {} {}, {}\n",more,if *inclusive { "me" } else { "m" },distance,step).as_str();
+            },
+            RangeFlag::Runtime(inclusive) => {
+                let beyond = Self::new_temp(symbol_table);
+                let exact = Self::new_temp(symbol_table);
+                let exact_included = Self::new_temp(symbol_table);
+                code += format!("{} = m {}, {}\n{} = equ {}, {}\n{} = and {}, {}\n",beyond,distance,step,exact,distance,step,exact_included,exact,inclusive).as_str();
+                code += format!("{} = or {}, {}\n",more,beyond,exact_included).as_str();
+            }
+        }
+        code += format!("ifnot {} goto {}\n{} = {} {}, {}\ngoto {}\n",more,exit_label,counter,op,counter,step,body_label).as_str();
+        code
+    }
+
+    fn gen_for_elements(&self,f:&ForLoop,var:&AstNode<Node>,loop_scope:Rc<SymbolTable>,symbol_table:Rc<SymbolTable>) -> String {
+        let mut code = String::new();
+        let iterable_type = match Self::type_of(&f.range, symbol_table.clone()) {
+            Some(t) => t,
+            None => return code
+        };
+        let iterable_expression = self.gen_ir(&f.range, symbol_table.clone());
+        let iterable = if iterable_expression.starts_with("tac_temp"){
+            code += iterable_expression.as_str();
+            "tac_temp".to_string() + &symbol_table.get_var_version(String::from("tac_temp")).to_string()
+        }
+        else {
+            iterable_expression
+        };
+        let (pointer,length,element_type) = match iterable_type {
+            DataType::Array(arr) => (iterable,arr.length.to_string(),*arr.data_type),
+            DataType::Slice(_) | DataType::Str(_) => {
+                let element_type = match iterable_type {
+                    DataType::Slice(element) => *element,
+                    _ => DataType::U8
+                };
+                let pointer = Self::new_temp(&symbol_table);
+                let length = Self::new_temp(&symbol_table);
+                code += format!("{} = load {}, 0\n{} = load {}, {}\n",pointer,iterable,length,iterable,DataType::U64.get_size_in_bytes()).as_str();
+                (pointer,length,element_type)
+            },
+            _ => return code
+        };
+        let index = Self::new_temp(&symbol_table);
+        code += format!("{} = 0\n",index).as_str();
+        let element = match &var.node {
+            Node::Variable(var) => Some(format!("{}{}",var,loop_scope.consume_var_version(var.clone()))),
+            _ => None
+        };
+        let names = match &var.node {
+            Node::Tuple(tuple) => Self::tuple_names(&tuple.members, &loop_scope),
+            _ => vec![]
+        };
+        let versions = Self::var_versions(&loop_scope);
+        let body_label = Self::new_label(&symbol_table);
+        let latch_label = Self::new_label(&symbol_table);
+        let exit_label = Self::new_label(&symbol_table);
+        loop_scope.update_loop(exit_label.clone(), latch_label.clone(), None, versions.clone());
+        let not_empty = Self::new_temp(&symbol_table);
+        code += format!("{} = l {}, {}\nifnot {} goto {}\n{}:\n",not_empty,index,length,not_empty,exit_label,body_label).as_str();
+        let stride = symbol_table.size_of(&element_type);
+        let offset = Self::new_temp(&symbol_table);
+        code += format!("{} = mul {}, {}\n",offset,index,stride).as_str();
+        // the loop variable holds a copy of the element, a tuple of names takes it apart
+        match element {
+            Some(element) if Self::is_aggregate(&element_type, &symbol_table) => {
+                let address = Self::new_temp(&symbol_table);
+                code += format!("{} = add {}, {}\n{} = alloc {}\ncopy {}, 0, {}, {}\n",address,pointer,offset,element,stride,element,address,stride).as_str();
+            },
+            Some(element) => code += format!("{} = load {}, {}\n",element,pointer,offset).as_str(),
+            None => {
+                let address = Self::new_temp(&symbol_table);
+                code += format!("{} = add {}, {}\n",address,pointer,offset).as_str();
+                code += Self::gen_unpack(&address, &names, &element_type, &symbol_table).as_str();
+            }
+        }
+        code += self.gen_body(&f.body, loop_scope.clone()).as_str();
+        if !Self::diverges(&f.body) {
+            code += Self::gen_sync(&versions, &loop_scope).as_str();
+        }
+        let more = Self::new_temp(&symbol_table);
+        code += format!("{}:\n{} = add {}, 1\n{} = l {}, {}\nifnot {} goto {}\ngoto {}\n{}:\n",latch_label,index,index,more,index,length,more,exit_label,body_label,exit_label).as_str();
+        Self::restore_versions(&versions, &loop_scope);
+        code
+    }
+
+    // a tuple is laid out like a struct whose fields are its elements in order
+    fn gen_tuple(&self,members:&[AstNode<Node>],expected:Option<&Vec<DataType>>,symbol_table:Rc<SymbolTable>) -> String {
+        let mut code = String::new();
+        let mut stores:Vec<(u32,String,Option<u32>)> = vec![];
+        let mut offset = 0;
+        for (i,member) in members.iter().enumerate() {
+            let member_type = match expected.and_then(|e| e.get(i)).cloned().or_else(|| Self::type_of(member, symbol_table.clone())) {
+                Some(t) => t,
+                None => return code
+            };
+            let member_expression = self.gen_value(member, Some(&member_type), symbol_table.clone());
+            let value = if member_expression.starts_with("tac_temp"){
+                code += member_expression.as_str();
+                "tac_temp".to_string() + &symbol_table.get_var_version(String::from("tac_temp")).to_string()
+            }
+            else {
+                member_expression
+            };
+            let size = symbol_table.size_of(&member_type);
+            stores.push((offset,value,Self::is_aggregate(&member_type, &symbol_table).then_some(size)));
+            offset += size;
+        }
+        let address = Self::new_temp(&symbol_table);
+        code += format!("{} = alloc {}\n",address,offset).as_str();
+        for (offset,value,copy_size) in stores {
+            match copy_size {
+                Some(size) => code += format!("copy {}, {}, {}, {}\n",address,offset,value,size).as_str(),
+                None => code += format!("store {}, {}, {}\n",address,offset,value).as_str()
+            }
+        }
+        code
+    }
+
+    fn gen_destructure(&self,d:&Destructure,symbol_table:Rc<SymbolTable>) -> String {
+        let mut code = String::new();
+        let tuple_type = match d.var_type.as_ref().map(|t| symbol_table.source_type(&t.node)).or_else(|| Self::type_of(&d.value, symbol_table.clone())) {
+            Some(t) => t,
+            None => return code
+        };
+        let value_expression = self.gen_value(&d.value, Some(&tuple_type), symbol_table.clone());
+        let value = if value_expression.starts_with("tac_temp"){
+            code += value_expression.as_str();
+            "tac_temp".to_string() + &symbol_table.get_var_version(String::from("tac_temp")).to_string()
+        }
+        else {
+            value_expression
+        };
+        let names = d.names.iter().map(|name| {
+            (name.node != "_").then(|| format!("{}{}",name.node,symbol_table.consume_var_version(name.node.clone())))
+        }).collect::<Vec<Option<String>>>();
+        code += Self::gen_unpack(&value, &names, &tuple_type, &symbol_table).as_str();
+        code
+    }
+
+    // the versioned names a tuple of loop variables writes to, _ is not declared
+    fn tuple_names(members:&[AstNode<Node>],symbol_table:&Rc<SymbolTable>) -> Vec<Option<String>> {
+        members.iter().map(|member| match &member.node {
+            Node::Variable(name) if name != "_" => Some(format!("{}{}",name,symbol_table.consume_var_version(name.clone()))),
+            _ => None
+        }).collect()
+    }
+
+    // reads every element of the tuple at the address into its name, elements that
+    // are aggregates are copied so the names do not alias the tuple
+    fn gen_unpack(address:&str,names:&[Option<String>],tuple_type:&DataType,symbol_table:&Rc<SymbolTable>) -> String {
+        let mut code = String::new();
+        let elements = match tuple_type {
+            DataType::Tuple(elements) => elements,
+            _ => return code
+        };
+        let mut offset = 0;
+        for (name,element_type) in names.iter().zip(elements) {
+            let size = symbol_table.size_of(element_type);
+            if let Some(name) = name {
+                if Self::is_aggregate(element_type, symbol_table) {
+                    let element = Self::new_temp(symbol_table);
+                    code += format!("{} = add {}, {}\n{} = alloc {}\ncopy {}, 0, {}, {}\n",element,address,offset,name,size,name,element,size).as_str();
+                }
+                else {
+                    code += format!("{} = load {}, {}\n",name,address,offset).as_str();
+                }
+            }
+            offset += size;
+        }
+        code
+    }
+
+    // the type and offset of a numbered tuple element
+    fn tuple_element(elements:&[DataType],field:&str,symbol_table:&Rc<SymbolTable>) -> Option<(DataType,u32)> {
+        let index = field.parse::<usize>().ok()?;
+        let offset = elements.iter().take(index).map(|e| symbol_table.size_of(e)).sum();
+        Some((elements.get(index)?.clone(),offset))
+    }
+
+    // a range value is a block holding its start, its end and whether the end is included
+    fn gen_range(&self,range:&Range,element_type:&DataType,symbol_table:Rc<SymbolTable>) -> String {
+        let mut code = String::new();
+        let mut bounds = vec![];
+        for bound in [&range.start,&range.end] {
+            let bound_expression = self.gen_ir(bound, symbol_table.clone());
+            bounds.push(if bound_expression.starts_with("tac_temp"){
+                code += bound_expression.as_str();
+                "tac_temp".to_string() + &symbol_table.get_var_version(String::from("tac_temp")).to_string()
+            }
+            else {
+                bound_expression
+            });
+        }
+        let size = symbol_table.size_of(element_type);
+        let address = Self::new_temp(&symbol_table);
+        code += format!(
+            "{} = alloc {}\nstore {}, 0, {}\nstore {}, {}, {}\nstore {}, {}, {}\n",
+            address,
+            DataType::Range(Box::new(element_type.clone())).get_size_in_bytes(),
+            address,
+            bounds[0],
+            address,
+            size,
+            bounds[1],
+            address,
+            2 * size,
+            range.inclusive as u8
+        ).as_str();
+        code
+    }
+
+    // the element type of a range built from bounds, two literals make a range of i32
+    fn range_element(range:&Range,symbol_table:Rc<SymbolTable>) -> DataType {
+        [&range.start,&range.end].iter()
+            .filter(|bound| !matches!(bound.node,Node::Literal(_)))
+            .find_map(|bound| Self::type_of(bound, symbol_table.clone()))
+            .unwrap_or(DataType::I32)
+    }
+
+    // integers are widened with sext or zext depending on the sign of the original type,
+    // narrowed with trunc and converted to and from floats with sitofp, uitofp, fptosi and
+    // fptoui, bool and char are unsigned integers, a cast between types of the same size
+    // only copies the value
+    fn gen_cast(&self,cast:&CastExp,symbol_table:Rc<SymbolTable>) -> String {
+        let mut code = String::new();
+        let original_type = match Self::type_of(&cast.value, symbol_table.clone()) {
+            Some(t) => t,
+            None => return code
+        };
+        let value_expression = self.gen_ir(&cast.value, symbol_table.clone());
+        let value = if value_expression.starts_with("tac_temp"){
+            code += value_expression.as_str();
+            "tac_temp".to_string() + &symbol_table.get_var_version(String::from("tac_temp")).to_string()
+        }
+        else {
+            value_expression
+        };
+        let target_type = &symbol_table.source_type(&cast.target.node);
+        // an integer literal that fits the target type already is a value of that type
+        let literal_fits = match (&cast.value.node,target_type) {
+            (Node::Literal(LiteralValue::Int(i)),DataType::I8 | DataType::I16 | DataType::I32 | DataType::I64 | DataType::U8 | DataType::U16 | DataType::U32 | DataType::U64) => {
+                let (start,end) = target_type.int_domain();
+                (start..end).contains(&(*i as i128))
+            },
+            _ => false
+        };
+        let original_type = if literal_fits { target_type.clone() } else { original_type };
+        let is_float = |t:&DataType| matches!(t,DataType::F32 | DataType::F64);
+        let original_size = original_type.get_size_in_bytes();
+        let target_size = target_type.get_size_in_bytes();
+        let op = match (is_float(&original_type),is_float(target_type)) {
+            (false,true) => if Self::is_signed(&original_type) { "sitofp" } else { "uitofp" },
+            (true,false) => if Self::is_signed(target_type) { "fptosi" } else { "fptoui" },
+            (true,true) if original_size < target_size => "fpext",
+            (true,true) if original_size > target_size => "fptrunc",
+            (false,false) if original_size < target_size => if Self::is_signed(&original_type) { "sext" } else { "zext" },
+            (false,false) if original_size > target_size => "trunc",
+            _ => ""
+        };
+        let result = Self::new_temp(&symbol_table);
+        if op.is_empty() {
+            code += format!("{} = {}\n",result,value).as_str();
+        }
+        else {
+            code += format!("{} = {} {}, {}\n",result,op,value,target_type.to_string()).as_str();
+        }
+        code
+    }
+
+    // x? returns a None or an Err from the function right away, the tag of None is 0
+    // and the tag of Err is 1, otherwise the value is read out of the Some or the Ok
+    fn gen_try(&self,operand:&AstNode<Node>,symbol_table:Rc<SymbolTable>) -> String {
+        let mut code = String::new();
+        let (operand_type,return_type) = match (Self::type_of(operand, symbol_table.clone()),symbol_table.get_current_func_info()) {
+            (Some(operand_type),Some(function)) => (operand_type,function.return_type),
+            _ => return code
+        };
+        let (value_type,failed_tag) = match &operand_type {
+            DataType::Option(value) => (value.as_ref().clone(),0),
+            DataType::Result(value,_) => (value.as_ref().clone(),1),
+            _ => return code
+        };
+        let operand_expression = self.gen_ir(operand, symbol_table.clone());
+        let value = if operand_expression.starts_with("tac_temp"){
+            code += operand_expression.as_str();
+            "tac_temp".to_string() + &symbol_table.get_var_version(String::from("tac_temp")).to_string()
+        }
+        else {
+            operand_expression
+        };
+        let payload_offset = DataType::I32.get_size_in_bytes();
+        let tag = Self::new_temp(&symbol_table);
+        let failed = Self::new_temp(&symbol_table);
+        let continue_label = Self::new_label(&symbol_table);
+        code += format!("{} = load {}, 0
+{} = equ {}, {}
+ifnot {} goto {}
+",tag,value,failed,tag,failed_tag,failed,continue_label).as_str();
+        let returned = Self::new_temp(&symbol_table);
+        code += format!("{} = alloc {}
+store {}, 0, {}
+",returned,symbol_table.size_of(&return_type),returned,failed_tag).as_str();
+        if let (DataType::Result(_,error_type),DataType::Result(_,_)) = (&operand_type,&return_type) {
+            let error = Self::new_temp(&symbol_table);
+            if Self::is_aggregate(error_type, &symbol_table) {
+                code += format!("{} = add {}, {}
+copy {}, {}, {}, {}
+",error,value,payload_offset,returned,payload_offset,error,symbol_table.size_of(error_type)).as_str();
+            }
+            else {
+                code += format!("{} = load {}, {}
+store {}, {}, {}
+",error,value,payload_offset,returned,payload_offset,error).as_str();
+            }
+        }
+        code += format!("ret {}
+{}:
+",returned,continue_label).as_str();
+        let op = if Self::is_aggregate(&value_type, &symbol_table) { "add" } else { "load" };
+        code += format!("{} = {} {}, {}\n",Self::new_temp(&symbol_table),op,value,payload_offset).as_str();
+        code
+    }
+
+    fn literal_text(literal:&LiteralValue) -> String {
+        match literal {
+            LiteralValue::Int(i) => i.to_string(),
+            LiteralValue::Float(f) => f.to_string(),
+            LiteralValue::Str(s) => s.clone(),
+            LiteralValue::Bool(b) => b.to_string(),
+        }
+    }
+
+    // values stored inline wherever they live, a variable of one of these types
+    // holds the address of its block and assigning it copies the block, an enum
+    // without a payload is only its tag
+    fn is_aggregate(data_type:&DataType,symbol_table:&Rc<SymbolTable>) -> bool {
+        match data_type {
+            DataType::Struct(_) | DataType::Array(_) | DataType::Slice(_) | DataType::Str(_) | DataType::Range(_) | DataType::Tuple(_) => true,
+            DataType::Enum(_) | DataType::Option(_) | DataType::Result(_,_) => symbol_table.enum_of(data_type).is_some_and(|e| e.has_payload()),
+            _ => false
+        }
+    }
+
+    fn is_signed(data_type:&DataType) -> bool {
+        matches!(data_type,DataType::I8 | DataType::I16 | DataType::I32 | DataType::I64 | DataType::F32 | DataType::F64)
+    }
+
+    fn type_of(node:&AstNode<Node>,symbol_table:Rc<SymbolTable>) -> Option<DataType> {
+        let data_type = match &node.node {
+            Node::Variable(v) => match symbol_table.lookup_var(v.clone()) {
+                Some((var,_)) => var.data_type?,
+                None => Self::function_type(&symbol_table.lookup_func(v.clone())?.0)
+            },
+            Node::Closure(_) => Self::function_type(&symbol_table.lookup_func(symbol_table.closure_name(node.pos))?.0),
+            Node::ClosureCall(call) => match Self::type_of(&call.callee, symbol_table.clone())? {
+                DataType::Function(_,return_type) => *return_type,
+                _ => return None
+            },
+            Node::FunctionCall(fun) if Self::holds_function(&fun.function_name.node, &symbol_table) => match symbol_table.lookup_var(fun.function_name.node.clone())?.0.data_type? {
+                DataType::Function(_,return_type) => *return_type,
+                _ => return None
+            },
+            Node::DeclareVar(var) => symbol_table.lookup_var(var.name.node.clone())?.0.data_type?,
+            Node::StructLiteral(literal) => DataType::Struct(literal.struct_name.node.clone()),
+            Node::Literal(LiteralValue::Str(s)) => DataType::Str(s.len() as u32),
+            Node::FunctionCall(fun) if fun.function_name.node == "len" && symbol_table.lookup_func(fun.function_name.node.clone()).is_none() => DataType::I32,
+            Node::FunctionCall(fun) if Self::is_heap_call(&fun.function_name.node, &symbol_table) => match fun.function_name.node.as_str() {
+                "alloc" => DataType::Reference(Box::new(Self::type_of(fun.arguments.first()?, symbol_table)?),true),
+                _ => DataType::Void
+            },
+            Node::Reference(reference) => DataType::Reference(Box::new(Self::type_of(&reference.target, symbol_table)?),reference.mutable),
+            Node::Deref(exp) => match Self::type_of(&exp.exp, symbol_table)? {
+                DataType::Reference(target,_) => *target,
+                _ => return None
+            },
+            Node::FunctionCall(fun) => {
+                let function_name = symbol_table.lookup_generic_call(fun.function_name.pos).unwrap_or(fun.function_name.node.clone());
+                symbol_table.lookup_func(function_name)?.0.return_type
+            },
+            Node::EnumValue(_) if symbol_table.lookup_variant_type(node.pos).is_some() => symbol_table.lookup_variant_type(node.pos)?,
+            Node::EnumValue(value) if symbol_table.lookup_module(value.enum_name.node.clone()).is_none() => {
+                Self::associated_function(value, &symbol_table)?.return_type
+            },
+            Node::MethodCall(call) => {
+                let receiver = Self::type_of(call.caller.as_ref()?, symbol_table.clone())?;
+                let (function,module) = Self::method_function(&receiver, &call.method_name.node, &symbol_table)?;
+                match module {
+                    Some(module) => symbol_table.import_type(&module, &function.return_type),
+                    None => function.return_type
+                }
+            },
+            Node::EnumValue(value) => {
+                let module = symbol_table.lookup_module(value.enum_name.node.clone())?;
+                let module_table = module.module_table.clone()?;
+                let item_type = match module_table.lookup_func(value.variant.node.clone()) {
+                    Some((f,_)) => f.return_type,
+                    None => module_table.lookup_var(value.variant.node.clone())?.0.data_type?
+                };
+                symbol_table.import_type(&module, &item_type)
+            },
+            Node::FieldAccess(access) => match Self::dereferenced(Self::type_of(&access.target, symbol_table.clone())?) {
+                DataType::Struct(name) => symbol_table.lookup_struct(name)?.get_field(&access.field.node)?.0,
+                DataType::Tuple(elements) => Self::tuple_element(&elements, &access.field.node, &symbol_table)?.0,
+                _ => return None
+            },
+            Node::Tuple(tuple) => {
+                let mut members = vec![];
+                for member in &tuple.members {
+                    members.push(Self::type_of(member, symbol_table.clone())?);
+                }
+                DataType::Tuple(members)
+            },
+            Node::Index(index) => match (Self::dereferenced(Self::type_of(&index.target, symbol_table.clone())?),&index.index.node) {
+                (DataType::Array(arr),Node::Range(_)) => DataType::Slice(arr.data_type),
+                (DataType::Array(arr),_) => *arr.data_type,
+                (DataType::Slice(element),Node::Range(_)) => DataType::Slice(element),
+                (DataType::Slice(element),_) => *element,
+                (DataType::Str(_),Node::Range(_)) => DataType::Str(0),
+                (DataType::Str(_),_) => DataType::U8,
+                _ => return None
+            },
+            Node::Range(range) => DataType::Range(Box::new(Self::range_element(range, symbol_table))),
+            Node::Cast(cast) => symbol_table.source_type(&cast.target.node),
+            Node::Try(t) => match Self::type_of(&t.exp, symbol_table)? {
+                DataType::Option(value) | DataType::Result(value,_) => *value,
+                _ => return None
+            },
+            Node::Literal(LiteralValue::Int(i)) => if i32::try_from(*i).is_ok() { DataType::I32 } else { DataType::I64 },
+            Node::Literal(LiteralValue::Float(_)) => DataType::F64,
+            Node::Literal(LiteralValue::Bool(_)) | Node::BooleanNot(_) => DataType::Boolean,
+            Node::Negation(exp) | Node::BitwiseNot(exp) => Self::type_of(&exp.exp, symbol_table)?,
+            // a literal operand takes the type of the other operand
+            Node::BinaryExpression(binexp) => match binexp.operator.node {
+                TokenType::Equal | TokenType::NotEqual | TokenType::Less | TokenType::LessEqual
+                | TokenType::More | TokenType::MoreEqual | TokenType::And | TokenType::Or => DataType::Boolean,
+                _ if matches!(binexp.left.node,Node::Literal(_)) => Self::type_of(&binexp.right, symbol_table)?,
+                _ => Self::type_of(&binexp.left, symbol_table)?
+            },
+            _ => return None
+        };
+        Some(data_type)
+    }
+
+    // array literals take their layout from the type they are stored into,
+    // a literal 1 stored in an i64 array still needs the stride of an i64
+    fn gen_value(&self,node:&AstNode<Node>,expected:Option<&DataType>,symbol_table:Rc<SymbolTable>) -> String {
+        if let (Node::ArrayLiteral(literal),Some(DataType::Array(arr))) = (&node.node,expected) {
+            return self.gen_array_literal(literal, Some(arr.data_type.as_ref()), symbol_table);
+        }
+        if let (Node::Range(range),Some(DataType::Range(element))) = (&node.node,expected) {
+            return self.gen_range(range, element, symbol_table);
+        }
+        if let (Node::Tuple(tuple),Some(DataType::Tuple(elements))) = (&node.node,expected) {
+            return self.gen_tuple(&tuple.members, Some(elements), symbol_table);
+        }
+        // an array passed where a slice is expected becomes a slice over all of its elements
+        if let Some(DataType::Slice(element)) = expected {
+            let length = match (&node.node,Self::type_of(node, symbol_table.clone())) {
+                (Node::ArrayLiteral(ArrayLiteral::Elements(elements)),_) => elements.len() as u32,
+                (Node::ArrayLiteral(ArrayLiteral::Repeat(_,count)),_) => count.node,
+                (_,Some(DataType::Array(arr))) => arr.length,
+                _ => return self.gen_ir(node, symbol_table)
+            };
+            let mut code = String::new();
+            let array_expression = match &node.node {
+                Node::ArrayLiteral(literal) => self.gen_array_literal(literal, Some(element.as_ref()), symbol_table.clone()),
+                _ => self.gen_ir(node, symbol_table.clone())
+            };
+            let array = if array_expression.starts_with("tac_temp"){
+                code += array_expression.as_str();
+                "tac_temp".to_string() + &symbol_table.get_var_version(String::from("tac_temp")).to_string()
+            }
+            else {
+                array_expression
+            };
+            code += Self::gen_slice_header(&array, &length.to_string(), &symbol_table).as_str();
+            return code;
+        }
+        self.gen_ir(node, symbol_table)
+    }
+
+    // a slice is a block holding the address of its first element followed by its length
+    fn gen_slice_header(pointer:&str,length:&str,symbol_table:&Rc<SymbolTable>) -> String {
+        let slice = Self::new_temp(symbol_table);
+        format!(
+            "{} = alloc {}\nstore {}, 0, {}\nstore {}, {}, {}\n",
+            slice,
+            DataType::Slice(Box::new(DataType::Void)).get_size_in_bytes(),
+            slice,
+            pointer,
+            slice,
+            DataType::U64.get_size_in_bytes(),
+            length
+        )
+    }
+
+    // returns the code loading the address of the first element and the length of an
+    // array, slice or str, the address, the length and the element type
+    fn gen_elements(&self,target:&AstNode<Node>,symbol_table:Rc<SymbolTable>) -> Option<(String,String,String,DataType)> {
+        let target_type = Self::dereferenced(Self::type_of(target, symbol_table.clone())?);
+        let mut code = String::new();
+        let target_expression = self.gen_ir(target, symbol_table.clone());
+        let base = if target_expression.starts_with("tac_temp"){
+            code += target_expression.as_str();
+            "tac_temp".to_string() + &symbol_table.get_var_version(String::from("tac_temp")).to_string()
+        }
+        else {
+            target_expression
+        };
+        let element_type = match &target_type {
+            DataType::Array(arr) => return Some((code,base,arr.length.to_string(),*arr.data_type.clone())),
+            DataType::Slice(element) => *element.clone(),
+            DataType::Str(_) => DataType::U8,
+            _ => return None
+        };
+        let pointer = Self::new_temp(&symbol_table);
+        code += format!("{} = load {}, 0\n",pointer,base).as_str();
+        let length = if self.bounds_checks {
+            let length = Self::new_temp(&symbol_table);
+            code += format!("{} = load {}, {}\n",length,base,DataType::U64.get_size_in_bytes()).as_str();
+            length
+        }
+        else {
+            String::new()
+        };
+        Some((code,pointer,length,element_type))
+    }
+
+    fn gen_trap_unless(condition:&str,message:&str,pos:(u32,u32),symbol_table:&Rc<SymbolTable>) -> String {
+        let outside = Self::new_temp(symbol_table);
+        let in_bounds = Self::new_label(symbol_table);
+        format!("{} = not {}\nifnot {} goto {}\ntrap \"{} at {}:{}\"\n{}:\n",outside,condition,outside,in_bounds,message,pos.0,pos.1,in_bounds)
+    }
+
+    fn gen_slice(&self,index:&IndexExp,symbol_table:Rc<SymbolTable>) -> String {
+        let range = match &index.index.node {
+            Node::Range(range) => range,
+            _ => return String::new()
+        };
+        let (mut code,pointer,length,element_type) = match self.gen_elements(&index.target, symbol_table.clone()) {
+            Some(elements) => elements,
+            None => return String::new()
+        };
+        let mut bounds:Vec<String> = vec![];
+        for bound in [&range.start,&range.end] {
+            let bound_expression = self.gen_ir(bound, symbol_table.clone());
+            let bound = if bound_expression.starts_with("tac_temp"){
+                code += bound_expression.as_str();
+                "tac_temp".to_string() + &symbol_table.get_var_version(String::from("tac_temp")).to_string()
+            }
+            else {
+                bound_expression
+            };
+            bounds.push(bound);
+        }
+        // from here on the end is exclusive
+        if range.inclusive {
+            let end = Self::new_temp(&symbol_table);
+            code += format!("{} = add {}, 1\n",end,bounds[1]).as_str();
+            bounds[1] = end;
+        }
+        let constant = matches!((&range.start.node,&range.end.node),(Node::Literal(_),Node::Literal(_)));
+        let is_array = matches!(Self::type_of(&index.target, symbol_table.clone()),Some(DataType::Array(_)));
+        if self.bounds_checks && !(constant && is_array) {
+            let checks = [("me",&bounds[0],"0".to_string()),("le",&bounds[0],bounds[1].clone()),("le",&bounds[1],length.clone())];
+            let mut inside = String::new();
+            for (op,left,right) in checks {
+                let check = Self::new_temp(&symbol_table);
+                code += format!("{} = {} {}, {}\n",check,op,left,right).as_str();
+                if !inside.is_empty() {
+                    let both = Self::new_temp(&symbol_table);
+                    code += format!("{} = and {}, {}\n",both,inside,check).as_str();
+                    inside = both;
+                }
+                else {
+                    inside = check;
+                }
+            }
+            code += Self::gen_trap_unless(&inside, "slice out of bounds", index.index.pos, &symbol_table).as_str();
+        }
+        let offset = Self::new_temp(&symbol_table);
+        code += format!("{} = mul {}, {}\n",offset,bounds[0],symbol_table.size_of(&element_type)).as_str();
+        let start = Self::new_temp(&symbol_table);
+        code += format!("{} = add {}, {}\n",start,pointer,offset).as_str();
+        let count = Self::new_temp(&symbol_table);
+        code += format!("{} = sub {}, {}\n",count,bounds[1],bounds[0]).as_str();
+        code += Self::gen_slice_header(&start, &count, &symbol_table).as_str();
+        code
+    }
+
+    fn gen_array_literal(&self,literal:&ArrayLiteral,element_type:Option<&DataType>,symbol_table:Rc<SymbolTable>) -> String {
+        let mut code = String::new();
+        let (elements,count) = match literal {
+            ArrayLiteral::Elements(elements) => (elements.iter().collect::<Vec<&AstNode<Node>>>(),elements.len() as u32),
+            ArrayLiteral::Repeat(value,count) => (vec![value.as_ref()],count.node),
+        };
+        let element_type = match element_type {
+            Some(t) => t.clone(),
+            None => match elements.first().and_then(|e| Self::literal_type(e, symbol_table.clone())) {
+                Some(t) => t,
+                None => return code
+            }
+        };
+        let stride = symbol_table.size_of(&element_type);
+        let mut values:Vec<String> = vec![];
+        for element in elements {
+            let value_expression = self.gen_value(element, Some(&element_type), symbol_table.clone());
+            let value = if value_expression.starts_with("tac_temp"){
+                code += value_expression.as_str();
+                "tac_temp".to_string() + &symbol_table.get_var_version(String::from("tac_temp")).to_string()
+            }
+            else {
+                value_expression
+            };
+            values.push(value);
+        }
+        let address = Self::new_temp(&symbol_table);
+        code += format!("{} = alloc {}\n",address,stride * count).as_str();
+        if let ArrayLiteral::Repeat(_,_) = literal {
+            // fill writes the value count times, stride bytes apart
+            if !Self::is_aggregate(&element_type, &symbol_table) {
+                code += format!("fill {}, {}, {}, {}\n",address,values[0],count,stride).as_str();
+                return code;
+            }
+            values = vec![values[0].clone();count as usize];
+        }
+        for (i,value) in values.iter().enumerate() {
+            if Self::is_aggregate(&element_type, &symbol_table) {
+                code += format!("copy {}, {}, {}, {}\n",address,i as u32 * stride,value,stride).as_str();
+            }
+            else {
+                code += format!("store {}, {}, {}\n",address,i as u32 * stride,value).as_str();
+            }
+        }
+        code
+    }
+
+    // the element type of an array literal that is not stored anywhere with a known type
+    fn literal_type(node:&AstNode<Node>,symbol_table:Rc<SymbolTable>) -> Option<DataType> {
+        match &node.node {
+            Node::Literal(LiteralValue::Int(i)) => {
+                if i32::try_from(*i).is_ok() { Some(DataType::I32) } else { Some(DataType::I64) }
+            },
+            Node::Literal(LiteralValue::Float(_)) => Some(DataType::F64),
+            Node::Literal(LiteralValue::Bool(_)) => Some(DataType::Boolean),
+            Node::Literal(LiteralValue::Str(s)) => Some(DataType::Str(s.len() as u32)),
+            Node::ArrayLiteral(ArrayLiteral::Elements(elements)) => {
+                let element_type = Self::literal_type(elements.first()?, symbol_table)?;
+                Some(DataType::Array(Array { length: elements.len() as u32, data_type: Box::new(element_type) }))
+            },
+            Node::ArrayLiteral(ArrayLiteral::Repeat(value,count)) => {
+                let element_type = Self::literal_type(value, symbol_table)?;
+                Some(DataType::Array(Array { length: count.node, data_type: Box::new(element_type) }))
+            },
+            _ => Self::type_of(node, symbol_table)
+        }
+    }
+
+    // returns the code computing the base address and the element offset, the base,
+    // the offset and the element type, a constant index into an array was already checked
+    // by the semantic analyzer so it never gets a runtime bounds check
+    fn element_address(&self,index:&IndexExp,symbol_table:Rc<SymbolTable>) -> Option<(String,String,String,DataType)> {
+        let is_array = matches!(Self::dereferenced(Self::type_of(&index.target, symbol_table.clone())?),DataType::Array(_));
+        let (mut code,base,length,element_type) = self.gen_elements(&index.target, symbol_table.clone())?;
+        let stride = symbol_table.size_of(&element_type);
+        if let (true,Node::Literal(LiteralValue::Int(i))) = (is_array,&index.index.node) {
+            return Some((code,base,(*i as u32 * stride).to_string(),element_type));
+        }
+        let index_expression = self.gen_ir(&index.index, symbol_table.clone());
+        let position = if index_expression.starts_with("tac_temp"){
+            code += index_expression.as_str();
+            "tac_temp".to_string() + &symbol_table.get_var_version(String::from("tac_temp")).to_string()
+        }
+        else {
+            index_expression
+        };
+        if self.bounds_checks {
+            let lower = Self::new_temp(&symbol_table);
+            code += format!("{} = me {}, 0\n",lower,position).as_str();
+            let upper = Self::new_temp(&symbol_table);
+            code += format!("{} = l {}, {}\n",upper,position,length).as_str();
+            let inside = Self::new_temp(&symbol_table);
+            code += format!("{} = and {}, {}\n",inside,lower,upper).as_str();
+            code += Self::gen_trap_unless(&inside, "index out of bounds", index.index.pos, &symbol_table).as_str();
+        }
+        let offset = Self::new_temp(&symbol_table);
+        code += format!("{} = mul {}, {}\n",offset,position,stride).as_str();
+        Some((code,base,offset,element_type))
+    }
+
+    // returns the code computing the base address, the base, the field offset and the field type,
+    // fields of nested structs and tuples are folded into a single offset from the outermost one,
+    // a field reached through a reference starts from the address the reference holds
+    fn field_address(&self,access:&FieldAccess,symbol_table:Rc<SymbolTable>) -> Option<(String,String,u32,DataType)> {
+        let target_type = Self::type_of(&access.target, symbol_table.clone())?;
+        let through_reference = matches!(target_type,DataType::Reference(_,_));
+        let (field_type,field_offset) = match Self::dereferenced(target_type) {
+            DataType::Struct(name) => symbol_table.lookup_struct(name)?.get_field(&access.field.node)?,
+            DataType::Tuple(elements) => Self::tuple_element(&elements, &access.field.node, &symbol_table)?,
+            _ => return None
+        };
+        if let (Node::FieldAccess(inner),false) = (&access.target.node,through_reference) {
+            let (code,base,offset,_) = self.field_address(inner, symbol_table.clone())?;
+            return Some((code,base,offset + field_offset,field_type));
+        }
+        let target_expression = self.gen_ir(&access.target, symbol_table.clone());
+        if target_expression.starts_with("tac_temp") {
+            let base = "tac_temp".to_string() + &symbol_table.get_var_version(String::from("tac_temp")).to_string();
+            return Some((target_expression,base,field_offset,field_type));
+        }
+        Some((String::new(),target_expression,field_offset,field_type))
+    }
+
 }
 
 // enum IrStatement{
@@ -3,16 +3,24 @@ mod arkparser;
 mod ir_generation;
 mod semantic_analyzer;
 mod symbol_table;
+mod syntax_tree;
+mod modules;
 use clap::{builder::OsStr, Parser};
 use semantic_analyzer::SemanticAnalyzer;
-use symbol_table::SymbolTable;
+use symbol_table::{ModuleAttribute, SymbolTable};
 use tokenizer::Tokenizer;
 use arkparser::ArkParser;
+use modules::{Module, ModuleLoader};
 use std::{cell::RefCell, fs, io::Write, path::{Path, PathBuf}, rc::Rc};
 use colored::Colorize;
 #[derive(Parser)]
 struct Cli{
     source:PathBuf,
+    #[arg(long)]
+    cst:bool,
+    // trap on out of bounds array indices computed at runtime
+    #[arg(long)]
+    bounds_check:bool,
 }
 
 #[derive(Clone)]
@@ -21,6 +29,10 @@ struct CompilerError{
     error_message:String,
     pos: (u32,u32),
     length: u32,
+    // printed below the source line
+    note: Option<String>,
+    // a second place in the source the error is about, printed with its own text
+    label: Option<((u32,u32),u32,String)>,
 }
 
 
@@ -30,15 +42,27 @@ impl CompilerError{
             error_type,
             error_message:String::from(error_message),
             pos:error_pos,
-            length
+            length,
+            note:None,
+            label:None
         }
     }
+    pub fn with_note(mut self,note:&str) -> CompilerError{
+        self.note = Some(String::from(note));
+        self
+    }
+    pub fn with_label(mut self,pos:(u32,u32),length:u32,label:&str) -> CompilerError{
+        self.label = Some((pos,length,String::from(label)));
+        self
+    }
 }
 #[derive(Debug,Clone, Copy)]
 pub enum ErrorType {
     SemanticError,
     SyntaxError,
-    LexicalError
+    LexicalError,
+    // reported like an error but the code is still generated
+    Warning
 }
 
 impl ErrorType{
@@ -46,7 +70,8 @@ impl ErrorType{
         match self {
             ErrorType::SemanticError => "Sematic Error",
             ErrorType::SyntaxError => "Syntax Error",
-            ErrorType::LexicalError => "Lexical Error"
+            ErrorType::LexicalError => "Lexical Error",
+            ErrorType::Warning => "Warning"
         }
     }
 }
@@ -62,7 +87,9 @@ impl ErrorPipeline {
                 error_type,
                 error_message:String::from(error_message),
                 pos:error_pos,
-                length
+                length,
+                note:None,
+                label:None
             }
         )
     }
@@ -74,6 +101,66 @@ impl ErrorPipeline {
 }
 
 
+// prints a source line with the columns [col,col+length) marked below it
+fn print_snippet(source_lines:&[&str],space:&str,pos:(u32,u32),length:u32,marker:&str,text:&str) {
+    // errors reported at end of file can point one line past the source
+    let source_snippet = source_lines.get((pos.0.saturating_sub(1)) as usize).copied().unwrap_or("");
+    let trimed_snippet = source_snippet.trim_start();
+    let indent = (source_snippet.len()-trimed_snippet.len()) as u32;
+    let error_col = pos.1.saturating_sub(indent + 1) as usize;
+    print!("{:>width$} |     ",pos.0,width = space.len());
+    for (i,ch) in trimed_snippet.chars().enumerate(){
+        if i >= error_col && i < error_col + length as usize{
+            print!("{}",ch.to_string().red());
+        }
+        else {
+            print!("{}",ch);
+        }
+    }
+    let mut arrow = " ".repeat(error_col);
+    arrow.push_str(&marker.repeat(length as usize));
+    if !text.is_empty() {
+        arrow = format!("{} {}",arrow,text);
+    }
+    println!("\n{} |     {}",space,arrow.red());
+}
+
+fn print_errors(module:&Module) {
+    let source_lines = module.source.split('\n').collect::<Vec<&str>>();
+    for e in module.error_pipe.error_generated.borrow().clone().into_iter() {
+        let widest_line = e.label.as_ref().map_or(e.pos.0,|(pos,_,_)| pos.0.max(e.pos.0));
+        let size = widest_line.to_string().len();
+        let mut space = String::new();
+        for _ in 0..size {
+            space += " ";
+        }
+        let error_type = match e.error_type {
+            ErrorType::Warning => e.error_type.as_str().yellow().bold(),
+            _ => e.error_type.as_str().red().bold()
+        };
+        println!(
+            "{}: {}",
+            error_type,
+            e.error_message.white().bold()
+        );
+        println!(
+            "{}--> {}:{}:{}",
+            space,
+            module.path.display(),
+            e.pos.0,
+            e.pos.1,
+        );
+        println!("{} |",space);
+        print_snippet(&source_lines, &space, e.pos, e.length, "^", "");
+        if let Some((pos,length,label)) = &e.label {
+            print_snippet(&source_lines, &space, *pos, *length, "-", label);
+        }
+        if let Some(note) = &e.note {
+            println!("{} = note: {}",space,note);
+        }
+    }
+}
+
 fn main(){
     let args = Cli::parse();
     let source_code = match fs::read_to_string(args.source.clone()){
@@ -81,59 +168,59 @@ fn main(){
         Err(_)=>panic!("Unable to find your source code"),
     };
     let error_pipe = ErrorPipeline {error_generated:RefCell::new(vec![])} ;
-    let mut tokenizer = Tokenizer::new(source_code.as_str(), &error_pipe);
-    let mut parser = ArkParser::new(&mut tokenizer,&error_pipe);
-    // let mut bin_location = match std::env::current_exe(){
-    //     Ok(path) => path,
-    //     Err(_) => panic!("can't find bin path"),
-    // };
-    let ast = parser.parse();
+    let ast = {
+        let mut tokenizer = Tokenizer::new(source_code.as_str(), &error_pipe);
+        tokenizer.emit_trivia(args.cst);
+        let mut parser = ArkParser::new(&mut tokenizer,&error_pipe);
+        // let mut bin_location = match std::env::current_exe(){
+        //     Ok(path) => path,
+        //     Err(_) => panic!("can't find bin path"),
+        // };
+        if args.cst {
+            let (ast,syntax_tree) = parser.parse_lossless();
+            print!("{}",syntax_tree.to_tree_string());
+            if syntax_tree.text() != source_code {
+                panic!("syntax tree does not reproduce the source");
+            }
+            ast
+        }
+        else {
+            parser.parse()
+        }
+    };
     println!("{:#?}",ast);
-    let semantic_analyzer = SemanticAnalyzer::new(&ast, &error_pipe);
-    let global_symbol_table = semantic_analyzer.analyze();
-    if error_pipe.error_generated.borrow().is_empty(){
-        let generator = ir_generation::IRGenerator::new(global_symbol_table);
-        println!("{}",generator.get_intermediate_representation(&ast))
+    let main_module = Module::new(args.source.clone(), String::new(), source_code, ast, error_pipe);
+    let modules = ModuleLoader::new().load(main_module);
+    // a module is analyzed after the modules it imports so their functions are known
+    let mut symbol_tables:Vec<Rc<SymbolTable>> = vec![];
+    let mut instances = vec![];
+    for module in &modules {
+        let imports = module.imports.iter()
+            .map(|(import_name,index)| (import_name.clone(),ModuleAttribute {
+                alias: String::new(),
+                module_name: modules[*index].name.clone(),
+                module_path: modules[*index].path.display().to_string(),
+                module_table: Some(symbol_tables[*index].clone())
+            }))
+            .collect();
+        let mut semantic_analyzer = SemanticAnalyzer::new(&module.ast, &module.error_pipe);
+        semantic_analyzer.import_modules(imports);
+        symbol_tables.push(semantic_analyzer.analyze());
+        instances.push(semantic_analyzer.take_instances());
     }
-    else{
-        let source_lines = source_code.split('\n').collect::<Vec<&str>>();
-        for e in error_pipe.error_generated.borrow().clone().into_iter() {
-            let size = e.pos.0.to_string().len();
-            let mut space = String::new();
-            for _ in 0..size {
-                space += " ";
-            }
-            let source_snippet = source_lines[(e.pos.0-1) as usize];
-            let trimed_snippet = source_snippet.trim();
-            let error_col = (e.pos.1-(source_snippet.len()-trimed_snippet.len()) as u32 -1) as usize;
-            println!(
-                "{}: {}",
-                e.error_type.as_str().red().bold(),
-                e.error_message.white().bold()
-            );
-            println!(
-                "{}--> {}:{}:{}",
-                space,
-                args.source.clone().into_os_string().into_string().unwrap(),
-                e.pos.0,
-                e.pos.1,
-            );
-            println!("{} |",space);
-            print!("{} |     ",e.pos.0);
-            for (i,ch) in trimed_snippet.chars().enumerate(){
-                if i >= error_col && i < error_col + e.length as usize{
-                    print!("{}",ch.to_string().red());
-                }
-                else {
-                    print!("{}",ch.to_string());
-                }
-            }
-            let mut arrow = std::iter::repeat(" ").take(error_col).collect::<String>();
-            arrow.extend(std::iter::repeat("^").take(e.length.try_into().unwrap()));
-            println!("\n{} |     {}",space,arrow.red());
+    let failed = modules.iter().any(|m| m.error_pipe.error_generated.borrow().iter().any(|e| !matches!(e.error_type,ErrorType::Warning)));
+    if !failed {
+        let mut intermediate_representation = String::new();
+        for ((module,symbol_table),instances) in modules.iter().zip(symbol_tables).zip(instances) {
+            let mut generator = ir_generation::IRGenerator::new(symbol_table);
+            generator.emit_bounds_checks(args.bounds_check).module_name(module.name.clone()).instances(instances);
+            intermediate_representation += generator.get_intermediate_representation(&module.ast).as_str();
         }
+        println!("{}",intermediate_representation)
+    }
+    for module in &modules {
+        print_errors(module);
     }
-    
             //println!("{:#?}",symbol_table);
     
 
@@ -0,0 +1,147 @@
+use std::{cell::RefCell, collections::HashMap, fs, path::{Path, PathBuf}};
+use crate::{arkparser::{ArkParser, Body, Node}, tokenizer::Tokenizer, CompilerError, ErrorPipeline, ErrorType};
+
+// the standard library is bundled with the compiler, `import "stdio"` finds it
+// before looking for a file
+const STD_MODULES: [(&str,&str);3] = [
+    ("stdio",include_str!("std/stdio.ark")),
+    ("math",include_str!("std/math.ark")),
+    ("string",include_str!("std/string.ark")),
+];
+
+// a source file of the program with its own errors, the errors are printed
+// against the source of the file they were found in
+pub struct Module {
+    pub path:PathBuf,
+    // prefix of the functions of the module in the ir, empty for the main file
+    pub name:String,
+    pub source:String,
+    pub ast:Body,
+    pub error_pipe:ErrorPipeline,
+    // import name as written in the source -> index of the imported module
+    pub imports:HashMap<String,usize>,
+}
+
+impl Module {
+    pub fn new(path:PathBuf,name:String,source:String,ast:Body,error_pipe:ErrorPipeline) -> Module {
+        Module { path, name, source, ast, error_pipe, imports: HashMap::new() }
+    }
+}
+
+// loads the files imported by a module, every file is parsed once no matter how often
+// it is imported and the modules are kept in an order where a module comes after
+// every module it imports, which is the order they are analyzed and generated in
+pub struct ModuleLoader {
+    modules:Vec<Module>,
+    // canonical path -> index in modules
+    loaded:HashMap<PathBuf,usize>,
+    // canonical paths of the modules whose imports are being loaded
+    loading:Vec<PathBuf>,
+}
+
+impl ModuleLoader {
+    pub fn new() -> Self {
+        ModuleLoader { modules: vec![], loaded: HashMap::new(), loading: vec![] }
+    }
+
+    // loads everything the main module imports, the main module is the last one
+    pub fn load(mut self,mut main:Module) -> Vec<Module> {
+        let canonical = fs::canonicalize(&main.path).unwrap_or(main.path.clone());
+        self.loading.push(canonical);
+        self.load_imports(&mut main);
+        self.loading.pop();
+        self.modules.push(main);
+        self.modules
+    }
+
+    fn load_imports(&mut self,module:&mut Module) {
+        let imports = module.ast.instructions.iter().filter_map(|node| match &node.node {
+            Node::Import(imp) => Some(imp.import_name.clone()),
+            _ => None
+        }).collect::<Vec<_>>();
+        for import_name in imports {
+            let std_source = STD_MODULES.iter().find(|(name,_)| *name == import_name.node).map(|(_,source)| source.to_string());
+            let mut path = match std_source {
+                Some(_) => PathBuf::from(format!("<std>/{}",import_name.node)),
+                None => module.path.parent().unwrap_or(Path::new("")).join(&import_name.node),
+            };
+            if path.extension().is_none() {
+                path.set_extension("ark");
+            }
+            let canonical = match std_source.as_ref().map_or_else(|| fs::canonicalize(&path), |_| Ok(path.clone())) {
+                Ok(p) => p,
+                Err(_) => {
+                    module.error_pipe.report_error(
+                        CompilerError::new(
+                            ErrorType::SemanticError,
+                            format!("cannot find module '{}'",import_name.node).as_str(),
+                            import_name.pos,
+                            import_name.length,
+                        )
+                    );
+                    continue;
+                }
+            };
+            if let Some(start) = self.loading.iter().position(|p| *p == canonical) {
+                let cycle = self.loading[start..].iter().chain([&canonical])
+                    .map(|p| p.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default())
+                    .collect::<Vec<String>>();
+                module.error_pipe.report_error(
+                    CompilerError::new(
+                        ErrorType::SemanticError,
+                        format!("import cycle {}",cycle.join(" -> ")).as_str(),
+                        import_name.pos,
+                        import_name.length,
+                    )
+                );
+                continue;
+            }
+            if let Some(index) = self.loaded.get(&canonical) {
+                module.imports.insert(import_name.node.clone(), *index);
+                continue;
+            }
+            let source = match std_source.clone().map_or_else(|| fs::read_to_string(&canonical), Ok) {
+                Ok(source) => source,
+                Err(_) => {
+                    module.error_pipe.report_error(
+                        CompilerError::new(
+                            ErrorType::SemanticError,
+                            format!("cannot read module '{}'",import_name.node).as_str(),
+                            import_name.pos,
+                            import_name.length,
+                        )
+                    );
+                    continue;
+                }
+            };
+            let error_pipe = ErrorPipeline { error_generated: RefCell::new(vec![]) };
+            let ast = {
+                let mut tokenizer = Tokenizer::new(source.as_str(), &error_pipe);
+                let mut parser = ArkParser::new(&mut tokenizer, &error_pipe);
+                if std_source.is_some() {
+                    parser.allow_runtime_declarations();
+                }
+                parser.parse()
+            };
+            let mut imported = Module::new(path, self.module_name(&canonical), source, ast, error_pipe);
+            self.loading.push(canonical.clone());
+            self.load_imports(&mut imported);
+            self.loading.pop();
+            self.modules.push(imported);
+            self.loaded.insert(canonical, self.modules.len() - 1);
+            module.imports.insert(import_name.node.clone(), self.modules.len() - 1);
+        }
+    }
+
+    // the file name without its extension, numbered when another module already uses it
+    fn module_name(&self,path:&Path) -> String {
+        let stem = path.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+        let mut name = stem.clone();
+        let mut count = 1;
+        while self.modules.iter().any(|m| m.name == name) {
+            count += 1;
+            name = format!("{}{}",stem,count);
+        }
+        name
+    }
+}
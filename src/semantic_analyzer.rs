@@ -1,5 +1,5 @@
-use std::{any::Any, borrow::BorrowMut, collections::HashMap, mem::discriminant, rc::Rc, thread::scope};
-use crate::{arkparser::{AstNode, BinExp, Body, FuncDef, LiteralValue, Node, ParserError, Var}, symbol_table::{self, Scope, SymbolTable}, tokenizer::{Array, DataType, Token}, CompilerError, ErrorPipeline, ErrorType};
+use std::{cell::RefCell, collections::HashMap, mem::{self, discriminant}, path::Path, rc::Rc};
+use crate::{arkparser::{ArrayLiteral, AstNode, BinExp, Body, CastExp, Destructure, EnumValue, ForLoop, FuncCall, FuncDef, ImplBlock, InfiniteLoop, MethodCall, TraitDef, LiteralValue, MatchExp, Node, Pattern, Range, Reference, Var}, symbol_table::{self, CallingConvention, EnumAttribute, FuncAttribute, LoopAttribute, ModuleAttribute, Scope, StructAttribute, SymbolTable, VarAttribute}, tokenizer::{Array, DataType}, CompilerError, ErrorPipeline, ErrorType};
 use crate::tokenizer::TokenType;
 use enum_map::{enum_map,EnumMap};
 pub struct OperationValidator {
@@ -40,11 +40,156 @@ impl Operation {
     }
 }
 
+// what the arms of a match have covered so far,
+// integer ranges are kept as half open intervals
+struct MatchCoverage {
+    wildcard:bool,
+    variants:Vec<String>,
+    bools:Vec<bool>,
+    intervals:Vec<(i128,i128)>,
+    literals:Vec<String>,
+}
+
+impl MatchCoverage {
+    fn new() -> Self {
+        MatchCoverage { wildcard: false, variants: vec![], bools: vec![], intervals: vec![], literals: vec![] }
+    }
+
+    // first value of [start,end) not covered by any interval
+    fn first_uncovered(&self,start:i128,end:i128) -> Option<i128> {
+        let mut intervals = self.intervals.clone();
+        intervals.sort();
+        let mut current = start;
+        for (low,high) in intervals {
+            if low > current {
+                break;
+            }
+            if high > current {
+                current = high;
+            }
+        }
+        if current < end {
+            return Some(current);
+        }
+        None
+    }
+
+    fn covers(&self,pattern:&Pattern) -> bool {
+        if self.wildcard {
+            return true;
+        }
+        match pattern {
+            Pattern::Wildcard => false,
+            Pattern::Literal(LiteralValue::Bool(b)) => self.bools.contains(b),
+            Pattern::Literal(LiteralValue::Int(i)) => self.first_uncovered(*i as i128, *i as i128 + 1).is_none(),
+            Pattern::Range(LiteralValue::Int(start),LiteralValue::Int(end),inclusive) => self.first_uncovered(*start as i128, *end as i128 + *inclusive as i128).is_none(),
+            Pattern::Literal(literal) => self.literals.contains(&format!("{:?}",literal)),
+            Pattern::Range(_,_,_) => false,
+            Pattern::Variant(v) => self.variants.contains(&v.variant.node),
+        }
+    }
+
+    fn add(&mut self,pattern:&Pattern) {
+        match pattern {
+            Pattern::Wildcard => self.wildcard = true,
+            Pattern::Literal(LiteralValue::Bool(b)) => self.bools.push(*b),
+            Pattern::Literal(LiteralValue::Int(i)) => self.intervals.push((*i as i128,*i as i128 + 1)),
+            Pattern::Range(LiteralValue::Int(start),LiteralValue::Int(end),inclusive) => self.intervals.push((*start as i128,*end as i128 + *inclusive as i128)),
+            Pattern::Literal(literal) => self.literals.push(format!("{:?}",literal)),
+            Pattern::Range(_,_,_) => (),
+            Pattern::Variant(v) => self.variants.push(v.variant.node.clone()),
+        }
+    }
+
+    // describes what is still not covered, None when the match is exhaustive
+    fn missing(&self,scrutinee_type:&DataType,enum_attribute:&Option<EnumAttribute>) -> Option<String> {
+        if self.wildcard {
+            return None;
+        }
+        if let Some(e) = enum_attribute {
+            let missing = e.variants.iter()
+                .filter(|(name,_)| !self.variants.contains(name))
+                .map(|(name,_)| format!("'{}::{}'",e.enum_name,name))
+                .collect::<Vec<String>>();
+            if missing.is_empty() {
+                return None;
+            }
+            return Some(format!("{} not covered",missing.join(", ")));
+        }
+        match scrutinee_type {
+            DataType::Boolean => {
+                let missing = [true,false].iter()
+                    .filter(|b| !self.bools.contains(b))
+                    .map(|b| format!("'{}'",b))
+                    .collect::<Vec<String>>();
+                if missing.is_empty() {
+                    return None;
+                }
+                Some(format!("{} not covered",missing.join(", ")))
+            },
+            DataType::I8 | DataType::I16 | DataType::I32 | DataType::I64 |
+            DataType::U8 | DataType::U16 | DataType::U32 | DataType::U64 => {
+                let (start,end) = scrutinee_type.int_domain();
+                self.first_uncovered(start, end).map(|value| format!("'{}' not covered",value))
+            },
+            _ => Some(String::from("add a '_' arm")),
+        }
+    }
+}
+
+// a reference taken to a variable of the function being checked
+#[derive(Clone)]
+struct Borrow {
+    variable:String,
+    // scope the borrowed variable is declared in
+    owner:Rc<SymbolTable>,
+    mutable:bool,
+    pos:(u32,u32),
+    length:u32,
+    // where the borrowed place names the variable, that is not a use of it
+    root_pos:(u32,u32),
+    // the variable the reference is stored in, a reference that is not stored
+    // only lives as long as the expression it is in
+    holder:Option<(String,Rc<SymbolTable>)>,
+    // where the holder got the reference, later than pos when it is copied from another variable
+    held_from:(u32,u32),
+}
+
+// a read or an assignment of a variable of the function being checked
+struct VarUse {
+    variable:String,
+    owner:Rc<SymbolTable>,
+    // scope the variable is used in
+    scope:Rc<SymbolTable>,
+    pos:(u32,u32),
+    length:u32,
+    write:bool,
+}
+
+// what the borrow checker needs of a function, checked once its whole body is known
+#[derive(Default)]
+struct BorrowLog {
+    borrows:Vec<Borrow>,
+    uses:Vec<VarUse>,
+}
+
 pub struct SemanticAnalyzer<'a,'b> {
     ast:&'a Body,
     error_pipe:&'b ErrorPipeline,
     symbol_table:Rc<SymbolTable>,
-    operation_validator:OperationValidator
+    operation_validator:OperationValidator,
+    // import name -> module it loaded, the alias is filled in by the import
+    modules:HashMap<String,ModuleAttribute>,
+    // generic functions are checked once per instance, not where they are declared
+    generic_functions:RefCell<HashMap<String,FuncDef>>,
+    // type parameter -> type, set while the body of an instance is checked
+    type_bindings:RefCell<HashMap<String,DataType>>,
+    // the functions instantiated from generic functions, generated after the module
+    instances:RefCell<Vec<AstNode<Node>>>,
+    // traits only constrain impl blocks, every method call is resolved on the type of its receiver
+    traits:RefCell<HashMap<String,TraitDef>>,
+    // one log per function being checked, the innermost function last
+    borrow_logs:RefCell<Vec<BorrowLog>>
 }
 
 impl<'a,'b> SemanticAnalyzer<'a,'b> {
@@ -53,9 +198,19 @@ impl<'a,'b> SemanticAnalyzer<'a,'b> {
             ast,
             error_pipe,
             symbol_table:Rc::new(SymbolTable::new(symbol_table::Scope::Global)),
-            operation_validator:OperationValidator {allow_list:vec![]}
+            operation_validator:OperationValidator {allow_list:vec![]},
+            modules:HashMap::new(),
+            generic_functions:RefCell::new(HashMap::new()),
+            type_bindings:RefCell::new(HashMap::new()),
+            instances:RefCell::new(vec![]),
+            traits:RefCell::new(HashMap::new()),
+            borrow_logs:RefCell::new(vec![])
         }
     }
+    pub fn import_modules(&mut self,modules:HashMap<String,ModuleAttribute>) -> &mut Self {
+        self.modules = modules;
+        self
+    }
 
     fn get_int_type(int:i64) -> Option<DataType> {
         if int <= i8::MAX.into() && int >= i8::MIN.into(){
@@ -124,226 +279,1592 @@ impl<'a,'b> SemanticAnalyzer<'a,'b> {
         // ]);
     }
 
-    fn type_coercion(&self,first_type:&DataType,second_type:&DataType) -> Option<DataType>{
-        if Self::type_castable(first_type, second_type){
-            return Some(second_type.clone());
+    // the conversions `as` allows, Some(true) when some values of the original
+    // type cannot be represented by the target type
+    fn explicit_cast(original_type:&DataType,target_type:&DataType) -> Option<bool> {
+        // integers up to 2^mantissa bits are exact in a float
+        let exact_float = |float_type:&DataType| match float_type {
+            DataType::F32 => 1i128 << 24,
+            _ => 1i128 << 53
+        };
+        let char_domain = (0,0x110000);
+        match (original_type,target_type) {
+            _ if original_type == target_type => Some(false),
+            (DataType::Boolean,target) if Self::is_integer(target) => Some(false),
+            (original,target) if Self::is_integer(original) && Self::is_integer(target) => {
+                let (start,end) = original.int_domain();
+                let (target_start,target_end) = target.int_domain();
+                Some(start < target_start || end > target_end)
+            },
+            (original,DataType::F32 | DataType::F64) if Self::is_integer(original) => {
+                let (start,end) = original.int_domain();
+                Some(start < -exact_float(target_type) || end - 1 > exact_float(target_type))
+            },
+            (DataType::F32 | DataType::F64,target) if Self::is_integer(target) => Some(true),
+            (DataType::F32,DataType::F64) => Some(false),
+            (DataType::F64,DataType::F32) => Some(true),
+            (DataType::Char,target) if Self::is_integer(target) => {
+                let (target_start,target_end) = target.int_domain();
+                Some(char_domain.0 < target_start || char_domain.1 > target_end)
+            },
+            // not every u32 is a unicode scalar value
+            (DataType::U8,DataType::Char) => Some(false),
+            (DataType::U32,DataType::Char) => Some(true),
+            _ => None
         }
-        if Self::type_castable( second_type,first_type){
-            return Some(first_type.clone());
+    }
+
+    fn is_integer(data_type:&DataType) -> bool {
+        matches!(
+            data_type,
+            DataType::I8 | DataType::I16 | DataType::I32 | DataType::I64
+            | DataType::U8 | DataType::U16 | DataType::U32 | DataType::U64
+        )
+    }
+
+    // the types a C function agrees on with us, aggregates are addresses into our own layout
+    fn is_c_type(data_type:&DataType) -> bool {
+        match data_type {
+            DataType::Void | DataType::Boolean | DataType::Char | DataType::F32 | DataType::F64 => true,
+            _ => Self::is_integer(data_type)
         }
-        None
     }
 
-    fn check_expression_type(&self, node:&AstNode<Node>,symbol_table:&Rc<SymbolTable>) -> Option<DataType>{
-        let symbol_table = Rc::clone(symbol_table);
-        match &node.node {
-            Node::Variable(v) => {
-                match symbol_table.lookup_var(v.clone()){
-                    Some((var,scope)) => {
-                        let line = node.pos.0;
-                        if let Some(line_declare) = var.line_declare {
-                            if line < line_declare {
-                                self.error_pipe.report_error(
-                                    CompilerError::new(
-                                        ErrorType::SemanticError,
-                                        format!("use of unclared variable '{}'",v).as_str(),
-                                        node.pos,
-                                        v.len() as u32,
-                                    )
-                                );
-                                symbol_table.var_push_line_ref_at(scope, v.clone(), node.pos.0);
-                                return None;
-                            }
-                            symbol_table.var_push_line_ref_at(scope, v.clone(), node.pos.0);
-                            return Some(var.data_type.unwrap());
-                        }
-                        return None;
-                    },
-                    None => {
-                        self.error_pipe.report_error(
-                            CompilerError::new(
-                                ErrorType::SemanticError,
-                                format!("use of unclared variable '{}'",v).as_str(),
-                                node.pos,
-                                v.len() as u32,
-                            )
-                        );
-                        return None;
-                    }
+    fn is_unsigned(data_type:&DataType) -> bool {
+        matches!(data_type,DataType::U8 | DataType::U16 | DataType::U32 | DataType::U64)
+    }
+
+    // structs and enums only match themselves, arrays and slices need the exact
+    // element type since it decides their layout, an array can be used as a slice,
+    // other types keep the implicit widening rules
+    fn type_matches(expected:&DataType,found:&DataType) -> bool {
+        // a &mut T can be used where a &T is expected but not the other way around
+        if let (DataType::Reference(expected_target,expected_mutable),DataType::Reference(found_target,found_mutable)) = (expected,found) {
+            return expected_target == found_target && (*found_mutable || !*expected_mutable);
+        }
+        if let (DataType::Reference(_,_),_) | (_,DataType::Reference(_,_)) = (expected,found) {
+            return false;
+        }
+        if let (DataType::Struct(_) | DataType::Enum(_),_) | (_,DataType::Struct(_) | DataType::Enum(_)) = (expected,found) {
+            return expected == found;
+        }
+        if let (DataType::Slice(element),DataType::Array(found_array)) = (expected,found) {
+            return **element == *found_array.data_type;
+        }
+        if let (DataType::Array(_) | DataType::Slice(_) | DataType::Range(_) | DataType::Tuple(_) | DataType::Function(_,_) | DataType::Option(_) | DataType::Result(_,_),_) | (_,DataType::Array(_) | DataType::Slice(_) | DataType::Range(_) | DataType::Tuple(_) | DataType::Function(_,_) | DataType::Option(_) | DataType::Result(_,_)) = (expected,found) {
+            return expected == found;
+        }
+        discriminant(expected) == discriminant(found) || Self::type_castable(found,expected)
+    }
+
+    // the parser reads every user defined type name as a struct, this turns the
+    // names declared by an enum into DataType::Enum and reports unknown names
+    fn resolve_type(&self,data_type:&AstNode<DataType>,symbol_table:&Rc<SymbolTable>) -> Option<DataType> {
+        match &data_type.node {
+            DataType::Array(arr) => {
+                let inner = self.resolve_type(&AstNode::new((*arr.data_type).clone(),data_type.pos,data_type.length), symbol_table)?;
+                Some(DataType::Array(Array { length: arr.length, data_type: Box::new(inner) }))
+            },
+            DataType::Tuple(elements) => {
+                let mut resolved = vec![];
+                for element in elements {
+                    resolved.push(self.resolve_type(&AstNode::new(element.clone(),data_type.pos,data_type.length), symbol_table)?);
                 }
-            }
-            Node::Assignment(tk) => {
-                let left_type = match &tk.left.node {
-                    Node::DeclareVar(v) => {
-                        symbol_table.insert_var(v.name.node.clone());
-                        symbol_table.update_var(
-                            v.name.node.clone(),
-                            Some(v.var_type.node.clone()),
-                            Some(v.var_type.node.get_size_in_bytes()),
-                            Some(0),
-                            Some(v.name.pos.0)
-                        );
-                        v.var_type.node.clone()
-                    },
-                    Node::Variable(v) => {
-                        match symbol_table.lookup_var(v.clone()){
-                            Some((var,scope)) => {
-                                let line = node.pos.0;
-                                if let Some(line_declare) = var.line_declare {
-                                    if line < line_declare {
-                                        self.error_pipe.report_error(
-                                            CompilerError::new(
-                                                ErrorType::SemanticError,
-                                                format!("use of unclared variable '{}'",v).as_str(),
-                                                node.pos,
-                                                v.len() as u32,
-                                            )
-                                        );
-                                        symbol_table.var_push_line_ref_at(scope, v.clone(), node.pos.0);
-                                        return None;
-                                    }
-                                    symbol_table.var_push_line_ref_at(scope, v.clone(), node.pos.0);
-                                    var.data_type.unwrap()
-                                }
-                                else{
-                                    self.error_pipe.report_error(
-                                        CompilerError::new(
-                                            ErrorType::SemanticError,
-                                            format!("use of unclared variable '{}'",v).as_str(),
-                                            node.pos,
-                                            v.len() as u32,
-                                        )
-                                    );
-                                    symbol_table.var_push_line_ref_at(scope, v.clone(), node.pos.0);
-                                    return None;
-                                }
-                            },
-                            None => {
-                                self.error_pipe.report_error(
-                                    CompilerError::new(
-                                        ErrorType::SemanticError,
-                                        format!("use of unclared variable '{}'",v).as_str(),
-                                        node.pos,
-                                        v.len() as u32,
-                                    )
-                                );
-                                return None;
-                            }
-                        }
-                    },
-                    _ => {
-                        self.error_pipe.report_error(
-                            CompilerError::new(
-                                ErrorType::SemanticError,
-                                format!("left operand can't assign to").as_str(),
-                                node.pos,
-                                node.length,
-                            )
-                        );
-                        return None;
-                    }
+                Some(DataType::Tuple(resolved))
+            },
+            DataType::Slice(element) => {
+                let inner = self.resolve_type(&AstNode::new((**element).clone(),data_type.pos,data_type.length), symbol_table)?;
+                Some(DataType::Slice(Box::new(inner)))
+            },
+            DataType::Reference(target,mutable) => {
+                let inner = self.resolve_type(&AstNode::new((**target).clone(),data_type.pos,data_type.length), symbol_table)?;
+                Some(DataType::Reference(Box::new(inner),*mutable))
+            },
+            DataType::Option(_) | DataType::Result(_,_) => {
+                let arguments = match &data_type.node {
+                    DataType::Option(value) => vec![value],
+                    DataType::Result(value,error) => vec![value,error],
+                    _ => vec![]
                 };
-                match self.check_expression_type(&tk.right, &symbol_table){
-                    Some(right_type) => {
-                        if discriminant(&left_type) == discriminant(&right_type) || Self::type_castable(&right_type,&left_type) {
-                            return Some(left_type);
-                        }
+                let mut resolved = vec![];
+                for argument in arguments {
+                    let argument = self.resolve_type(&AstNode::new((**argument).clone(),data_type.pos,data_type.length), symbol_table)?;
+                    if argument == DataType::Void {
                         self.error_pipe.report_error(
                             CompilerError::new(
                                 ErrorType::SemanticError,
-                                format!("expected '{}' found '{}'",left_type.to_string(),right_type.to_string()).as_str(),
-                                node.pos,
-                                node.length,
+                                format!("'{}' cannot hold 'void'",data_type.node.to_string()).as_str(),
+                                data_type.pos,
+                                data_type.length,
                             )
                         );
                         return None;
-                    },
-                    None => {
-                        return None;
                     }
+                    resolved.push(argument);
+                }
+                let mut resolved = resolved.into_iter();
+                match (resolved.next(),resolved.next()) {
+                    (Some(value),Some(error)) => Some(DataType::Result(Box::new(value),Box::new(error))),
+                    (Some(value),None) => Some(DataType::Option(Box::new(value))),
+                    _ => None
                 }
-                
             },
-            Node::Literal(literal) => {
-                match literal {
-                    LiteralValue::Int(i) => {
-                        match Self::get_int_type(*i){
-                            Some(t) => return Some(t),
-                            None => {
-                                self.error_pipe.report_error(
-                                    CompilerError::new(
-                                        ErrorType::SemanticError,
-                                        format!("value is too large").as_str(),
-                                        node.pos,
-                                        node.length,
-                                    )
-                                );
-                                return None;
-                            }
-                        }
-                    },
-                    LiteralValue::Float(f) => {
-                        match Self::get_float_type(*f){
-                            Some(t) => return Some(t),
-                            None => {
-                                self.error_pipe.report_error(
-                                    CompilerError::new(
-                                        ErrorType::SemanticError,
-                                        format!("value is too large").as_str(),
-                                        node.pos,
-                                        node.length,
-                                    )
-                                );
-                                return None;
-                            }
-                        }
-                    },
-                    LiteralValue::Str(st) => {
-                        return Some(DataType::Str(st.len() as u32))
-                    },
-                    LiteralValue::Bool(b) => {
-                        return Some(DataType::Boolean);
-                    }
+            DataType::Function(parameters,return_type) => {
+                let mut resolved = vec![];
+                for parameter in parameters {
+                    resolved.push(self.resolve_type(&AstNode::new(parameter.clone(),data_type.pos,data_type.length), symbol_table)?);
                 }
+                let return_type = self.resolve_type(&AstNode::new((**return_type).clone(),data_type.pos,data_type.length), symbol_table)?;
+                Some(DataType::Function(resolved,Box::new(return_type)))
             },
-            Node::BinaryExpression(exp) => {
-                let left_type = match self.check_expression_type(&exp.left, &symbol_table){
-                    Some(dt) => dt,
-                    None => return None
-                };
-                let right_type = match self.check_expression_type(&exp.right, &symbol_table){
-                    Some(right_type) => {
-                        
-                        right_type
-                    },
-                    None => {
-                        return None;
-                    }
-                };
-                
-                let operand_type = if left_type != right_type {
-                    match self.type_coercion(&left_type, &right_type) {
-                        Some(result_type) => result_type,
-                        None => {
-                            self.error_pipe.report_error(
-                                CompilerError::new(
-                                    ErrorType::SemanticError,
-                                    format!("operand have mismatched type '{}' and '{}'",left_type.to_string(),right_type.to_string()).as_str(),
-                                    exp.operator.pos,
-                                    exp.operator.length,
-                                )
-                            );
-                            return None
-                        },
-                    }
+            DataType::Struct(name) => {
+                if let Some(bound) = self.type_bindings.borrow().get(name) {
+                    return Some(bound.clone());
                 }
-                else{
-                    left_type
+                if let Some((alias,type_name)) = name.split_once("::") {
+                    return self.resolve_module_type(alias, type_name, data_type, symbol_table);
+                }
+                if symbol_table.lookup_struct(name.clone()).is_some() {
+                    return Some(data_type.node.clone());
+                }
+                if symbol_table.lookup_enum(name.clone()).is_some() {
+                    return Some(DataType::Enum(name.clone()));
+                }
+                self.error_pipe.report_error(
+                    CompilerError::new(
+                        ErrorType::SemanticError,
+                        format!("unknown type '{}'",name).as_str(),
+                        data_type.pos,
+                        data_type.length,
+                    )
+                );
+                None
+            },
+            _ => Some(data_type.node.clone())
+        }
+    }
+
+    // the type of a variable declared without one, integer literals default to i32 and
+    // float literals to f64 like the elements of an array literal made of them
+    fn inferred_type(initializer:&AstNode<Node>,found:DataType) -> DataType {
+        match (&initializer.node,found) {
+            (Node::Literal(LiteralValue::Int(_)),DataType::I8 | DataType::I16) => DataType::I32,
+            (Node::Literal(LiteralValue::Float(_)),_) => DataType::F64,
+            (Node::ArrayLiteral(literal),DataType::Array(arr)) => {
+                let elements = match literal {
+                    ArrayLiteral::Elements(elements) => elements.iter().collect::<Vec<&AstNode<Node>>>(),
+                    ArrayLiteral::Repeat(value,_) => vec![value.as_ref()],
                 };
-                match exp.operator.node {
-                    TokenType::AdditionOperator => {
-                        match operand_type {
-                            DataType::Array(_) => {
+                let literals_only = elements.iter().all(|e| matches!(e.node,Node::Literal(LiteralValue::Int(_) | LiteralValue::Float(_)) | Node::ArrayLiteral(_) | Node::Tuple(_)));
+                if !literals_only {
+                    return DataType::Array(arr);
+                }
+                let element_type = Self::inferred_type(elements[0], *arr.data_type.clone());
+                DataType::Array(Array { length: arr.length, data_type: Box::new(element_type) })
+            },
+            (Node::Tuple(tuple),DataType::Tuple(elements)) => {
+                DataType::Tuple(tuple.members.iter().zip(elements).map(|(member,element)| Self::inferred_type(member, element)).collect())
+            },
+            (_,DataType::Str(_)) => DataType::Str(0),
+            (_,found) => found
+        }
+    }
+
+    fn inferred_literal(node:&AstNode<Node>,symbol_table:&Rc<SymbolTable>) -> Option<i64> {
+        match &node.node {
+            Node::Variable(v) => symbol_table.lookup_var(v.clone())?.0.inferred_literal,
+            _ => None
+        }
+    }
+
+    // a variable declared from an integer literal takes the first integer type it is
+    // used as, as long as the literal fits that type
+    fn settle_inferred_literal(&self,node:&AstNode<Node>,data_type:&DataType,symbol_table:&Rc<SymbolTable>) {
+        let literal = match Self::inferred_literal(node, symbol_table) {
+            Some(literal) => literal,
+            None => return
+        };
+        if !Self::is_integer(data_type) {
+            return;
+        }
+        let (start,end) = data_type.int_domain();
+        if (literal as i128) < start || (literal as i128) >= end {
+            return;
+        }
+        if let Node::Variable(v) = &node.node {
+            if let Some((_,scope)) = symbol_table.lookup_var(v.clone()) {
+                symbol_table.settle_var_type_at(scope, v.clone(), data_type.clone());
+            }
+        }
+    }
+
+    fn type_name_taken(name:&str,symbol_table:&Rc<SymbolTable>) -> bool {
+        symbol_table.lookup_struct(name.to_string()).is_some() || symbol_table.lookup_enum(name.to_string()).is_some()
+    }
+
+    fn type_coercion(&self,first_type:&DataType,second_type:&DataType) -> Option<DataType>{
+        if Self::type_castable(first_type, second_type){
+            return Some(second_type.clone());
+        }
+        if Self::type_castable( second_type,first_type){
+            return Some(first_type.clone());
+        }
+        None
+    }
+
+    // a declaration whose type couldn't be found still declares the name, it is left
+    // without a type so its uses don't report anything more
+    fn declare_untyped(v:&Var,symbol_table:&Rc<SymbolTable>) {
+        symbol_table.insert_var(v.name.node.clone());
+        symbol_table.update_var(v.name.node.clone(), None, None, Some(0), Some(v.name.pos.0));
+        symbol_table.var_set_addressable(v.name.node.clone(), symbol_table.scope != Scope::Global);
+    }
+
+    fn check_not_constant(&self, target:&AstNode<Node>,symbol_table:&Rc<SymbolTable>) -> bool {
+        self.check_mutable(target, "assign to", symbol_table)
+    }
+
+    // whether the place can be changed, action says how for the error: assigning to it or
+    // taking a &mut of it. a field or element of a constant is constant as well and
+    // whatever a shared reference points to cannot be changed through it
+    fn check_mutable(&self, target:&AstNode<Node>,action:&str,symbol_table:&Rc<SymbolTable>) -> bool {
+        let inner = match &target.node {
+            Node::FieldAccess(access) => Some(&access.target),
+            Node::Index(exp) => Some(&exp.target),
+            Node::Deref(exp) => Some(&exp.exp),
+            _ => None
+        };
+        if let Some(inner) = inner {
+            return match Self::place_type(inner, symbol_table) {
+                Some(DataType::Reference(_,true)) => true,
+                Some(reference_type @ DataType::Reference(_,false)) => {
+                    self.error_pipe.report_error(
+                        CompilerError::new(
+                            ErrorType::SemanticError,
+                            format!("cannot {} a value behind the shared reference '{}'",action,reference_type.to_string()).as_str(),
+                            target.pos,
+                            target.length,
+                        )
+                    );
+                    false
+                },
+                _ if matches!(target.node,Node::Deref(_)) => true,
+                _ => self.check_mutable(inner, action, symbol_table)
+            };
+        }
+        if let Node::Variable(v) = &target.node {
+            symbol_table.capture_var(v);
+            if let Some((var,_)) = symbol_table.lookup_var(v.clone()) {
+                if var.captured {
+                    self.error_pipe.report_error(
+                        CompilerError::new(
+                            ErrorType::SemanticError,
+                            format!("cannot {} '{}' inside a closure, the closure holds a copy of it",action,v).as_str(),
+                            target.pos,
+                            target.length,
+                        )
+                    );
+                    return false;
+                }
+                if var.constant {
+                    self.error_pipe.report_error(
+                        CompilerError::new(
+                            ErrorType::SemanticError,
+                            format!("cannot {} constant '{}'",action,v).as_str(),
+                            target.pos,
+                            target.length,
+                        )
+                    );
+                    return false;
+                }
+            }
+        }
+        true
+    }
+
+    // the type of a place without reporting anything, the place is checked on its own
+    fn place_type(node:&AstNode<Node>,symbol_table:&Rc<SymbolTable>) -> Option<DataType> {
+        let data_type = match &node.node {
+            Node::Variable(v) => symbol_table.lookup_var(v.clone())?.0.data_type?,
+            Node::FunctionCall(called) => symbol_table.lookup_func(called.function_name.node.clone())?.0.return_type,
+            Node::FieldAccess(access) => match Self::dereferenced(Self::place_type(&access.target, symbol_table)?) {
+                DataType::Struct(name) => symbol_table.lookup_struct(name)?.get_field(&access.field.node)?.0,
+                DataType::Tuple(elements) => elements.get(access.field.node.parse::<usize>().ok()?)?.clone(),
+                _ => return None
+            },
+            Node::Index(exp) => match Self::dereferenced(Self::place_type(&exp.target, symbol_table)?) {
+                DataType::Array(arr) => *arr.data_type,
+                DataType::Slice(element) => *element,
+                DataType::Str(_) => DataType::U8,
+                _ => return None
+            },
+            Node::Deref(exp) => match Self::place_type(&exp.exp, symbol_table)? {
+                DataType::Reference(target,_) => *target,
+                _ => return None
+            },
+            _ => return None
+        };
+        Some(data_type)
+    }
+
+    // fields and elements are reached through one reference without writing *
+    fn dereferenced(data_type:DataType) -> DataType {
+        match data_type {
+            DataType::Reference(target,_) => *target,
+            other => other
+        }
+    }
+
+    // the variable whose memory a place is part of, None when the place is reached
+    // through a reference or a slice since those point into memory of their own
+    fn place_root<'n>(node:&'n AstNode<Node>,symbol_table:&Rc<SymbolTable>) -> Option<&'n AstNode<Node>> {
+        let target = match &node.node {
+            Node::Variable(_) => return Some(node),
+            Node::FieldAccess(access) => &access.target,
+            Node::Index(exp) => &exp.target,
+            _ => return None
+        };
+        match Self::place_type(target, symbol_table)? {
+            DataType::Reference(_,_) | DataType::Slice(_) | DataType::Str(_) => None,
+            _ => Self::place_root(target, symbol_table)
+        }
+    }
+
+    fn check_reference(&self,reference:&Reference,node:&AstNode<Node>,symbol_table:&Rc<SymbolTable>) -> Option<DataType> {
+        let is_place = match &reference.target.node {
+            Node::Index(exp) => !matches!(exp.index.node,Node::Range(_)),
+            Node::Variable(_) | Node::FieldAccess(_) | Node::Deref(_) => true,
+            _ => false
+        };
+        if !is_place {
+            self.error_pipe.report_error(
+                CompilerError::new(
+                    ErrorType::SemanticError,
+                    "cannot take a reference to a temporary value, store it in a variable first",
+                    reference.target.pos,
+                    reference.target.length,
+                )
+            );
+            return None;
+        }
+        let target_type = self.check_expression_type(&reference.target, symbol_table)?;
+        if reference.mutable && !self.check_mutable(&reference.target, "take a mutable reference to", symbol_table) {
+            return None;
+        }
+        // the variable is kept in memory so the reference sees every later assignment
+        if let Some(AstNode { node: Node::Variable(v), pos, length }) = Self::place_root(&reference.target, symbol_table) {
+            if let Some((var,scope)) = symbol_table.lookup_var(v.clone()) {
+                if scope == Scope::Global || !var.addressable {
+                    self.error_pipe.report_error(
+                        CompilerError::new(
+                            ErrorType::SemanticError,
+                            format!("cannot take a reference to '{}', only variables declared in a function with let and parameters live in memory",v).as_str(),
+                            *pos,
+                            *length,
+                        )
+                    );
+                    return None;
+                }
+                symbol_table.var_set_in_memory_at(scope, v.clone(), true);
+                self.record_borrow(v, reference.mutable, node, *pos, symbol_table);
+            }
+        }
+        Some(DataType::Reference(Box::new(target_type),reference.mutable))
+    }
+
+    fn record_borrow(&self,variable:&str,mutable:bool,node:&AstNode<Node>,root_pos:(u32,u32),symbol_table:&Rc<SymbolTable>) {
+        let mut logs = self.borrow_logs.borrow_mut();
+        let (log,owner) = match (logs.last_mut(),symbol_table.var_owner(variable)) {
+            (Some(log),Some(owner)) => (log,owner),
+            _ => return
+        };
+        // an expression can be checked more than once
+        if log.borrows.iter().any(|b| b.pos == node.pos) {
+            return;
+        }
+        log.borrows.push(Borrow {
+            variable: variable.to_string(),
+            owner,
+            mutable,
+            pos: node.pos,
+            length: node.length,
+            root_pos,
+            holder: None,
+            held_from: node.pos
+        });
+    }
+
+    fn record_use(&self,variable:&str,pos:(u32,u32),length:u32,write:bool,symbol_table:&Rc<SymbolTable>) {
+        let mut logs = self.borrow_logs.borrow_mut();
+        let (log,owner) = match (logs.last_mut(),symbol_table.var_owner(variable)) {
+            (Some(log),Some(owner)) if owner.scope != Scope::Global => (log,owner),
+            _ => return
+        };
+        if let Some(existing) = log.uses.iter_mut().find(|u| u.pos == pos) {
+            existing.write |= write;
+            return;
+        }
+        log.uses.push(VarUse {
+            variable: variable.to_string(),
+            owner,
+            scope: Rc::clone(symbol_table),
+            pos,
+            length,
+            write
+        });
+    }
+
+    // `let r = &x`, `r = &x` and `h.field = Holder { r: &x }` keep the borrow alive for as
+    // long as r or h is used, a borrow stored in a second variable is held by both
+    fn hold_borrow(&self,assignment:&BinExp,symbol_table:&Rc<SymbolTable>) {
+        let holder = match &assignment.left.node {
+            Node::DeclareVar(v) => &v.name.node,
+            _ => match Self::place_root(&assignment.left, symbol_table) {
+                Some(AstNode { node: Node::Variable(v), .. }) => v,
+                _ => return
+            }
+        };
+        let owner = match symbol_table.var_owner(holder) {
+            Some(owner) => owner,
+            None => return
+        };
+        let carried = self.carried_borrows(&assignment.right, symbol_table);
+        let mut logs = self.borrow_logs.borrow_mut();
+        let log = match logs.last_mut() {
+            Some(log) => log,
+            None => return
+        };
+        for borrow in carried {
+            match log.borrows.iter_mut().find(|b| b.pos == borrow.pos && b.holder.is_none()) {
+                Some(taken) => taken.holder = Some((holder.clone(),owner.clone())),
+                None => log.borrows.push(Borrow { holder: Some((holder.clone(),owner.clone())), held_from: assignment.right.pos, ..borrow })
+            }
+        }
+    }
+
+    // the borrows a value carries, the reference it takes, the borrow held by a variable
+    // it reads and those of the fields, elements, arms and captures it is built from
+    fn carried_borrows(&self,value:&AstNode<Node>,symbol_table:&Rc<SymbolTable>) -> Vec<Borrow> {
+        let logs = self.borrow_logs.borrow();
+        let mut borrows = vec![];
+        if let Some(log) = logs.last() {
+            Self::collect_borrows(log, value, symbol_table, &mut borrows);
+        }
+        borrows
+    }
+
+    fn collect_borrows(log:&BorrowLog,value:&AstNode<Node>,symbol_table:&Rc<SymbolTable>,borrows:&mut Vec<Borrow>) {
+        let mut collect = |part:&AstNode<Node>| Self::collect_borrows(log, part, symbol_table, borrows);
+        match &value.node {
+            Node::Reference(_) => {
+                if let Some(borrow) = log.borrows.iter().find(|b| b.pos == value.pos) {
+                    borrows.push(borrow.clone());
+                }
+            },
+            Node::Variable(holder) => {
+                if let Some(borrow) = Self::held_at(log, holder, value.pos, symbol_table) {
+                    borrows.push(borrow.clone());
+                }
+            },
+            Node::StructLiteral(literal) => literal.fields.iter().for_each(|(_,field)| collect(field)),
+            Node::ArrayLiteral(ArrayLiteral::Elements(elements)) => elements.iter().for_each(collect),
+            Node::ArrayLiteral(ArrayLiteral::Repeat(element,_)) => collect(element),
+            Node::Tuple(tuple) => tuple.members.iter().for_each(collect),
+            Node::EnumValue(variant) if symbol_table.lookup_module(variant.enum_name.node.clone()).is_none() => variant.arguments.iter().for_each(collect),
+            Node::Match(m) => m.arms.iter().filter(|arm| !matches!(arm.body.node,Node::Body(_))).for_each(|arm| collect(&arm.body)),
+            // the captured values are copied into the closure, references among them included
+            Node::Closure(_) => {
+                if let Some((closure,_)) = symbol_table.lookup_func(symbol_table.closure_name(value.pos)) {
+                    for (_,capture) in &closure.captures {
+                        if let Some(borrow) = Self::held_at(log, capture, value.pos, symbol_table) {
+                            borrows.push(borrow.clone());
+                        }
+                    }
+                }
+            },
+            _ => ()
+        }
+    }
+
+    // the borrow a variable holds at pos, the last one stored in it before pos unless
+    // the variable is assigned again in between
+    fn held_at<'l>(log:&'l BorrowLog,holder:&str,pos:(u32,u32),symbol_table:&Rc<SymbolTable>) -> Option<&'l Borrow> {
+        let owner = symbol_table.var_owner(holder)?;
+        let borrow = log.borrows.iter()
+            .filter(|b| b.held_from < pos && b.holder.as_ref().is_some_and(|(name,o)| name == holder && Rc::ptr_eq(o,&owner)))
+            .max_by_key(|b| b.held_from)?;
+        let reassigned = log.uses.iter().any(|u| u.write && u.variable == holder && Rc::ptr_eq(&u.owner,&owner) && u.pos > borrow.held_from && u.pos < pos);
+        (!reassigned).then_some(borrow)
+    }
+
+    // the borrows a variable holding a reference has taken, with the reads of the variable
+    // the borrow lives until, it ends when the variable is assigned again
+    fn held_borrows<'l>(log:&'l BorrowLog,holder:&str,holder_owner:&Rc<SymbolTable>) -> Vec<(&'l Borrow,Vec<&'l VarUse>)> {
+        let same_holder = |u:&&VarUse| u.variable == holder && Rc::ptr_eq(&u.owner,holder_owner);
+        log.borrows.iter()
+            .filter(|b| b.holder.as_ref().is_some_and(|(name,owner)| name == holder && Rc::ptr_eq(owner,holder_owner)))
+            .map(|b| {
+                let reassigned = log.uses.iter().filter(same_holder).filter(|u| u.write && u.pos > b.held_from).map(|u| u.pos).min();
+                let reads = log.uses.iter().filter(same_holder)
+                    .filter(|u| !u.write && u.pos > b.held_from && reassigned.is_none_or(|r| u.pos < r))
+                    .collect::<Vec<&VarUse>>();
+                (b,reads)
+            })
+            .collect()
+    }
+
+    // a borrow lives from where it is taken to the last read of the variable holding it,
+    // the order of the source stands in for the order the function runs in. while a &mut
+    // lives its variable is not used in any other way and while a & lives the variable
+    // is neither assigned nor borrowed as &mut
+    fn check_borrows(&self,log:&BorrowLog) {
+        let mut reported:Vec<(u32,u32)> = vec![];
+        for borrow in &log.borrows {
+            let (holder,holder_owner) = match &borrow.holder {
+                Some(holder) => holder,
+                None => continue
+            };
+            let held = Self::held_borrows(log, holder, holder_owner);
+            let reads = match held.iter().find(|(b,_)| b.pos == borrow.pos) {
+                Some((_,reads)) if !reads.is_empty() => reads,
+                _ => continue
+            };
+            let last_read = reads.iter().max_by_key(|u| u.pos).unwrap();
+            let note = format!("the borrow is used later through '{}' at line {}",holder,last_read.pos.0);
+            // stored in a variable of an enclosing block the reference is read after the variable is gone
+            // a copy of the reference in another variable is the same borrow
+            if !Rc::ptr_eq(holder_owner,&borrow.owner) && holder_owner.encloses(&borrow.owner) && !reported.contains(&borrow.pos) {
+                if let Some(outside) = reads.iter().find(|u| !borrow.owner.encloses(&u.scope)) {
+                    self.error_pipe.report_error(
+                        CompilerError::new(
+                            ErrorType::SemanticError,
+                            format!("'{}' does not live long enough, '{}' still refers to it after the block '{}' is declared in ends",borrow.variable,holder,borrow.variable).as_str(),
+                            borrow.pos,
+                            borrow.length,
+                        ).with_label(outside.pos, outside.length, format!("'{}' is used here",holder).as_str())
+                    );
+                    reported.push(borrow.pos);
+                    continue;
+                }
+            }
+            let live = |pos:(u32,u32)| pos > borrow.pos && pos <= last_read.pos;
+            let same_variable = |name:&String,owner:&Rc<SymbolTable>| *name == borrow.variable && Rc::ptr_eq(owner,&borrow.owner);
+            for other in log.borrows.iter().filter(|b| same_variable(&b.variable,&b.owner) && live(b.pos)) {
+                if !(borrow.mutable || other.mutable) || reported.contains(&other.pos) {
+                    continue;
+                }
+                let message = match (other.mutable,borrow.mutable) {
+                    (true,true) => format!("cannot borrow '{}' as mutable more than once at a time",borrow.variable),
+                    (true,false) => format!("cannot borrow '{}' as mutable because it is also borrowed as shared",borrow.variable),
+                    _ => format!("cannot borrow '{}' as shared because it is also borrowed as mutable",borrow.variable)
+                };
+                self.error_pipe.report_error(
+                    CompilerError::new(ErrorType::SemanticError, message.as_str(), other.pos, other.length)
+                        .with_label(borrow.pos, borrow.length, format!("'{}' is borrowed here",borrow.variable).as_str())
+                        .with_note(note.as_str())
+                );
+                reported.push(other.pos);
+            }
+            for use_ in log.uses.iter().filter(|u| same_variable(&u.variable,&u.owner) && live(u.pos)) {
+                // naming the variable in another borrow is reported as that borrow
+                if !(borrow.mutable || use_.write) || reported.contains(&use_.pos) || log.borrows.iter().any(|b| b.root_pos == use_.pos) {
+                    continue;
+                }
+                let message = match use_.write {
+                    true => format!("cannot assign to '{}' while it is borrowed",borrow.variable),
+                    false => format!("cannot use '{}' while it is borrowed as mutable",borrow.variable)
+                };
+                self.error_pipe.report_error(
+                    CompilerError::new(ErrorType::SemanticError, message.as_str(), use_.pos, use_.length)
+                        .with_label(borrow.pos, borrow.length, format!("'{}' is borrowed here",borrow.variable).as_str())
+                        .with_note(note.as_str())
+                );
+                reported.push(use_.pos);
+            }
+        }
+    }
+
+    // a reference returned from a function cannot point at its locals or parameters,
+    // they are gone once the function returns
+    fn check_returned_reference(&self,value:&AstNode<Node>,symbol_table:&Rc<SymbolTable>) {
+        match &value.node {
+            Node::Reference(reference) => {
+                if let Some(AstNode { node: Node::Variable(v), .. }) = Self::place_root(&reference.target, symbol_table) {
+                    if symbol_table.lookup_var(v.clone()).is_some_and(|(_,scope)| scope != Scope::Global) {
+                        self.error_pipe.report_error(
+                            CompilerError::new(
+                                ErrorType::SemanticError,
+                                format!("cannot return a reference to local variable '{}'",v).as_str(),
+                                value.pos,
+                                value.length,
+                            ).with_note(format!("'{}' is freed when the function returns",v).as_str())
+                        );
+                    }
+                }
+            },
+            Node::Variable(holder) => {
+                let holder_owner = match symbol_table.var_owner(holder) {
+                    Some(owner) => owner,
+                    None => return
+                };
+                let logs = self.borrow_logs.borrow();
+                let log = match logs.last() {
+                    Some(log) => log,
+                    None => return
+                };
+                // the borrow the variable holds where it is returned
+                let borrow = Self::held_borrows(log, holder, &holder_owner).into_iter()
+                    .filter(|(b,reads)| reads.iter().any(|u| u.pos == value.pos) && b.held_from < value.pos)
+                    .map(|(b,_)| b)
+                    .max_by_key(|b| b.held_from);
+                if let Some(borrow) = borrow {
+                    self.error_pipe.report_error(
+                        CompilerError::new(
+                            ErrorType::SemanticError,
+                            format!("cannot return '{}', it refers to local variable '{}'",holder,borrow.variable).as_str(),
+                            value.pos,
+                            value.length,
+                        ).with_label(borrow.pos, borrow.length, format!("'{}' is borrowed here",borrow.variable).as_str())
+                    );
+                }
+            },
+            _ => {
+                let mut borrows = self.carried_borrows(value, symbol_table);
+                // every arm of a match can borrow the same variable
+                borrows.dedup_by(|b,first| b.variable == first.variable && Rc::ptr_eq(&b.owner,&first.owner));
+                for borrow in borrows {
+                    self.error_pipe.report_error(
+                        CompilerError::new(
+                            ErrorType::SemanticError,
+                            format!("cannot return a value holding a reference to local variable '{}'",borrow.variable).as_str(),
+                            value.pos,
+                            value.length,
+                        ).with_label(borrow.pos, borrow.length, format!("'{}' is borrowed here",borrow.variable).as_str())
+                    );
+                }
+            }
+        }
+    }
+
+    // the references passed to one call live together until the call returns
+    fn check_argument_borrows(&self,arguments:&[AstNode<Node>],symbol_table:&Rc<SymbolTable>) {
+        let mut borrows:Vec<(&AstNode<Node>,bool,String,Rc<SymbolTable>)> = vec![];
+        for argument in arguments {
+            let reference = match &argument.node {
+                Node::Reference(reference) => reference,
+                _ => continue
+            };
+            let variable = match Self::place_root(&reference.target, symbol_table) {
+                Some(AstNode { node: Node::Variable(v), .. }) => v,
+                _ => continue
+            };
+            let owner = match symbol_table.var_owner(variable) {
+                Some(owner) => owner,
+                None => continue
+            };
+            let conflict = borrows.iter().find(|(_,mutable,name,o)| name == variable && Rc::ptr_eq(o,&owner) && (*mutable || reference.mutable));
+            if let Some((first,first_mutable,_,_)) = conflict {
+                let message = match (reference.mutable,first_mutable) {
+                    (true,true) => format!("cannot borrow '{}' as mutable more than once at a time",variable),
+                    (true,false) => format!("cannot borrow '{}' as mutable because it is also borrowed as shared",variable),
+                    _ => format!("cannot borrow '{}' as shared because it is also borrowed as mutable",variable)
+                };
+                self.error_pipe.report_error(
+                    CompilerError::new(ErrorType::SemanticError, message.as_str(), argument.pos, argument.length)
+                        .with_label(first.pos, first.length, format!("'{}' is borrowed here",variable).as_str())
+                );
+                continue;
+            }
+            borrows.push((argument,reference.mutable,variable.clone(),owner));
+        }
+    }
+
+    // alloc(value) and free(reference) are built in unless the program declares its own
+    fn check_heap_call(&self,called:&FuncCall,node:&AstNode<Node>,symbol_table:&Rc<SymbolTable>) -> Option<DataType> {
+        let name = &called.function_name.node;
+        if called.arguments.len() != 1 {
+            self.error_pipe.report_error(
+                CompilerError::new(
+                    ErrorType::SemanticError,
+                    format!("function '{}' expects 1 argument(s) found {}",name,called.arguments.len()).as_str(),
+                    node.pos,
+                    node.length,
+                )
+            );
+            return None;
+        }
+        let argument = &called.arguments[0];
+        let argument_type = Self::inferred_type(argument, self.check_expression_type(argument, symbol_table)?);
+        match (name.as_str(),argument_type) {
+            ("alloc",DataType::Void) => {
+                self.error_pipe.report_error(
+                    CompilerError::new(
+                        ErrorType::SemanticError,
+                        "alloc expects a value to store on the heap found 'void'",
+                        argument.pos,
+                        argument.length,
+                    )
+                );
+                None
+            },
+            ("alloc",value_type) => Some(DataType::Reference(Box::new(value_type),true)),
+            (_,DataType::Reference(_,true)) => Some(DataType::Void),
+            (_,other) => {
+                self.error_pipe.report_error(
+                    CompilerError::new(
+                        ErrorType::SemanticError,
+                        format!("free expects a '&mut' returned by alloc found '{}'",other.to_string()).as_str(),
+                        argument.pos,
+                        argument.length,
+                    )
+                );
+                None
+            }
+        }
+    }
+
+    fn literal_type(literal:&LiteralValue) -> DataType {
+        match literal {
+            LiteralValue::Int(i) => Self::get_int_type(*i).unwrap_or(DataType::I64),
+            LiteralValue::Float(f) => Self::get_float_type(*f).unwrap_or(DataType::F64),
+            LiteralValue::Str(s) => DataType::Str(s.len() as u32),
+            LiteralValue::Bool(_) => DataType::Boolean,
+        }
+    }
+
+    // checks a pattern against the type being matched, bindings of a variant
+    // pattern are declared in the scope of the arm
+    fn check_pattern(&self,pattern:&AstNode<Pattern>,scrutinee_type:&DataType,arm_scope:&Rc<SymbolTable>) -> bool {
+        let report = |message:String| {
+            self.error_pipe.report_error(
+                CompilerError::new(
+                    ErrorType::SemanticError,
+                    message.as_str(),
+                    pattern.pos,
+                    pattern.length,
+                )
+            );
+            false
+        };
+        match &pattern.node {
+            Pattern::Wildcard => true,
+            Pattern::Literal(literal) => {
+                let literal_type = Self::literal_type(literal);
+                let compatible = match (literal,scrutinee_type) {
+                    (LiteralValue::Int(i),_) if Self::is_integer(scrutinee_type) => {
+                        let (start,end) = scrutinee_type.int_domain();
+                        if (*i as i128) < start || *i as i128 >= end {
+                            return report(format!("value '{}' is out of range for '{}'",i,scrutinee_type.to_string()));
+                        }
+                        true
+                    },
+                    (LiteralValue::Float(_),DataType::F32 | DataType::F64) => true,
+                    (LiteralValue::Str(_),DataType::Str(_)) => true,
+                    (LiteralValue::Bool(_),DataType::Boolean) => true,
+                    _ => false
+                };
+                if !compatible {
+                    return report(format!("expected '{}' found '{}'",scrutinee_type.to_string(),literal_type.to_string()));
+                }
+                true
+            },
+            Pattern::Range(start,end,inclusive) => {
+                let (start,end) = match (start,end) {
+                    (LiteralValue::Int(start),LiteralValue::Int(end)) if Self::is_integer(scrutinee_type) => (*start as i128,*end as i128),
+                    _ => return report(format!("range patterns are only allowed on integers found '{}'",scrutinee_type.to_string()))
+                };
+                // start..=end covers the same values as start..end+1
+                let end = end + *inclusive as i128;
+                if start >= end {
+                    return report(String::from("empty range pattern"));
+                }
+                let (low,high) = scrutinee_type.int_domain();
+                if start < low || end > high {
+                    return report(format!("range pattern is out of range for '{}'",scrutinee_type.to_string()));
+                }
+                true
+            },
+            Pattern::Variant(v) => {
+                if !matches!(scrutinee_type,DataType::Enum(_) | DataType::Option(_) | DataType::Result(_,_)) {
+                    return report(format!("expected '{}' found enum '{}'",scrutinee_type.to_string(),v.enum_name.node));
+                }
+                let enum_attribute = match arm_scope.enum_of(scrutinee_type) {
+                    Some(e) => e,
+                    None => return false
+                };
+                let enum_name = &enum_attribute.enum_name;
+                if &v.enum_name.node != enum_name && Self::imported_enum_name(&v.enum_name.node, scrutinee_type, arm_scope).as_ref() != Some(enum_name) {
+                    return report(format!("expected '{}' found enum '{}'",scrutinee_type.to_string(),v.enum_name.node));
+                }
+                let (_,payload) = match enum_attribute.get_variant(&v.variant.node) {
+                    Some(variant) => variant,
+                    None => {
+                        self.error_pipe.report_error(
+                            CompilerError::new(
+                                ErrorType::SemanticError,
+                                format!("enum '{}' has no variant '{}'",enum_name,v.variant.node).as_str(),
+                                v.variant.pos,
+                                v.variant.length,
+                            )
+                        );
+                        return false;
+                    }
+                };
+                // the bindings are declared even when their count is wrong so the arm
+                // does not report them as undeclared as well
+                for (binding,binding_type) in v.bindings.iter().zip(payload.iter()) {
+                    if binding.node == "_" {
+                        continue;
+                    }
+                    arm_scope.insert_var(binding.node.clone());
+                    arm_scope.update_var(
+                        binding.node.clone(),
+                        Some(binding_type.clone()),
+                        Some(arm_scope.size_of(binding_type)),
+                        Some(0),
+                        Some(binding.pos.0)
+                    );
+                }
+                if payload.len() != v.bindings.len() {
+                    return report(format!("variant '{}::{}' has {} value(s) found {} binding(s)",enum_name,v.variant.node,payload.len(),v.bindings.len()));
+                }
+                true
+            },
+        }
+    }
+
+    // the values of a variant have to match its payload, Option and Result are built in
+    // unless the program declares enums of those names
+    fn check_enum_value(&self,value:&EnumValue,node:&AstNode<Node>,expected:Option<&DataType>,symbol_table:&Rc<SymbolTable>) -> Option<DataType> {
+        if value.enum_name.node.contains("::") {
+            self.import_path(&value.enum_name, symbol_table)?;
+        }
+        let imported = expected.and_then(|expected| Self::imported_enum_name(&value.enum_name.node, expected, symbol_table));
+        let (enum_attribute,value_type) = match symbol_table.lookup_enum(imported.unwrap_or(value.enum_name.node.clone())) {
+            Some(e) => {
+                let value_type = DataType::Enum(e.enum_name.clone());
+                // the ir finds the enum of a variant named without its module by the type
+                if e.enum_name != value.enum_name.node {
+                    symbol_table.insert_variant_type(node.pos, value_type.clone());
+                }
+                (e,value_type)
+            },
+            None if matches!(value.enum_name.node.as_str(),"Option" | "Result") => {
+                let value_type = self.builtin_value_type(value, node, expected, symbol_table)?;
+                symbol_table.insert_variant_type(node.pos, value_type.clone());
+                (symbol_table.enum_of(&value_type)?,value_type)
+            },
+            None => {
+                self.error_pipe.report_error(
+                    CompilerError::new(
+                        ErrorType::SemanticError,
+                        format!("unknown enum '{}'",value.enum_name.node).as_str(),
+                        value.enum_name.pos,
+                        value.enum_name.length,
+                    )
+                );
+                return None;
+            }
+        };
+        let (_,payload) = match enum_attribute.get_variant(&value.variant.node) {
+            Some(v) => v,
+            None => {
+                self.error_pipe.report_error(
+                    CompilerError::new(
+                        ErrorType::SemanticError,
+                        format!("enum '{}' has no variant '{}'",enum_attribute.enum_name,value.variant.node).as_str(),
+                        value.variant.pos,
+                        value.variant.length,
+                    )
+                );
+                return None;
+            }
+        };
+        if payload.len() != value.arguments.len() {
+            self.error_pipe.report_error(
+                CompilerError::new(
+                    ErrorType::SemanticError,
+                    format!("variant '{}::{}' expects {} value(s) found {}",enum_attribute.enum_name,value.variant.node,payload.len(),value.arguments.len()).as_str(),
+                    node.pos,
+                    node.length,
+                )
+            );
+            return None;
+        }
+        let mut valid = true;
+        for (expected_type,argument) in payload.iter().zip(value.arguments.iter()) {
+            match self.check_value_type(argument, expected_type, symbol_table) {
+                Some(argument_type) => {
+                    if !Self::type_matches(expected_type, &argument_type) {
+                        self.error_pipe.report_error(
+                            CompilerError::new(
+                                ErrorType::SemanticError,
+                                format!("expected '{}' found '{}'",expected_type.to_string(),argument_type.to_string()).as_str(),
+                                argument.pos,
+                                argument.length,
+                            )
+                        );
+                        valid = false;
+                    }
+                },
+                None => valid = false
+            }
+        }
+        if !valid {
+            return None;
+        }
+        Some(value_type)
+    }
+
+    // the type of a variant of Option or Result is the type expected where it is used,
+    // without one only Option::Some(value) can tell it from its value
+    fn builtin_value_type(&self,value:&EnumValue,node:&AstNode<Node>,expected:Option<&DataType>,symbol_table:&Rc<SymbolTable>) -> Option<DataType> {
+        let placeholder = match value.enum_name.node.as_str() {
+            "Option" => DataType::Option(Box::new(DataType::Void)),
+            _ => DataType::Result(Box::new(DataType::Void),Box::new(DataType::Void))
+        };
+        if symbol_table.enum_of(&placeholder)?.get_variant(&value.variant.node).is_none() {
+            self.error_pipe.report_error(
+                CompilerError::new(
+                    ErrorType::SemanticError,
+                    format!("enum '{}' has no variant '{}'",value.enum_name.node,value.variant.node).as_str(),
+                    value.variant.pos,
+                    value.variant.length,
+                )
+            );
+            return None;
+        }
+        match (expected,value.variant.node.as_str(),value.arguments.as_slice()) {
+            (Some(expected @ DataType::Option(_)),_,_) if matches!(placeholder,DataType::Option(_)) => Some(expected.clone()),
+            (Some(expected @ DataType::Result(_,_)),_,_) if matches!(placeholder,DataType::Result(_,_)) => Some(expected.clone()),
+            (_,"Some",[argument]) => {
+                let found = Self::inferred_type(argument, self.check_expression_type(argument, symbol_table)?);
+                if found == DataType::Void {
+                    self.error_pipe.report_error(
+                        CompilerError::new(
+                            ErrorType::SemanticError,
+                            "'Option' cannot hold 'void'",
+                            argument.pos,
+                            argument.length,
+                        )
+                    );
+                    return None;
+                }
+                Some(DataType::Option(Box::new(found)))
+            },
+            _ => {
+                self.error_pipe.report_error(
+                    CompilerError::new(
+                        ErrorType::SemanticError,
+                        format!("cannot infer the type of '{}::{}', it has to be used where {} '{}' is expected",value.enum_name.node,value.variant.node,if matches!(placeholder,DataType::Option(_)) { "an" } else { "a" },placeholder.to_string().replace("void","_")).as_str(),
+                        node.pos,
+                        node.length,
+                    )
+                );
+                None
+            }
+        }
+    }
+
+    // as_value is set when the match is used as an expression, every arm then
+    // has to be an expression of the same type
+    fn check_match(&self,m:&MatchExp,node:&AstNode<Node>,symbol_table:&Rc<SymbolTable>,as_value:bool) -> Option<DataType> {
+        let scrutinee_type = self.check_expression_type(&m.scrutinee, symbol_table)?;
+        let enum_attribute = symbol_table.enum_of(&scrutinee_type);
+        let mut coverage = MatchCoverage::new();
+        let mut result_type:Option<DataType> = None;
+        let mut valid = true;
+        // a broken pattern should not cause a second error for a missing arm
+        let mut report_missing = true;
+        for arm in &m.arms {
+            let arm_scope = symbol_table.insert_block_scope();
+            if self.check_pattern(&arm.pattern, &scrutinee_type, &arm_scope) {
+                let exhausted = coverage.missing(&scrutinee_type, &enum_attribute).is_none();
+                if exhausted || coverage.covers(&arm.pattern.node) {
+                    self.error_pipe.report_error(
+                        CompilerError::new(
+                            ErrorType::SemanticError,
+                            "unreachable match arm",
+                            arm.pattern.pos,
+                            arm.pattern.length,
+                        )
+                    );
+                }
+                coverage.add(&arm.pattern.node);
+            }
+            else {
+                valid = false;
+                report_missing = false;
+            }
+            match &arm.body.node {
+                Node::Body(bd) => {
+                    if as_value {
+                        self.error_pipe.report_error(
+                            CompilerError::new(
+                                ErrorType::SemanticError,
+                                "a match used as a value cannot have block arms",
+                                arm.body.pos,
+                                arm.body.length,
+                            )
+                        );
+                        valid = false;
+                    }
+                    self.analyze_body(bd, arm_scope);
+                },
+                _ if as_value => {
+                    let arm_type = match self.check_expression_type(&arm.body, &arm_scope) {
+                        Some(t) => t,
+                        None => {
+                            valid = false;
+                            continue;
+                        }
+                    };
+                    match &result_type {
+                        None => result_type = Some(arm_type),
+                        Some(expected) => {
+                            if !Self::type_matches(expected, &arm_type) {
+                                self.error_pipe.report_error(
+                                    CompilerError::new(
+                                        ErrorType::SemanticError,
+                                        format!("match arms have incompatible types, expected '{}' found '{}'",expected.to_string(),arm_type.to_string()).as_str(),
+                                        arm.body.pos,
+                                        arm.body.length,
+                                    )
+                                );
+                                valid = false;
+                            }
+                        }
+                    }
+                },
+                _ => {
+                    self.analyze_node(&arm.body, arm_scope);
+                }
+            }
+        }
+        if let (true,Some(missing)) = (report_missing,coverage.missing(&scrutinee_type, &enum_attribute)) {
+            self.error_pipe.report_error(
+                CompilerError::new(
+                    ErrorType::SemanticError,
+                    format!("non-exhaustive match, {}",missing).as_str(),
+                    node.pos,
+                    node.length,
+                )
+            );
+            valid = false;
+        }
+        if !valid {
+            return None;
+        }
+        if as_value {
+            return result_type;
+        }
+        Some(DataType::Void)
+    }
+
+    // an array literal takes its element type from where it is stored, so [1, 2]
+    // can initialize an u64[2] even though its literals alone would be i8
+    fn check_value_type(&self,node:&AstNode<Node>,expected:&DataType,symbol_table:&Rc<SymbolTable>) -> Option<DataType> {
+        if let Node::Loop(l) = &node.node {
+            return self.check_loop(l, node, symbol_table, true, Some(expected));
+        }
+        self.settle_inferred_literal(node, expected, symbol_table);
+        // Option::None only gets its type from where it is used
+        if let (Node::EnumValue(value),DataType::Option(_) | DataType::Result(_,_)) = (&node.node,expected) {
+            if symbol_table.lookup_enum(value.enum_name.node.clone()).is_none() && matches!(value.enum_name.node.as_str(),"Option" | "Result") {
+                return self.check_enum_value(value, node, Some(expected), symbol_table);
+            }
+        }
+        if let (Node::EnumValue(value),DataType::Enum(_)) = (&node.node,expected) {
+            if Self::imported_enum_name(&value.enum_name.node, expected, symbol_table).is_some() {
+                return self.check_enum_value(value, node, Some(expected), symbol_table);
+            }
+        }
+        if let (Node::Range(range),DataType::Range(element)) = (&node.node,expected) {
+            self.check_range(range, Some(element), symbol_table)?;
+            return Some(expected.clone());
+        }
+        // members are checked one by one so literals take the expected element types
+        if let (Node::Tuple(tuple),DataType::Tuple(element_types)) = (&node.node,expected) {
+            if tuple.members.len() == element_types.len() {
+                let mut valid = true;
+                for (member,element_type) in tuple.members.iter().zip(element_types) {
+                    match self.check_value_type(member, element_type, symbol_table) {
+                        Some(found) if !Self::type_matches(element_type, &found) => {
+                            self.error_pipe.report_error(
+                                CompilerError::new(
+                                    ErrorType::SemanticError,
+                                    format!("expected '{}' found '{}'",element_type.to_string(),found.to_string()).as_str(),
+                                    member.pos,
+                                    member.length,
+                                )
+                            );
+                            valid = false;
+                        },
+                        Some(_) => (),
+                        None => valid = false
+                    }
+                }
+                if !valid {
+                    return None;
+                }
+                return Some(expected.clone());
+            }
+        }
+        // the same goes for an integer literal that fits the expected integer type
+        if let (Node::Literal(LiteralValue::Int(i)),true) = (&node.node,Self::is_integer(expected)) {
+            let (start,end) = expected.int_domain();
+            if (*i as i128) >= start && (*i as i128) < end {
+                return Some(expected.clone());
+            }
+        }
+        let element_type = match expected {
+            DataType::Array(arr) => arr.data_type.as_ref(),
+            DataType::Slice(element) => element.as_ref(),
+            _ => return self.check_expression_type(node, symbol_table)
+        };
+        let (elements,length) = match &node.node {
+            Node::ArrayLiteral(ArrayLiteral::Elements(elements)) if !elements.is_empty() => (elements.iter().collect::<Vec<&AstNode<Node>>>(),elements.len() as u32),
+            Node::ArrayLiteral(ArrayLiteral::Repeat(value,count)) => (vec![value.as_ref()],count.node),
+            _ => return self.check_expression_type(node, symbol_table)
+        };
+        let mut valid = true;
+        for element in elements {
+            match self.check_value_type(element, element_type, symbol_table) {
+                Some(found) => {
+                    if !Self::type_matches(element_type, &found) {
+                        self.error_pipe.report_error(
+                            CompilerError::new(
+                                ErrorType::SemanticError,
+                                format!("expected '{}' found '{}'",element_type.to_string(),found.to_string()).as_str(),
+                                element.pos,
+                                element.length,
+                            )
+                        );
+                        valid = false;
+                    }
+                },
+                None => valid = false
+            }
+        }
+        if !valid {
+            return None;
+        }
+        Some(DataType::Array(Array { length, data_type: Box::new(element_type.clone()) }))
+    }
+
+    fn check_arguments(&self,called:&FuncCall,parameters:&[(DataType,String)],symbol_table:&Rc<SymbolTable>) -> bool {
+        if called.arguments.len() != parameters.len() {
+            self.error_pipe.report_error(
+                CompilerError::new(
+                    ErrorType::SemanticError,
+                    format!("function '{}' expects {} argument(s) found {}",called.function_name.node,parameters.len(),called.arguments.len()).as_str(),
+                    called.function_name.pos,
+                    called.function_name.length,
+                )
+            );
+            return false;
+        }
+        let mut valid = true;
+        for (argument,(parameter_type,_)) in called.arguments.iter().zip(parameters.iter()) {
+            match self.check_value_type(argument, parameter_type, symbol_table) {
+                Some(argument_type) => {
+                    if !Self::type_matches(parameter_type, &argument_type) {
+                        self.error_pipe.report_error(
+                            CompilerError::new(
+                                ErrorType::SemanticError,
+                                format!("expected '{}' found '{}'",parameter_type.to_string(),argument_type.to_string()).as_str(),
+                                argument.pos,
+                                argument.length,
+                            )
+                        );
+                        valid = false;
+                    }
+                },
+                None => valid = false
+            }
+        }
+        if valid {
+            self.check_argument_borrows(&called.arguments, symbol_table);
+        }
+        valid
+    }
+
+    // len(x) is built in unless the program declares its own len
+    fn check_len_call(&self,called:&FuncCall,node:&AstNode<Node>,symbol_table:&Rc<SymbolTable>) -> Option<DataType> {
+        if called.arguments.len() != 1 {
+            self.error_pipe.report_error(
+                CompilerError::new(
+                    ErrorType::SemanticError,
+                    format!("function 'len' expects 1 argument(s) found {}",called.arguments.len()).as_str(),
+                    node.pos,
+                    node.length,
+                )
+            );
+            return None;
+        }
+        let argument = &called.arguments[0];
+        match self.check_expression_type(argument, symbol_table)? {
+            DataType::Array(_) | DataType::Slice(_) | DataType::Str(_) => Some(DataType::I32),
+            other => {
+                self.error_pipe.report_error(
+                    CompilerError::new(
+                        ErrorType::SemanticError,
+                        format!("len expects an array, slice or str found '{}'",other.to_string()).as_str(),
+                        argument.pos,
+                        argument.length,
+                    )
+                );
+                None
+            }
+        }
+    }
+
+    fn check_expression_type(&self, node:&AstNode<Node>,symbol_table:&Rc<SymbolTable>) -> Option<DataType>{
+        let symbol_table = Rc::clone(symbol_table);
+        match &node.node {
+            Node::Variable(v) => {
+                match symbol_table.lookup_var(v.clone()){
+                    Some((var,scope)) => {
+                        let line = node.pos.0;
+                        if let Some(line_declare) = var.line_declare {
+                            if line < line_declare {
+                                self.error_pipe.report_error(
+                                    CompilerError::new(
+                                        ErrorType::SemanticError,
+                                        format!("use of unclared variable '{}'",v).as_str(),
+                                        node.pos,
+                                        v.len() as u32,
+                                    )
+                                );
+                                symbol_table.var_push_line_ref_at(scope, v.clone(), node.pos.0);
+                                return None;
+                            }
+                            // used where no particular integer type is expected
+                            if var.inferred_literal.is_some() {
+                                symbol_table.settle_var_type_at(scope.clone(), v.clone(), var.data_type.clone()?);
+                            }
+                            symbol_table.var_push_line_ref_at(scope, v.clone(), node.pos.0);
+                            symbol_table.capture_var(v);
+                            self.record_use(v, node.pos, node.length, false, &symbol_table);
+                            return var.data_type;
+                        }
+                        return None;
+                    },
+                    // a named function used as a value
+                    None if symbol_table.lookup_func(v.clone()).is_some() => {
+                        let (func_att,_) = symbol_table.lookup_func(v.clone())?;
+                        if !func_att.type_parameters.is_empty() {
+                            self.error_pipe.report_error(
+                                CompilerError::new(
+                                    ErrorType::SemanticError,
+                                    format!("generic function '{}' cannot be used as a value, its type arguments are only inferred at a call",v).as_str(),
+                                    node.pos,
+                                    v.len() as u32,
+                                )
+                            );
+                            return None;
+                        }
+                        Some(Self::function_type(&func_att))
+                    },
+                    None => {
+                        self.error_pipe.report_error(
+                            CompilerError::new(
+                                ErrorType::SemanticError,
+                                format!("use of unclared variable '{}'",v).as_str(),
+                                node.pos,
+                                v.len() as u32,
+                            )
+                        );
+                        return None;
+                    }
+                }
+            }
+            Node::Assignment(tk) => {
+                let left_type = match &tk.left.node {
+                    Node::DeclareVar(v) => {
+                        let var_type = match &v.var_type {
+                            Some(var_type) => self.resolve_type(var_type, &symbol_table),
+                            // checked before the variable exists so `let x = x` reads an outer x
+                            None => self.check_expression_type(&tk.right, &symbol_table).map(|found| Self::inferred_type(&tk.right, found))
+                        };
+                        let Some(var_type) = var_type else {
+                            Self::declare_untyped(v, &symbol_table);
+                            return None;
+                        };
+                        symbol_table.insert_var(v.name.node.clone());
+                        symbol_table.update_var(
+                            v.name.node.clone(),
+                            Some(var_type.clone()),
+                            Some(symbol_table.size_of(&var_type)),
+                            Some(0),
+                            Some(v.name.pos.0)
+                        );
+                        if let Some(constant) = &v.constant {
+                            symbol_table.var_set_constant(v.name.node.clone(), constant.node);
+                        }
+                        symbol_table.var_set_public(v.name.node.clone(), v.public);
+                        symbol_table.var_set_addressable(v.name.node.clone(), symbol_table.scope != Scope::Global);
+                        if v.var_type.is_none() {
+                            if let Node::Literal(LiteralValue::Int(i)) = &tk.right.node {
+                                symbol_table.var_set_inferred_literal(v.name.node.clone(), Some(*i));
+                            }
+                            return Some(var_type);
+                        }
+                        var_type
+                    },
+                    Node::Variable(v) => {
+                        match symbol_table.lookup_var(v.clone()){
+                            Some((var,scope)) => {
+                                let line = node.pos.0;
+                                if let Some(line_declare) = var.line_declare {
+                                    if line < line_declare {
+                                        self.error_pipe.report_error(
+                                            CompilerError::new(
+                                                ErrorType::SemanticError,
+                                                format!("use of unclared variable '{}'",v).as_str(),
+                                                node.pos,
+                                                v.len() as u32,
+                                            )
+                                        );
+                                        symbol_table.var_push_line_ref_at(scope, v.clone(), node.pos.0);
+                                        return None;
+                                    }
+                                    symbol_table.var_push_line_ref_at(scope, v.clone(), node.pos.0);
+                                    if !self.check_not_constant(&tk.left, &symbol_table) {
+                                        return None;
+                                    }
+                                    match var.data_type {
+                                        Some(var_type) => var_type,
+                                        None => {
+                                            self.check_expression_type(&tk.right, &symbol_table);
+                                            return None;
+                                        }
+                                    }
+                                }
+                                else{
+                                    self.error_pipe.report_error(
+                                        CompilerError::new(
+                                            ErrorType::SemanticError,
+                                            format!("use of unclared variable '{}'",v).as_str(),
+                                            node.pos,
+                                            v.len() as u32,
+                                        )
+                                    );
+                                    symbol_table.var_push_line_ref_at(scope, v.clone(), node.pos.0);
+                                    return None;
+                                }
+                            },
+                            None => {
+                                self.error_pipe.report_error(
+                                    CompilerError::new(
+                                        ErrorType::SemanticError,
+                                        format!("use of unclared variable '{}'",v).as_str(),
+                                        node.pos,
+                                        v.len() as u32,
+                                    )
+                                );
+                                return None;
+                            }
+                        }
+                    },
+                    Node::FieldAccess(_) | Node::Index(_) | Node::Deref(_) => {
+                        if !self.check_not_constant(&tk.left, &symbol_table) {
+                            return None;
+                        }
+                        self.check_expression_type(&tk.left, &symbol_table)?
+                    },
+                    _ => {
+                        self.error_pipe.report_error(
+                            CompilerError::new(
+                                ErrorType::SemanticError,
+                                format!("left operand can't assign to").as_str(),
+                                node.pos,
+                                node.length,
+                            )
+                        );
+                        return None;
+                    }
+                };
+                match self.check_value_type(&tk.right, &left_type, &symbol_table){
+                    Some(right_type) => {
+                        if Self::type_matches(&left_type, &right_type) {
+                            return Some(left_type);
+                        }
+                        self.error_pipe.report_error(
+                            CompilerError::new(
+                                ErrorType::SemanticError,
+                                format!("expected '{}' found '{}'",left_type.to_string(),right_type.to_string()).as_str(),
+                                node.pos,
+                                node.length,
+                            )
+                        );
+                        return None;
+                    },
+                    None => {
+                        return None;
+                    }
+                }
+                
+            },
+            Node::CompoundAssignment(tk) => {
+                let operator = tk.operator.node.compound_operator()?;
+                let value = AstNode::new(
+                    Node::BinaryExpression(BinExp {
+                        left: tk.left.clone(),
+                        right: tk.right.clone(),
+                        operator: AstNode::new(operator, tk.operator.pos, tk.operator.length)
+                    }),
+                    node.pos,
+                    node.length
+                );
+                let value_type = self.check_expression_type(&value, &symbol_table)?;
+                if !self.check_not_constant(&tk.left, &symbol_table) {
+                    return None;
+                }
+                let left_type = match &tk.left.node {
+                    Node::Variable(v) => match symbol_table.lookup_var(v.clone()) {
+                        Some((var,_)) => var.data_type?,
+                        None => return None
+                    },
+                    Node::FieldAccess(_) | Node::Index(_) | Node::Deref(_) => self.check_expression_type(&tk.left, &symbol_table)?,
+                    _ => {
+                        self.error_pipe.report_error(
+                            CompilerError::new(
+                                ErrorType::SemanticError,
+                                "left operand can't assign to",
+                                node.pos,
+                                node.length,
+                            )
+                        );
+                        return None;
+                    }
+                };
+                if discriminant(&left_type) == discriminant(&value_type) || Self::type_castable(&value_type,&left_type) {
+                    return Some(left_type);
+                }
+                self.error_pipe.report_error(
+                    CompilerError::new(
+                        ErrorType::SemanticError,
+                        format!("expected '{}' found '{}'",left_type.to_string(),value_type.to_string()).as_str(),
+                        node.pos,
+                        node.length,
+                    )
+                );
+                None
+            },
+            Node::Literal(literal) => {
+                match literal {
+                    LiteralValue::Int(i) => {
+                        match Self::get_int_type(*i){
+                            Some(t) => return Some(t),
+                            None => {
+                                self.error_pipe.report_error(
+                                    CompilerError::new(
+                                        ErrorType::SemanticError,
+                                        format!("value is too large").as_str(),
+                                        node.pos,
+                                        node.length,
+                                    )
+                                );
+                                return None;
+                            }
+                        }
+                    },
+                    LiteralValue::Float(f) => {
+                        match Self::get_float_type(*f){
+                            Some(t) => return Some(t),
+                            None => {
+                                self.error_pipe.report_error(
+                                    CompilerError::new(
+                                        ErrorType::SemanticError,
+                                        format!("value is too large").as_str(),
+                                        node.pos,
+                                        node.length,
+                                    )
+                                );
+                                return None;
+                            }
+                        }
+                    },
+                    LiteralValue::Str(st) => {
+                        return Some(DataType::Str(st.len() as u32))
+                    },
+                    LiteralValue::Bool(b) => {
+                        return Some(DataType::Boolean);
+                    }
+                }
+            },
+            Node::BinaryExpression(exp) => {
+                // a variable declared from an integer literal takes the type of the other operand,
+                // unless that is a literal too or only a shift amount
+                let shift = exp.operator.node == TokenType::ShiftLeft || exp.operator.node == TokenType::ShiftRight;
+                let decides = |operand:&AstNode<Node>| !shift && !matches!(operand.node,Node::Literal(_)) && Self::inferred_literal(operand, &symbol_table).is_none();
+                let (left_type,right_type) = if Self::inferred_literal(&exp.left, &symbol_table).is_some() && decides(&exp.right) {
+                    let right_type = self.check_expression_type(&exp.right, &symbol_table)?;
+                    self.settle_inferred_literal(&exp.left, &right_type, &symbol_table);
+                    (self.check_expression_type(&exp.left, &symbol_table)?,right_type)
+                }
+                else {
+                    let left_type = self.check_expression_type(&exp.left, &symbol_table)?;
+                    if decides(&exp.left) {
+                        self.settle_inferred_literal(&exp.right, &left_type, &symbol_table);
+                    }
+                    (left_type,self.check_expression_type(&exp.right, &symbol_table)?)
+                };
+                if exp.operator.node == TokenType::ShiftLeft || exp.operator.node == TokenType::ShiftRight {
+                    let operator = if exp.operator.node == TokenType::ShiftLeft { "<<" } else { ">>" };
+                    if !Self::is_integer(&left_type) {
+                        self.error_pipe.report_error(
+                            CompilerError::new(
+                                ErrorType::SemanticError,
+                                format!("operator '{}' can only be used on integer type found '{}'",operator,left_type.to_string()).as_str(),
+                                exp.operator.pos,
+                                exp.operator.length,
+                            )
+                        );
+                        return None;
+                    }
+                    let literal_amount = match &exp.right.node {
+                        Node::Literal(LiteralValue::Int(i)) => *i >= 0,
+                        _ => false
+                    };
+                    if !Self::is_unsigned(&right_type) && !literal_amount {
+                        self.error_pipe.report_error(
+                            CompilerError::new(
+                                ErrorType::SemanticError,
+                                format!("shift amount must be an unsigned integer found '{}'",right_type.to_string()).as_str(),
+                                exp.right.pos,
+                                exp.right.length,
+                            )
+                        );
+                        return None;
+                    }
+                    return Some(left_type);
+                }
+                
+                let operand_type = if left_type != right_type {
+                    match self.type_coercion(&left_type, &right_type) {
+                        Some(result_type) => result_type,
+                        None => {
+                            self.error_pipe.report_error(
+                                CompilerError::new(
+                                    ErrorType::SemanticError,
+                                    format!("operand have mismatched type '{}' and '{}'",left_type.to_string(),right_type.to_string()).as_str(),
+                                    exp.operator.pos,
+                                    exp.operator.length,
+                                )
+                            );
+                            return None
+                        },
+                    }
+                }
+                else{
+                    left_type
+                };
+                match exp.operator.node {
+                    TokenType::AdditionOperator => {
+                        match operand_type {
+                            DataType::Array(_) => {
                                 self.error_pipe.report_error(
                                     CompilerError::new(
                                         ErrorType::SemanticError,
@@ -603,6 +2124,43 @@ impl<'a,'b> SemanticAnalyzer<'a,'b> {
                             }
                         }
                     },
+                    TokenType::NotEqual => {
+                        match operand_type {
+                            DataType::Array(_) => {
+                                self.error_pipe.report_error(
+                                    CompilerError::new(
+                                        ErrorType::SemanticError,
+                                        "operator '!=' cannot be used on Array",
+                                        exp.operator.pos,
+                                        exp.operator.length,
+                                    )
+                                );
+                                None
+                            },
+                            _ => {
+                                Some(DataType::Boolean)
+                            }
+                        }
+                    },
+                    TokenType::BitwiseAnd | TokenType::BitwiseOr | TokenType::BitwiseXor => {
+                        if Self::is_integer(&operand_type) {
+                            return Some(operand_type);
+                        }
+                        let operator = match exp.operator.node {
+                            TokenType::BitwiseAnd => "&",
+                            TokenType::BitwiseOr => "|",
+                            _ => "^"
+                        };
+                        self.error_pipe.report_error(
+                            CompilerError::new(
+                                ErrorType::SemanticError,
+                                format!("operator '{}' can only be used on integer type found '{}'",operator,operand_type.to_string()).as_str(),
+                                exp.operator.pos,
+                                exp.operator.length,
+                            )
+                        );
+                        None
+                    },
                     TokenType::Less => {
                         match operand_type {
                             DataType::Array(_) => {
@@ -824,51 +2382,450 @@ impl<'a,'b> SemanticAnalyzer<'a,'b> {
                                 return None;
                             }
                         }
-                    },
-                    TokenType::Or => {
-                        match operand_type {
-                            DataType::Boolean => {
-                                return Some(operand_type);
-                            },
-                            _ => {
+                    },
+                    TokenType::Or => {
+                        match operand_type {
+                            DataType::Boolean => {
+                                return Some(operand_type);
+                            },
+                            _ => {
+                                self.error_pipe.report_error(
+                                    CompilerError::new(
+                                        ErrorType::SemanticError,
+                                        "operator '||' can only be used on boolean expression",
+                                        exp.operator.pos,
+                                        exp.operator.length,
+                                    )
+                                );
+                                return None;
+                            }
+                        }
+                    },
+                    _ => return None
+                }
+            },
+            // a variable holding a function value hides a function of the same name
+            Node::FunctionCall(called) if matches!(symbol_table.lookup_var(called.function_name.node.clone()),Some((VarAttribute { data_type: Some(DataType::Function(_,_)), .. },_))) => {
+                let callee = AstNode::new(Node::Variable(called.function_name.node.clone()),called.function_name.pos,called.function_name.length);
+                let function_type = self.check_expression_type(&callee, &symbol_table)?;
+                self.check_value_call(&callee, &function_type, &called.arguments, &symbol_table)
+            },
+            Node::FunctionCall(called) => {
+                let func_att = match symbol_table.lookup_func(called.function_name.node.clone()){
+                    Some((calling_func,_)) => {
+                        calling_func
+                    },
+                    None if called.function_name.node == "len" => {
+                        return self.check_len_call(called, node, &symbol_table);
+                    },
+                    None if called.function_name.node == "alloc" || called.function_name.node == "free" => {
+                        return self.check_heap_call(called, node, &symbol_table);
+                    },
+                    None if symbol_table.lookup_var(called.function_name.node.clone()).is_some() => {
+                        let callee = AstNode::new(Node::Variable(called.function_name.node.clone()),called.function_name.pos,called.function_name.length);
+                        let value_type = self.check_expression_type(&callee, &symbol_table)?;
+                        return self.check_value_call(&callee, &value_type, &called.arguments, &symbol_table);
+                    },
+                    None => {
+                        self.error_pipe.report_error(
+                            CompilerError::new(
+                                ErrorType::SemanticError,
+                                format!("use of undeclared function '{}'",called.function_name.node).as_str(),
+                                called.function_name.pos,
+                                called.function_name.length,
+                            )
+                        );
+                        return None;
+                    }
+                };
+                if !func_att.type_parameters.is_empty() {
+                    return self.check_generic_call(called, &symbol_table);
+                }
+                if !self.check_arguments(called, &func_att.parameter, &symbol_table) {
+                    return None;
+                }
+                return Some(func_att.return_type);
+            },
+            Node::MethodCall(call) => self.check_method_call(call, &symbol_table),
+            Node::ClosureCall(call) => {
+                let function_type = Self::inferred_type(&call.callee, self.check_expression_type(&call.callee, &symbol_table)?);
+                self.check_value_call(&call.callee, &function_type, &call.arguments, &symbol_table)
+            },
+            Node::Closure(func) => self.check_closure(func, node, &symbol_table),
+            Node::BooleanNot(exp) => {
+                let exp_type = self.check_expression_type(&exp.exp, &symbol_table)?;
+                if discriminant(&exp_type) != discriminant(&DataType::Boolean){
+                    self.error_pipe.report_error(
+                        CompilerError::new(
+                            ErrorType::SemanticError,
+                            format!("cannot apply ! to '{}'",exp_type.to_string()).as_str(),
+                            node.pos,
+                            node.length,
+                        )
+                    );
+                    return None;
+                }
+                Some(DataType::Boolean)
+            },
+            Node::BitwiseNot(exp) => {
+                let exp_type = self.check_expression_type(&exp.exp, &symbol_table)?;
+                if !Self::is_integer(&exp_type){
+                    self.error_pipe.report_error(
+                        CompilerError::new(
+                            ErrorType::SemanticError,
+                            format!("cannot apply ~ to '{}'",exp_type.to_string()).as_str(),
+                            node.pos,
+                            node.length,
+                        )
+                    );
+                    return None;
+                }
+                Some(exp_type)
+            },
+            Node::Negation(exp) => {
+                let exp_type = self.check_expression_type(&exp.exp, &symbol_table)?;
+                let signed = matches!(exp_type,DataType::I8 | DataType::I16 | DataType::I32 | DataType::I64 | DataType::F32 | DataType::F64);
+                if !signed {
+                    self.error_pipe.report_error(
+                        CompilerError::new(
+                            ErrorType::SemanticError,
+                            format!("cannot apply - to '{}'",exp_type.to_string()).as_str(),
+                            node.pos,
+                            node.length,
+                        )
+                    );
+                    return None;
+                }
+                Some(exp_type)
+            },
+            Node::Reference(reference) => self.check_reference(reference, node, &symbol_table),
+            Node::Deref(exp) => {
+                match self.check_expression_type(&exp.exp, &symbol_table)? {
+                    DataType::Reference(target,_) => Some(*target),
+                    other => {
+                        self.error_pipe.report_error(
+                            CompilerError::new(
+                                ErrorType::SemanticError,
+                                format!("cannot dereference a value of type '{}'",other.to_string()).as_str(),
+                                node.pos,
+                                node.length,
+                            )
+                        );
+                        None
+                    }
+                }
+            },
+            Node::Index(exp) => {
+                let target_type = match self.check_expression_type(&exp.target, &symbol_table){
+                    Some(t) => Self::dereferenced(t),
+                    None => return None,
+                };
+                let (element_type,length) = match &target_type {
+                    DataType::Array(arr) => (*arr.data_type.clone(),Some(arr.length)),
+                    DataType::Slice(element) => (*element.clone(),None),
+                    DataType::Str(_) => (DataType::U8,None),
+                    _ => {
+                        self.error_pipe.report_error(
+                            CompilerError::new(
+                                ErrorType::SemanticError,
+                                format!("cannot index into a value of type '{}'",target_type.to_string()).as_str(),
+                                exp.target.pos,
+                                exp.target.length,
+                            )
+                        );
+                        return None;
+                    }
+                };
+                // a[start..end] takes a slice of the elements from start up to end
+                let bounds = match &exp.index.node {
+                    Node::Range(range) => vec![range.start.as_ref(),range.end.as_ref()],
+                    _ => vec![exp.index.as_ref()]
+                };
+                let inclusive = matches!(&exp.index.node,Node::Range(range) if range.inclusive);
+                let mut constants:Vec<(usize,i64)> = vec![];
+                for (position,bound) in bounds.iter().enumerate() {
+                    let bound_type = self.check_expression_type(bound, &symbol_table)?;
+                    if !Self::is_integer(&bound_type) {
+                        self.error_pipe.report_error(
+                            CompilerError::new(
+                                ErrorType::SemanticError,
+                                format!("array index must be an integer found '{}'",bound_type.to_string()).as_str(),
+                                bound.pos,
+                                bound.length,
+                            )
+                        );
+                        return None;
+                    }
+                    if let Node::Literal(LiteralValue::Int(i)) = &bound.node {
+                        constants.push((position,*i));
+                    }
+                }
+                let is_slice = bounds.len() == 2;
+                for (position,i) in &constants {
+                    // the exclusive end of a slice may point one past the last element
+                    let past_end = match length {
+                        Some(length) if is_slice && *position == 1 && !inclusive => *i > length as i64,
+                        Some(length) => *i >= length as i64,
+                        None => false
+                    };
+                    if *i < 0 || past_end {
+                        let message = match length {
+                            Some(length) => format!("index {} is out of bounds for an array of length {}",i,length),
+                            None => format!("index {} is out of bounds",i)
+                        };
+                        self.error_pipe.report_error(
+                            CompilerError::new(
+                                ErrorType::SemanticError,
+                                message.as_str(),
+                                bounds[*position].pos,
+                                bounds[*position].length,
+                            )
+                        );
+                        return None;
+                    }
+                }
+                // a[2..=1] is as empty as a[2..2]
+                let empty_end = if inclusive { 1 } else { 0 };
+                if let [(0,start),(1,end)] = constants[..] {
+                    if start > end + empty_end {
+                        self.error_pipe.report_error(
+                            CompilerError::new(
+                                ErrorType::SemanticError,
+                                format!("slice starts at {} but ends at {}",start,end).as_str(),
+                                exp.index.pos,
+                                exp.index.length,
+                            )
+                        );
+                        return None;
+                    }
+                }
+                if !is_slice {
+                    return Some(element_type);
+                }
+                if let DataType::Str(_) = target_type {
+                    return Some(DataType::Str(0));
+                }
+                Some(DataType::Slice(Box::new(element_type)))
+            },
+            Node::FieldAccess(access) => {
+                let target_type = match self.check_expression_type(&access.target, &symbol_table){
+                    Some(t) => Self::dereferenced(t),
+                    None => return None,
+                };
+                if let DataType::Struct(name) = &target_type {
+                    if let Some(struct_attribute) = symbol_table.lookup_struct(name.clone()) {
+                        if let Some((field_type,_)) = struct_attribute.get_field(&access.field.node) {
+                            return Some(field_type);
+                        }
+                    }
+                }
+                if let DataType::Tuple(elements) = &target_type {
+                    if let Some(element_type) = access.field.node.parse::<usize>().ok().and_then(|i| elements.get(i)) {
+                        return Some(element_type.clone());
+                    }
+                }
+                self.error_pipe.report_error(
+                    CompilerError::new(
+                        ErrorType::SemanticError,
+                        format!("type '{}' has no field '{}'",target_type.to_string(),access.field.node).as_str(),
+                        access.field.pos,
+                        access.field.length,
+                    )
+                );
+                None
+            },
+            Node::StructLiteral(literal) => {
+                if literal.struct_name.node.contains("::") {
+                    self.import_path(&literal.struct_name, &symbol_table)?;
+                }
+                let struct_attribute = match symbol_table.lookup_struct(literal.struct_name.node.clone()) {
+                    Some(s) => s,
+                    None => {
+                        self.error_pipe.report_error(
+                            CompilerError::new(
+                                ErrorType::SemanticError,
+                                format!("unknown struct '{}'",literal.struct_name.node).as_str(),
+                                literal.struct_name.pos,
+                                literal.struct_name.length,
+                            )
+                        );
+                        return None;
+                    }
+                };
+                let mut valid = true;
+                let mut initialized:Vec<String> = vec![];
+                for (field_name,value) in &literal.fields {
+                    let value_type = match struct_attribute.get_field(&field_name.node) {
+                        Some((field_type,_)) => self.check_value_type(value, &field_type, &symbol_table),
+                        None => self.check_expression_type(value, &symbol_table)
+                    };
+                    if initialized.contains(&field_name.node) {
+                        self.error_pipe.report_error(
+                            CompilerError::new(
+                                ErrorType::SemanticError,
+                                format!("field '{}' specified more than once",field_name.node).as_str(),
+                                field_name.pos,
+                                field_name.length,
+                            )
+                        );
+                        valid = false;
+                        continue;
+                    }
+                    initialized.push(field_name.node.clone());
+                    let field_type = match struct_attribute.get_field(&field_name.node) {
+                        Some((t,_)) => t,
+                        None => {
+                            self.error_pipe.report_error(
+                                CompilerError::new(
+                                    ErrorType::SemanticError,
+                                    format!("struct '{}' has no field '{}'",struct_attribute.struct_name,field_name.node).as_str(),
+                                    field_name.pos,
+                                    field_name.length,
+                                )
+                            );
+                            valid = false;
+                            continue;
+                        }
+                    };
+                    match value_type {
+                        Some(value_type) => {
+                            if !Self::type_matches(&field_type, &value_type) {
+                                self.error_pipe.report_error(
+                                    CompilerError::new(
+                                        ErrorType::SemanticError,
+                                        format!("expected '{}' found '{}'",field_type.to_string(),value_type.to_string()).as_str(),
+                                        value.pos,
+                                        value.length,
+                                    )
+                                );
+                                valid = false;
+                            }
+                        },
+                        None => valid = false
+                    }
+                }
+                for (_,field_name,_) in &struct_attribute.fields {
+                    if !initialized.contains(field_name) {
+                        self.error_pipe.report_error(
+                            CompilerError::new(
+                                ErrorType::SemanticError,
+                                format!("missing field '{}' in initializer of '{}'",field_name,struct_attribute.struct_name).as_str(),
+                                literal.struct_name.pos,
+                                literal.struct_name.length,
+                            )
+                        );
+                        valid = false;
+                    }
+                }
+                if !valid {
+                    return None;
+                }
+                Some(DataType::Struct(struct_attribute.struct_name))
+            },
+            Node::EnumValue(value) => {
+                if let Some(module) = symbol_table.lookup_module(value.enum_name.node.clone()) {
+                    return self.check_module_call(&module, value, &symbol_table);
+                }
+                // Point::new(..) calls a function of an impl block that takes no self
+                let is_variant = symbol_table.lookup_enum(value.enum_name.node.clone()).is_some_and(|e| e.get_variant(&value.variant.node).is_some());
+                let associated = ImplBlock::method_name(&DataType::Struct(value.enum_name.node.clone()), &value.variant.node);
+                if let (false,Some((func_att,_))) = (is_variant,symbol_table.lookup_func(associated)) {
+                    let called = FuncCall { function_name: value.variant.clone(), arguments: value.arguments.clone() };
+                    if !self.check_arguments(&called, &func_att.parameter, &symbol_table) {
+                        return None;
+                    }
+                    return Some(func_att.return_type);
+                }
+                self.check_enum_value(value, node, None, &symbol_table)
+            },
+            Node::Match(m) => {
+                self.check_match(m, node, &symbol_table, true)
+            },
+            Node::Loop(l) => {
+                self.check_loop(l, node, &symbol_table, true, None)
+            },
+            Node::ArrayLiteral(literal) => {
+                let (elements,length) = match literal {
+                    ArrayLiteral::Elements(elements) => (elements.iter().collect::<Vec<&AstNode<Node>>>(),elements.len() as u32),
+                    ArrayLiteral::Repeat(value,count) => (vec![value.as_ref()],count.node),
+                };
+                if elements.is_empty() {
+                    self.error_pipe.report_error(
+                        CompilerError::new(
+                            ErrorType::SemanticError,
+                            "cannot infer the element type of an empty array",
+                            node.pos,
+                            node.length,
+                        )
+                    );
+                    return None;
+                }
+                // integer and float literals widen to the largest element
+                let mut element_type:Option<DataType> = None;
+                let mut valid = true;
+                for element in elements {
+                    let found = match self.check_expression_type(element, &symbol_table) {
+                        Some(t) => t,
+                        None => {
+                            valid = false;
+                            continue;
+                        }
+                    };
+                    element_type = match element_type {
+                        None => Some(found),
+                        Some(expected) => {
+                            let unified = if expected == found {
+                                Some(found.clone())
+                            }
+                            else if let (DataType::Struct(_) | DataType::Enum(_) | DataType::Array(_),_) = (&expected,&found) {
+                                if Self::type_matches(&expected, &found) {
+                                    Some(expected.clone())
+                                }
+                                else if Self::type_matches(&found, &expected) {
+                                    Some(found.clone())
+                                }
+                                else {
+                                    None
+                                }
+                            }
+                            else {
+                                self.type_coercion(&expected, &found)
+                            };
+                            if unified.is_none() {
                                 self.error_pipe.report_error(
                                     CompilerError::new(
                                         ErrorType::SemanticError,
-                                        "operator '||' can only be used on boolean expression",
-                                        exp.operator.pos,
-                                        exp.operator.length,
+                                        format!("expected '{}' found '{}'",expected.to_string(),found.to_string()).as_str(),
+                                        element.pos,
+                                        element.length,
                                     )
                                 );
-                                return None;
+                                valid = false;
                             }
+                            Some(unified.unwrap_or(expected))
                         }
-                    },
-                    _ => return None
+                    };
+                }
+                if !valid {
+                    return None;
                 }
+                Some(DataType::Array(Array { length, data_type: Box::new(element_type?) }))
             },
-            Node::FunctionCall(called) => {
-                let func_att = match symbol_table.lookup_func(called.function_name.node.clone()){
-                    Some((calling_func,_)) => {
-                        calling_func
-                    },
-                    None => {
-                        self.error_pipe.report_error(
-                            CompilerError::new(
-                                ErrorType::SemanticError,
-                                format!("use of undeclared function '{}'",called.function_name.node).as_str(),
-                                called.function_name.pos,
-                                called.function_name.length,
-                            )
-                        );
-                        return None;
-                    }
-                };
-                return Some(func_att.return_type);
+            Node::Tuple(tuple) => {
+                let mut member_types = vec![];
+                for member in &tuple.members {
+                    member_types.push(self.check_expression_type(member, &symbol_table)?);
+                }
+                Some(DataType::Tuple(member_types))
+            },
+            Node::Range(range) => {
+                let element_type = self.check_range(range, None, &symbol_table)?;
+                Some(DataType::Range(Box::new(element_type)))
             },
-            Node::MethodCall(_) => todo!(),
-            Node::BooleanNot(_) => todo!(),
-            Node::Tuple(_) => todo!(),
-            Node::Range(_) => todo!(),
+            Node::Cast(cast) => self.check_cast(cast, node, &symbol_table),
+            Node::Try(t) => self.check_try(&t.exp, node, &symbol_table),
+            // already reported by the parser
+            Node::ParserError(_) => None,
             _ => {
                 println!("{:#?}",node);
                 todo!()
@@ -914,17 +2871,48 @@ impl<'a,'b> SemanticAnalyzer<'a,'b> {
                 }
             },
             Node::DeclareVar(v) => {
+                let var_type = match &v.var_type {
+                    Some(var_type) => match self.resolve_type(var_type, &symbol_table) {
+                        Some(t) => t,
+                        None => {
+                            Self::declare_untyped(v, &symbol_table);
+                            return
+                        }
+                    },
+                    None => {
+                        self.error_pipe.report_error(
+                            CompilerError::new(
+                                ErrorType::SemanticError,
+                                format!("cannot infer the type of '{}' without an initializer",v.name.node).as_str(),
+                                v.name.pos,
+                                v.name.length,
+                            )
+                        );
+                        Self::declare_untyped(v, &symbol_table);
+                        return;
+                    }
+                };
                 symbol_table.insert_var(v.name.node.clone());
                 symbol_table.update_var(
                     v.name.node.clone(),
-                    Some(v.var_type.node.clone()),
-                    Some(v.var_type.node.get_size_in_bytes()),
+                    Some(var_type.clone()),
+                    Some(symbol_table.size_of(&var_type)),
                     Some(0),
                     Some(v.name.pos.0)
-                )
+                );
+                if let Some(constant) = &v.constant {
+                    symbol_table.var_set_constant(v.name.node.clone(), constant.node);
+                }
+                symbol_table.var_set_addressable(v.name.node.clone(), symbol_table.scope != Scope::Global);
             },
-            Node::Assignment(_) => {
+            Node::CompoundAssignment(tk) | Node::Assignment(tk) => {
                 self.check_expression_type(&node, &symbol_table);
+                if let Some(AstNode { node: Node::Variable(v), pos, length }) = Self::place_root(&tk.left, &symbol_table) {
+                    self.record_use(v, *pos, *length, true, &symbol_table);
+                }
+                if matches!(node.node,Node::Assignment(_)) {
+                    self.hold_borrow(tk, &symbol_table);
+                }
             },
             Node::Literal(_) => {
                 self.check_expression_type(&node, &symbol_table);
@@ -938,23 +2926,61 @@ impl<'a,'b> SemanticAnalyzer<'a,'b> {
             Node::MethodCall(_) => {
                 self.check_expression_type(&node, &symbol_table);
             },
+            Node::ClosureCall(_) | Node::Closure(_) => {
+                self.check_expression_type(node, &symbol_table);
+            },
+            Node::Function(func) if !func.type_parameters.is_empty() => {
+                self.declare_generic(func, &symbol_table);
+            },
+            Node::Trait(def) => self.declare_trait(def, &symbol_table),
+            Node::Impl(block) => self.check_impl(block, &symbol_table),
             Node::Function(func) => {
+                let return_type = self.resolve_type(&func.return_type, &symbol_table).unwrap_or(DataType::Void);
                 let func_block = symbol_table.insert_func(func.function_name.node.clone());
-                symbol_table.update_func(func.function_name.node.clone(), Some(func.return_type.node.clone()), Some(func.function_name.pos.0));
+                symbol_table.update_func(func.function_name.node.clone(), Some(return_type.clone()), Some(func.function_name.pos.0));
+                symbol_table.func_set_public(func.function_name.node.clone(), func.public);
+                if func.external {
+                    symbol_table.func_set_calling_convention(func.function_name.node.clone(), CallingConvention::C);
+                    if !Self::is_c_type(&return_type) {
+                        self.error_pipe.report_error(
+                            CompilerError::new(
+                                ErrorType::SemanticError,
+                                format!("extern function cannot return '{}', only numbers, bool and char cross the C boundary",return_type.to_string()).as_str(),
+                                func.function_name.pos,
+                                func.function_name.length,
+                            )
+                        );
+                    }
+                }
                 for param in &func.parameters {
                     let param_name = &param.node.name.node;
-                    let param_type = &param.node.var_type.node;
+                    let param_type = &param.node.var_type.as_ref().and_then(|t| self.resolve_type(t, &symbol_table)).unwrap_or(DataType::Void);
+                    if func.external && !Self::is_c_type(param_type) {
+                        let (pos,length) = param.node.var_type.as_ref().map_or((param.pos,param.length),|t| (t.pos,t.length));
+                        self.error_pipe.report_error(
+                            CompilerError::new(
+                                ErrorType::SemanticError,
+                                format!("extern function cannot take '{}', only numbers, bool and char cross the C boundary",param_type.to_string()).as_str(),
+                                pos,
+                                length,
+                            )
+                        );
+                    }
                     symbol_table.func_push_param(func.function_name.node.clone(), (param_type.clone(),param_name.clone()));
                     func_block.insert_var(param_name.clone());
                     func_block.update_var(
                         param_name.clone(),
                         Some(param_type.clone()),
-                        Some(param_type.get_size_in_bytes()),
+                        Some(symbol_table.size_of(param_type)),
                         Some(1),
                         Some(param.pos.0)
-                    )
+                    );
+                    func_block.var_set_addressable(param_name.clone(), true);
                 }
+                self.borrow_logs.borrow_mut().push(BorrowLog::default());
                 self.analyze_body(&func.body, func_block);
+                let log = self.borrow_logs.borrow_mut().pop().unwrap_or_default();
+                self.check_borrows(&log);
             },
             Node::Import(imp) => {
                 if symbol_table.scope != Scope::Global{
@@ -968,29 +2994,36 @@ impl<'a,'b> SemanticAnalyzer<'a,'b> {
                     );
                     return;
                 }
-                match &imp.alias {
-                    Some(alias) => {
-                        match self.symbol_table.lookup_var(alias.node.clone()){
-                            Some(_) => {
-                                self.error_pipe.report_error(
-                                    CompilerError::new(
-                                        ErrorType::SemanticError,
-                                        "import alias overide existing identifier",
-                                        alias.pos,
-                                        alias.length,
-                                    )
-                                );
-                            },
-                            None => {
-                                symbol_table.insert_var(alias.node.clone());
-                            },
-                        }
-                    },
+                // without an alias the module is named after its file
+                let alias = match &imp.alias {
+                    Some(alias) => alias.clone(),
                     None => {
-
+                        let stem = Path::new(&imp.import_name.node).file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+                        AstNode::new(stem,imp.import_name.pos,imp.import_name.length)
                     }
+                };
+                if self.symbol_table.lookup_var(alias.node.clone()).is_some() || self.symbol_table.lookup_module(alias.node.clone()).is_some() {
+                    self.error_pipe.report_error(
+                        CompilerError::new(
+                            ErrorType::SemanticError,
+                            "import alias overide existing identifier",
+                            alias.pos,
+                            alias.length,
+                        )
+                    );
+                    return;
                 }
-                
+                // a module that could not be loaded has already been reported
+                let module = match self.modules.get(&imp.import_name.node) {
+                    Some(module) => ModuleAttribute { alias: alias.node, ..module.clone() },
+                    None => ModuleAttribute {
+                        alias: alias.node,
+                        module_name: String::new(),
+                        module_path: imp.import_name.node.clone(),
+                        module_table: None
+                    }
+                };
+                symbol_table.insert_module(module);
             },
             Node::Return(ret) => {
                 let current_func = match symbol_table.get_current_func_info(){
@@ -1010,20 +3043,25 @@ impl<'a,'b> SemanticAnalyzer<'a,'b> {
                 
                 let return_value_type = match ret {
                     Some(return_exp) => {
-                        match self.check_expression_type(&return_exp, &symbol_table){
+                        match self.check_value_type(return_exp, &current_func.return_type, &symbol_table){
                             Some(dt) => dt,
                             None => return ()
                         }
                     },
                     None => DataType::Void,
                 };
-                if self.type_coercion(&current_func.return_type, &return_value_type).is_none() && current_func.return_type != return_value_type {
+                // references only convert from &mut to &, not the other way around
+                let accepted = match (&current_func.return_type,&return_value_type) {
+                    (DataType::Reference(_,_),_) | (_,DataType::Reference(_,_)) => Self::type_matches(&current_func.return_type, &return_value_type),
+                    _ => self.type_coercion(&current_func.return_type, &return_value_type).is_some() || current_func.return_type == return_value_type
+                };
+                if !accepted {
                     self.error_pipe.report_error(
                         CompilerError::new(
                             ErrorType::SemanticError,
                             format!(
-                                "function '{}' expect return type '{}' found '{}'",
-                                current_func.func_name,
+                                "{} expect return type '{}' found '{}'",
+                                Self::function_description(&current_func),
                                 current_func.return_type.to_string(),
                                 return_value_type.to_string()
                             ).as_str(),
@@ -1032,14 +3070,50 @@ impl<'a,'b> SemanticAnalyzer<'a,'b> {
                         )
                     );
                 }
+                else if let Some(return_exp) = ret {
+                    self.check_returned_reference(return_exp, &symbol_table);
+                }
             },
             Node::For(f) => {
                 let for_block = symbol_table.insert_block_scope();
+                self.check_loop_label(&f.label, &symbol_table);
+                for_block.insert_loop(Self::loop_attribute(&f.label, false, false));
+                let element_type = self.check_for_iterable(f, &symbol_table);
                 match &f.var{
                     Some(v) => {
                         match &v.node {
                             Node::Variable(var) => {
                                 for_block.insert_var(var.clone());
+                                // left undeclared when the iterable is invalid so its uses are not reported again
+                                if let Some(element_type) = element_type {
+                                    for_block.update_var(
+                                        var.clone(),
+                                        Some(element_type.clone()),
+                                        Some(for_block.size_of(&element_type)),
+                                        Some(0),
+                                        Some(v.pos.0)
+                                    );
+                                }
+                            },
+                            // for (a, b) in pairs takes the elements apart like a destructuring let
+                            Node::Tuple(tuple) => {
+                                let names = tuple.members.iter().filter_map(|m| match &m.node {
+                                    Node::Variable(name) => Some(AstNode::new(name.clone(),m.pos,m.length)),
+                                    _ => None
+                                }).collect::<Vec<AstNode<String>>>();
+                                if names.len() != tuple.members.len() {
+                                    self.error_pipe.report_error(
+                                        CompilerError::new(
+                                            ErrorType::SemanticError,
+                                            "expected a name for every element of the tuple",
+                                            v.pos,
+                                            v.length,
+                                        )
+                                    );
+                                }
+                                else {
+                                    self.declare_tuple_names(&names, element_type.as_ref(), v, &for_block);
+                                }
                             },
                             _ => ()
                         }
@@ -1050,6 +3124,8 @@ impl<'a,'b> SemanticAnalyzer<'a,'b> {
             },
             Node::While(w) => {
                 let while_block = symbol_table.insert_block_scope();
+                self.check_loop_label(&w.label, &symbol_table);
+                while_block.insert_loop(Self::loop_attribute(&w.label, false, false));
                 let condition_type = match self.check_expression_type(&w.condition, &symbol_table){
                     Some(t) => t,
                     None => return (),
@@ -1064,75 +3140,1210 @@ impl<'a,'b> SemanticAnalyzer<'a,'b> {
                         )
                     );
                 }
-                self.analyze_body(&w.body, while_block);
+                self.analyze_body(&w.body, while_block);
+            },
+            Node::Loop(l) => {
+                self.check_loop(l, node, &symbol_table, false, None);
+            },
+            Node::Break(exit) | Node::Continue(exit) => {
+                let keyword = if let Node::Break(_) = &node.node {"break"} else {"continue"};
+                let label = exit.label.as_ref().map(|l| l.node.clone());
+                let loop_attribute = match symbol_table.lookup_loop(label.clone()) {
+                    Some(loop_attribute) => loop_attribute,
+                    None => {
+                        let message = match &exit.label {
+                            Some(label) if symbol_table.lookup_loop(None).is_some() => format!("use of undeclared loop label '{}'",label.node),
+                            _ => format!("'{}' outside of a loop",keyword)
+                        };
+                        self.error_pipe.report_error(
+                            CompilerError::new(
+                                ErrorType::SemanticError,
+                                message.as_str(),
+                                node.pos,
+                                node.length,
+                            )
+                        );
+                        return;
+                    }
+                };
+                if let Node::Continue(_) = &node.node {
+                    return;
+                }
+                // the loop is left even when the value turns out to be invalid
+                symbol_table.update_loop_break(label.clone(), None);
+                let value_type = match &exit.value {
+                    Some(value) => {
+                        if !loop_attribute.as_value {
+                            let message = if loop_attribute.infinite {
+                                "a loop used as a statement cannot break with a value"
+                            }
+                            else {
+                                "only 'loop' can break with a value"
+                            };
+                            self.error_pipe.report_error(
+                                CompilerError::new(
+                                    ErrorType::SemanticError,
+                                    message,
+                                    value.pos,
+                                    value.length,
+                                )
+                            );
+                            return;
+                        }
+                        let found = match &loop_attribute.value_type {
+                            Some(expected) => self.check_value_type(value, expected, &symbol_table),
+                            None => self.check_expression_type(value, &symbol_table)
+                        };
+                        let found = match found {
+                            Some(t) => t,
+                            None => return
+                        };
+                        if let Some(expected) = &loop_attribute.value_type {
+                            if !Self::type_matches(expected, &found) {
+                                self.error_pipe.report_error(
+                                    CompilerError::new(
+                                        ErrorType::SemanticError,
+                                        format!("expected '{}' found '{}'",expected.to_string(),found.to_string()).as_str(),
+                                        value.pos,
+                                        value.length,
+                                    )
+                                );
+                                return;
+                            }
+                        }
+                        Some(found)
+                    },
+                    None => {
+                        if loop_attribute.as_value {
+                            self.error_pipe.report_error(
+                                CompilerError::new(
+                                    ErrorType::SemanticError,
+                                    "break of a loop used as a value needs a value",
+                                    node.pos,
+                                    node.length,
+                                )
+                            );
+                        }
+                        None
+                    }
+                };
+                symbol_table.update_loop_break(label, value_type);
+            },
+            Node::BooleanNot(exp) => {
+                let exp_type = match self.check_expression_type(&exp.exp, &symbol_table){
+                    Some(t) => t,
+                    None => return (),
+                };
+                if discriminant(&exp_type) != discriminant(&DataType::Boolean){
+                    self.error_pipe.report_error(
+                        CompilerError::new(
+                            ErrorType::SemanticError,
+                            format!("cannot apply ! to '{}'",exp_type.to_string()).as_str(),
+                            node.pos,
+                            node.length,
+                        )
+                    );
+                }
+            },
+            Node::BitwiseNot(_) | Node::Negation(_) | Node::Reference(_) | Node::Deref(_) | Node::FieldAccess(_) | Node::Index(_) | Node::StructLiteral(_) | Node::EnumValue(_) | Node::ArrayLiteral(_) => {
+                self.check_expression_type(node, &symbol_table);
+            },
+            Node::Conditional(con) => {
+                let if_block = symbol_table.insert_block_scope();
+                let condition_type = match self.check_expression_type(&con.if_block.0,&symbol_table){
+                    Some(dt) => dt,
+                    None => {
+                        return ();
+                    }
+                };
+                if condition_type != DataType::Boolean && !Self::type_castable(&condition_type,&DataType::Boolean){
+                    self.error_pipe.report_error(
+                        CompilerError::new(
+                            ErrorType::SemanticError,
+                            format!("expected boolean expression in if statement found '{}'",condition_type.to_string()).as_str(),
+                            con.if_block.0.pos,
+                            con.if_block.0.length,
+                        )
+                    );
+                }
+                self.analyze_body(&con.if_block.1, if_block);
+                for (condition,body) in &con.elif_block{
+                    let elif_block = symbol_table.insert_block_scope();
+                    let condition_type = match self.check_expression_type(&condition,&symbol_table){
+                        Some(dt) => dt,
+                        None => {
+                            return ();
+                        }
+                    };
+                    if condition_type != DataType::Boolean && !Self::type_castable(&condition_type,&DataType::Boolean){
+                        self.error_pipe.report_error(
+                            CompilerError::new(
+                                ErrorType::SemanticError,
+                                format!("expected boolean expression in if statement found '{}'",condition_type.to_string()).as_str(),
+                                con.if_block.0.pos,
+                                con.if_block.0.length,
+                            )
+                        );
+                    }
+                    self.analyze_body(body, elif_block);
+                }
+                match &con.else_block {
+                    Some(body) => {
+                        let else_block = symbol_table.insert_block_scope();
+                        self.analyze_body(body, else_block);
+                    },
+                    None => return ()
+                }
+            },
+            Node::Struct(def) => {
+                if Self::type_name_taken(&def.struct_name.node, &symbol_table) {
+                    self.error_pipe.report_error(
+                        CompilerError::new(
+                            ErrorType::SemanticError,
+                            format!("redefinition of type '{}'",def.struct_name.node).as_str(),
+                            def.struct_name.pos,
+                            def.struct_name.length,
+                        )
+                    );
+                    return;
+                }
+                let mut struct_attribute = StructAttribute {
+                    struct_name: def.struct_name.node.clone(),
+                    line_declare: Some(def.struct_name.pos.0),
+                    fields: vec![],
+                    size: 0,
+                    public: def.public
+                };
+                for field in &def.fields {
+                    let field_name = &field.node.name;
+                    let field_type = match &field.node.var_type {
+                        Some(t) => t,
+                        None => continue
+                    };
+                    if struct_attribute.get_field(&field_name.node).is_some() {
+                        self.error_pipe.report_error(
+                            CompilerError::new(
+                                ErrorType::SemanticError,
+                                format!("duplicate field '{}' in struct '{}'",field_name.node,def.struct_name.node).as_str(),
+                                field_name.pos,
+                                field_name.length,
+                            )
+                        );
+                        continue;
+                    }
+                    // the struct is only inserted after its fields, so a field of its own
+                    // type is reported here instead of as an unknown type
+                    if field_type.node == DataType::Struct(def.struct_name.node.clone()) {
+                        self.error_pipe.report_error(
+                            CompilerError::new(
+                                ErrorType::SemanticError,
+                                format!("recursive struct '{}' has infinite size",def.struct_name.node).as_str(),
+                                field_type.pos,
+                                field_type.length,
+                            )
+                        );
+                        continue;
+                    }
+                    // a reference to the struct itself only holds an address
+                    if let DataType::Reference(target,_) = &field_type.node {
+                        if **target == DataType::Struct(def.struct_name.node.clone()) {
+                            struct_attribute.push_field(field_type.node.clone(), field_name.node.clone(), field_type.node.get_size_in_bytes());
+                            continue;
+                        }
+                    }
+                    let resolved_type = match self.resolve_type(field_type, &symbol_table) {
+                        Some(t) => t,
+                        None => continue
+                    };
+                    let field_size = symbol_table.size_of(&resolved_type);
+                    struct_attribute.push_field(resolved_type, field_name.node.clone(), field_size);
+                }
+                symbol_table.insert_struct(struct_attribute);
+            },
+            Node::Enum(def) => {
+                if Self::type_name_taken(&def.enum_name.node, &symbol_table) {
+                    self.error_pipe.report_error(
+                        CompilerError::new(
+                            ErrorType::SemanticError,
+                            format!("redefinition of type '{}'",def.enum_name.node).as_str(),
+                            def.enum_name.pos,
+                            def.enum_name.length,
+                        )
+                    );
+                    return;
+                }
+                let mut enum_attribute = EnumAttribute {
+                    enum_name: def.enum_name.node.clone(),
+                    line_declare: Some(def.enum_name.pos.0),
+                    variants: vec![],
+                    size: 0,
+                    public: def.public
+                };
+                let mut largest_payload = 0;
+                for variant in &def.variants {
+                    let variant_name = &variant.node.name;
+                    if enum_attribute.get_variant(&variant_name.node).is_some() {
+                        self.error_pipe.report_error(
+                            CompilerError::new(
+                                ErrorType::SemanticError,
+                                format!("duplicate variant '{}' in enum '{}'",variant_name.node,def.enum_name.node).as_str(),
+                                variant_name.pos,
+                                variant_name.length,
+                            )
+                        );
+                        continue;
+                    }
+                    let mut payload = vec![];
+                    let mut payload_size = 0;
+                    for payload_type in &variant.node.payload {
+                        if payload_type.node == DataType::Struct(def.enum_name.node.clone()) {
+                            self.error_pipe.report_error(
+                                CompilerError::new(
+                                    ErrorType::SemanticError,
+                                    format!("recursive enum '{}' has infinite size",def.enum_name.node).as_str(),
+                                    payload_type.pos,
+                                    payload_type.length,
+                                )
+                            );
+                            continue;
+                        }
+                        if let Some(resolved_type) = self.resolve_type(payload_type, &symbol_table) {
+                            payload_size += symbol_table.size_of(&resolved_type);
+                            payload.push(resolved_type);
+                        }
+                    }
+                    if payload_size > largest_payload {
+                        largest_payload = payload_size;
+                    }
+                    enum_attribute.push_variant(variant_name.node.clone(), payload);
+                }
+                if enum_attribute.has_payload() {
+                    enum_attribute.size = DataType::I32.get_size_in_bytes() + largest_payload;
+                }
+                symbol_table.insert_enum(enum_attribute);
             },
-            Node::BooleanNot(exp) => {
-                let exp_type = match self.check_expression_type(&exp.exp, &symbol_table){
-                    Some(t) => t,
-                    None => return (),
-                };
-                if discriminant(&exp_type) != discriminant(&DataType::Boolean){
+            Node::Match(m) => {
+                self.check_match(m, node, &symbol_table, false);
+            },
+            Node::Destructure(d) => {
+                self.check_destructure(d, &symbol_table);
+            },
+            Node::Range(_) | Node::Cast(_) | Node::Try(_) | Node::Tuple(_) => {
+                self.check_expression_type(node, &symbol_table);
+            },
+            Node::ParserError(_) => (),
+        };
+    }
+
+    fn loop_attribute(label:&Option<AstNode<String>>,infinite:bool,as_value:bool) -> LoopAttribute {
+        LoopAttribute {
+            label: label.as_ref().map(|l| l.node.clone()),
+            infinite,
+            as_value,
+            value_type: None,
+            has_break: false,
+            exit_label: None,
+            latch_label: None,
+            result: None,
+            versions: vec![]
+        }
+    }
+
+    // a loop used as a value has the type of its break values, when the type is
+    // already known from where the loop is used the break values are checked against it
+    fn check_loop(&self,l:&InfiniteLoop,node:&AstNode<Node>,symbol_table:&Rc<SymbolTable>,as_value:bool,expected:Option<&DataType>) -> Option<DataType> {
+        let loop_block = symbol_table.insert_block_scope();
+        self.check_loop_label(&l.label, symbol_table);
+        let mut loop_attribute = Self::loop_attribute(&l.label, true, as_value);
+        loop_attribute.value_type = expected.cloned();
+        loop_block.insert_loop(loop_attribute);
+        self.analyze_body(&l.body, loop_block.clone());
+        let loop_attribute = loop_block.lookup_loop(None)?;
+        if !as_value {
+            return None;
+        }
+        if !loop_attribute.has_break {
+            self.error_pipe.report_error(
+                CompilerError::new(
+                    ErrorType::SemanticError,
+                    "a loop used as a value never breaks",
+                    node.pos,
+                    node.length,
+                )
+            );
+            return None;
+        }
+        loop_attribute.value_type
+    }
+
+    // both bounds of a range share one integer type, a literal bound takes the type
+    // of the other bound and a range of two literals is a range of i32
+    fn check_range(&self,range:&Range,expected:Option<&DataType>,symbol_table:&Rc<SymbolTable>) -> Option<DataType> {
+        let is_literal = |bound:&AstNode<Node>| matches!(bound.node,Node::Literal(LiteralValue::Int(_)));
+        let element_type = match expected {
+            Some(element_type) => element_type.clone(),
+            None => match (is_literal(&range.start),is_literal(&range.end)) {
+                (true,true) => DataType::I32,
+                (true,false) => self.check_expression_type(&range.end, symbol_table)?,
+                _ => self.check_expression_type(&range.start, symbol_table)?
+            }
+        };
+        if !Self::is_integer(&element_type) {
+            let bound = if is_literal(&range.start) { &range.end } else { &range.start };
+            self.error_pipe.report_error(
+                CompilerError::new(
+                    ErrorType::SemanticError,
+                    format!("range bounds have to be integers found '{}'",element_type.to_string()).as_str(),
+                    bound.pos,
+                    bound.length,
+                )
+            );
+            return None;
+        }
+        let mut valid = true;
+        for bound in [&range.start,&range.end] {
+            match self.check_value_type(bound, &element_type, symbol_table) {
+                Some(found) if !Self::type_matches(&element_type, &found) || !Self::is_integer(&found) => {
                     self.error_pipe.report_error(
                         CompilerError::new(
                             ErrorType::SemanticError,
-                            format!("cannot apply ! to '{}'",exp_type.to_string()).as_str(),
-                            node.pos,
-                            node.length,
+                            format!("expected '{}' found '{}'",element_type.to_string(),found.to_string()).as_str(),
+                            bound.pos,
+                            bound.length,
                         )
                     );
+                    valid = false;
+                },
+                Some(_) => (),
+                None => valid = false
+            }
+        }
+        if !valid {
+            return None;
+        }
+        Some(element_type)
+    }
+
+    // an integer literal, possibly negated
+    fn constant_int(node:&AstNode<Node>) -> Option<i64> {
+        match &node.node {
+            Node::Literal(LiteralValue::Int(i)) => Some(*i),
+            Node::Negation(exp) => match &exp.exp.node {
+                Node::Literal(LiteralValue::Int(i)) => Some(-*i),
+                _ => None
+            },
+            _ => None
+        }
+    }
+
+    // returns the type of the loop variable, the elements of a range count up towards
+    // the end by the step, or down from the end with .rev(), arrays, slices and strs
+    // are visited element by element
+    fn check_for_iterable(&self,f:&ForLoop,symbol_table:&Rc<SymbolTable>) -> Option<DataType> {
+        let (element_type,is_range) = match &f.range.node {
+            Node::Range(range) => {
+                let element_type = self.check_range(range, None, symbol_table)?;
+                let empty_end = if range.inclusive { 1 } else { 0 };
+                if let (Some(start),Some(end)) = (Self::constant_int(&range.start),Self::constant_int(&range.end)) {
+                    if start >= end + empty_end {
+                        self.error_pipe.report_error(
+                            CompilerError::new(
+                                ErrorType::Warning,
+                                format!("range {}{}{} is empty",start,if range.inclusive { "..=" } else { ".." },end).as_str(),
+                                f.range.pos,
+                                f.range.length,
+                            ).with_note("ranges count up, use (end..start).rev() to count down")
+                        );
+                    }
                 }
+                (element_type,true)
             },
-            Node::Conditional(con) => {
-                let if_block = symbol_table.insert_block_scope();
-                let condition_type = match self.check_expression_type(&con.if_block.0,&symbol_table){
-                    Some(dt) => dt,
-                    None => {
-                        return ();
+            _ => {
+                let iterable_type = self.check_expression_type(&f.range, symbol_table)?;
+                match iterable_type {
+                    DataType::Range(element) => (*element,true),
+                    DataType::Array(arr) => (*arr.data_type,false),
+                    DataType::Slice(element) => (*element,false),
+                    DataType::Str(_) => (DataType::U8,false),
+                    _ => {
+                        self.error_pipe.report_error(
+                            CompilerError::new(
+                                ErrorType::SemanticError,
+                                format!("cannot iterate over '{}'",iterable_type.to_string()).as_str(),
+                                f.range.pos,
+                                f.range.length,
+                            )
+                        );
+                        return None;
                     }
-                };
-                if condition_type != DataType::Boolean && !Self::type_castable(&condition_type,&DataType::Boolean){
+                }
+            }
+        };
+        if let (Some(reverse),false) = (&f.reverse,is_range) {
+            self.error_pipe.report_error(
+                CompilerError::new(
+                    ErrorType::SemanticError,
+                    "rev can only be used with a range",
+                    reverse.pos,
+                    reverse.length,
+                )
+            );
+        }
+        let step = match &f.step {
+            Some(step) => step,
+            None => return Some(element_type)
+        };
+        if !is_range {
+            self.error_pipe.report_error(
+                CompilerError::new(
+                    ErrorType::SemanticError,
+                    "a step can only be used with a range",
+                    step.pos,
+                    step.length,
+                )
+            );
+            return Some(element_type);
+        }
+        match self.check_value_type(step, &element_type, symbol_table) {
+            Some(found) if !Self::type_matches(&element_type, &found) || !Self::is_integer(&found) => {
+                self.error_pipe.report_error(
+                    CompilerError::new(
+                        ErrorType::SemanticError,
+                        format!("expected '{}' found '{}'",element_type.to_string(),found.to_string()).as_str(),
+                        step.pos,
+                        step.length,
+                    )
+                );
+            },
+            _ => ()
+        }
+        // the direction comes from .rev() so the step is only a distance
+        if let Some(i) = Self::constant_int(step).filter(|i| *i <= 0) {
+            self.error_pipe.report_error(
+                CompilerError::new(
+                    ErrorType::SemanticError,
+                    format!("step of a for loop has to be positive found {}",i).as_str(),
+                    step.pos,
+                    step.length,
+                )
+            );
+        }
+        Some(element_type)
+    }
+
+    // m::func(args) calls a top-level function of an imported module and m::NAME reads
+    // one of its constants, both have to be pub
+    fn check_module_call(&self,module:&ModuleAttribute,value:&EnumValue,symbol_table:&Rc<SymbolTable>) -> Option<DataType> {
+        let module_table = module.module_table.as_ref()?;
+        if let Some((func_att,_)) = module_table.lookup_func(value.variant.node.clone()) {
+            if !func_att.public {
+                self.report_private("function", &value.variant, module, func_att.line_declare);
+                return None;
+            }
+            // its instances would have to be checked inside the module, which is already done
+            if !func_att.type_parameters.is_empty() {
+                self.error_pipe.report_error(
+                    CompilerError::new(
+                        ErrorType::SemanticError,
+                        format!("generic function '{}' can only be called inside module '{}'",value.variant.node,module.alias).as_str(),
+                        value.variant.pos,
+                        value.variant.length,
+                    )
+                );
+                return None;
+            }
+            let parameters = func_att.parameter.iter()
+                .map(|(param_type,param_name)| (symbol_table.import_type(module, param_type),param_name.clone()))
+                .collect::<Vec<(DataType,String)>>();
+            let called = FuncCall { function_name: value.variant.clone(), arguments: value.arguments.clone() };
+            if !self.check_arguments(&called, &parameters, symbol_table) {
+                return None;
+            }
+            return Some(symbol_table.import_type(module, &func_att.return_type));
+        }
+        let constant = module_table.lookup_var(value.variant.node.clone()).filter(|(var,_)| var.constant && value.arguments.is_empty());
+        if let Some((var,_)) = constant {
+            if !var.public {
+                self.report_private("constant", &value.variant, module, var.line_declare);
+                return None;
+            }
+            return var.data_type.map(|t| symbol_table.import_type(module, &t));
+        }
+        self.error_pipe.report_error(
+            CompilerError::new(
+                ErrorType::SemanticError,
+                format!("module '{}' has no function or constant '{}'",module.alias,value.variant.node).as_str(),
+                value.variant.pos,
+                value.variant.length,
+            )
+        );
+        None
+    }
+
+    fn declare_trait(&self,def:&TraitDef,symbol_table:&Rc<SymbolTable>) {
+        if self.traits.borrow().contains_key(&def.trait_name.node) || Self::type_name_taken(&def.trait_name.node, symbol_table) {
+            self.error_pipe.report_error(
+                CompilerError::new(
+                    ErrorType::SemanticError,
+                    format!("redefinition of type '{}'",def.trait_name.node).as_str(),
+                    def.trait_name.pos,
+                    def.trait_name.length,
+                )
+            );
+            return;
+        }
+        self.traits.borrow_mut().insert(def.trait_name.node.clone(), def.clone());
+    }
+
+    // the methods become functions named Type::method, an impl of a trait has to
+    // define every method of the trait with the signature the trait gives it
+    fn check_impl(&self,block:&ImplBlock,symbol_table:&Rc<SymbolTable>) {
+        let target = match self.resolve_type(&block.target, symbol_table) {
+            Some(t) => t,
+            None => return
+        };
+        let local = match &target {
+            DataType::Struct(name) | DataType::Enum(name) => !name.contains("::"),
+            DataType::Array(_) | DataType::Slice(_) | DataType::Range(_) | DataType::Tuple(_) | DataType::Void => false,
+            _ => true
+        };
+        if !local {
+            self.error_pipe.report_error(
+                CompilerError::new(
+                    ErrorType::SemanticError,
+                    format!("cannot add methods to '{}', only to structs, enums and primitive types of this module",target.to_string()).as_str(),
+                    block.target.pos,
+                    block.target.length,
+                )
+            );
+            return;
+        }
+        for method in &block.methods {
+            let Node::Function(func) = &method.node else { continue };
+            let method_name = ImplBlock::method_name(&target, &func.function_name.node);
+            if symbol_table.lookup_func(method_name.clone()).is_some() {
+                self.error_pipe.report_error(
+                    CompilerError::new(
+                        ErrorType::SemanticError,
+                        format!("method '{}' is already defined for '{}'",func.function_name.node,target.to_string()).as_str(),
+                        func.function_name.pos,
+                        func.function_name.length,
+                    )
+                );
+                continue;
+            }
+            if let Some(type_parameter) = func.type_parameters.first() {
+                self.error_pipe.report_error(
+                    CompilerError::new(
+                        ErrorType::SemanticError,
+                        "a method cannot have type parameters",
+                        type_parameter.pos,
+                        type_parameter.length,
+                    )
+                );
+                continue;
+            }
+            let mut function = func.clone();
+            function.function_name.node = method_name;
+            self.analyze_node(&AstNode::new(Node::Function(function),method.pos,method.length), symbol_table.clone());
+        }
+        let trait_name = match &block.trait_name {
+            Some(name) => name,
+            None => return
+        };
+        let def = match self.traits.borrow().get(&trait_name.node) {
+            Some(def) => def.clone(),
+            None => {
+                self.error_pipe.report_error(
+                    CompilerError::new(
+                        ErrorType::SemanticError,
+                        format!("unknown trait '{}'",trait_name.node).as_str(),
+                        trait_name.pos,
+                        trait_name.length,
+                    )
+                );
+                return;
+            }
+        };
+        let self_binding = HashMap::from([(String::from("Self"),target.clone())]);
+        let mut missing = vec![];
+        for signature in &def.methods {
+            let Node::Function(signature) = &signature.node else { continue };
+            let implemented = block.methods.iter().find_map(|m| match &m.node {
+                Node::Function(func) if func.function_name.node == signature.function_name.node => Some(func),
+                _ => None
+            });
+            let func = match implemented {
+                Some(func) => func,
+                None => {
+                    missing.push(format!("'{}'",signature.function_name.node));
+                    continue;
+                }
+            };
+            let resolve = |t:&AstNode<DataType>| self.resolve_type(&AstNode::new(t.node.substitute(&self_binding),t.pos,t.length), symbol_table);
+            let expected_parameters = signature.parameters.iter()
+                .map(|p| p.node.var_type.as_ref().and_then(resolve).unwrap_or(DataType::Void))
+                .collect::<Vec<DataType>>();
+            let expected_return = resolve(&signature.return_type).unwrap_or(DataType::Void);
+            let (found,_) = match symbol_table.lookup_func(ImplBlock::method_name(&target, &func.function_name.node)) {
+                Some(found) => found,
+                None => continue
+            };
+            let found_parameters = found.parameter.iter().map(|(t,_)| t.clone()).collect::<Vec<DataType>>();
+            let takes_self = |parameters:&[AstNode<crate::arkparser::Var>]| parameters.first().is_some_and(|p| p.node.name.node == "self");
+            if found_parameters != expected_parameters || found.return_type != expected_return || takes_self(&func.parameters) != takes_self(&signature.parameters) {
+                self.error_pipe.report_error(
+                    CompilerError::new(
+                        ErrorType::SemanticError,
+                        format!(
+                            "method '{}' does not match trait '{}', expected '({}) : {}'",
+                            func.function_name.node,
+                            def.trait_name.node,
+                            expected_parameters.iter().map(|t| t.to_string()).collect::<Vec<String>>().join(", "),
+                            expected_return.to_string()
+                        ).as_str(),
+                        func.function_name.pos,
+                        func.function_name.length,
+                    )
+                );
+            }
+        }
+        if !missing.is_empty() {
+            self.error_pipe.report_error(
+                CompilerError::new(
+                    ErrorType::SemanticError,
+                    format!("missing method {} of trait '{}'",missing.join(", "),def.trait_name.node).as_str(),
+                    trait_name.pos,
+                    trait_name.length,
+                )
+            );
+        }
+        for method in &block.methods {
+            let Node::Function(func) = &method.node else { continue };
+            let declared = def.methods.iter().any(|m| matches!(&m.node,Node::Function(s) if s.function_name.node == func.function_name.node));
+            if !declared {
+                self.error_pipe.report_error(
+                    CompilerError::new(
+                        ErrorType::SemanticError,
+                        format!("method '{}' is not a member of trait '{}'",func.function_name.node,def.trait_name.node).as_str(),
+                        func.function_name.pos,
+                        func.function_name.length,
+                    )
+                );
+            }
+        }
+    }
+
+    fn function_type(func_att:&FuncAttribute) -> DataType {
+        DataType::Function(func_att.parameter.iter().map(|(t,_)| t.clone()).collect(),Box::new(func_att.return_type.clone()))
+    }
+
+    // the body of a closure is checked like a function declared where the closure is,
+    // reading a variable of the function around it captures the variable
+    fn check_closure(&self,func:&FuncDef,node:&AstNode<Node>,symbol_table:&Rc<SymbolTable>) -> Option<DataType> {
+        let name = symbol_table.closure_name(node.pos);
+        // an expression can be checked more than once, the closure is only declared the first time
+        if let Some((func_att,_)) = symbol_table.lookup_func(name.clone()) {
+            return Some(Self::function_type(&func_att));
+        }
+        let bindings = symbol_table.get_current_func_info().map(|f| f.type_bindings).unwrap_or_default();
+        let mut function = func.clone();
+        function.function_name.node = name.clone();
+        self.analyze_node(&AstNode::new(Node::Function(function),node.pos,node.length), symbol_table.clone());
+        symbol_table.func_set_type_bindings(name.clone(), bindings);
+        let (func_att,_) = symbol_table.lookup_func(name)?;
+        Some(Self::function_type(&func_att))
+    }
+
+    fn check_value_call(&self,callee:&AstNode<Node>,function_type:&DataType,arguments:&[AstNode<Node>],symbol_table:&Rc<SymbolTable>) -> Option<DataType> {
+        let (parameters,return_type) = match function_type {
+            DataType::Function(parameters,return_type) => (parameters,return_type),
+            _ => {
+                self.error_pipe.report_error(
+                    CompilerError::new(
+                        ErrorType::SemanticError,
+                        format!("cannot call a value of type '{}'",function_type.to_string()).as_str(),
+                        callee.pos,
+                        callee.length,
+                    )
+                );
+                return None;
+            }
+        };
+        let name = match &callee.node {
+            Node::Variable(v) => v.clone(),
+            _ => function_type.to_string()
+        };
+        let called = FuncCall { function_name: AstNode::new(name,callee.pos,callee.length), arguments: arguments.to_vec() };
+        let parameters = parameters.iter().map(|t| (t.clone(),String::new())).collect::<Vec<(DataType,String)>>();
+        if !self.check_arguments(&called, &parameters, symbol_table) {
+            return None;
+        }
+        Some(*return_type.clone())
+    }
+
+    // x.area() calls the method area of the type of x with x as the first argument
+    fn check_method_call(&self,call:&MethodCall,symbol_table:&Rc<SymbolTable>) -> Option<DataType> {
+        let caller = call.caller.as_ref()?;
+        let receiver = self.check_expression_type(caller, symbol_table)?;
+        let receiver = Self::inferred_type(caller, receiver);
+        let (function,module) = match &receiver {
+            DataType::Struct(name) | DataType::Enum(name) if name.contains("::") => {
+                let (alias,type_name) = name.split_once("::")?;
+                let module = symbol_table.lookup_module(alias.to_string())?;
+                let method_name = ImplBlock::method_name(&DataType::Struct(type_name.to_string()), &call.method_name.node);
+                let function = module.module_table.as_ref()?.lookup_func(method_name).map(|(f,_)| f);
+                (function,Some(module))
+            },
+            _ => (symbol_table.lookup_func(ImplBlock::method_name(&receiver, &call.method_name.node)).map(|(f,_)| f),None)
+        };
+        let function = match function {
+            Some(f) => f,
+            None => {
+                self.error_pipe.report_error(
+                    CompilerError::new(
+                        ErrorType::SemanticError,
+                        format!("no method '{}' on type '{}'",call.method_name.node,receiver.to_string()).as_str(),
+                        call.method_name.pos,
+                        call.method_name.length,
+                    )
+                );
+                return None;
+            }
+        };
+        if let Some(module) = &module {
+            if !function.public {
+                self.report_private("method", &call.method_name, module, function.line_declare);
+                return None;
+            }
+        }
+        if function.parameter.first().is_none_or(|(_,name)| name != "self") {
+            self.error_pipe.report_error(
+                CompilerError::new(
+                    ErrorType::SemanticError,
+                    format!("'{}' takes no self, call it as {}(..)",call.method_name.node,function.func_name).as_str(),
+                    call.method_name.pos,
+                    call.method_name.length,
+                )
+            );
+            return None;
+        }
+        let import = |t:&DataType| match &module {
+            Some(module) => symbol_table.import_type(module, t),
+            None => t.clone()
+        };
+        let parameters = function.parameter[1..].iter().map(|(t,name)| (import(t),name.clone())).collect::<Vec<(DataType,String)>>();
+        let called = FuncCall { function_name: call.method_name.clone(), arguments: call.arguments.clone() };
+        if !self.check_arguments(&called, &parameters, symbol_table) {
+            return None;
+        }
+        Some(import(&function.return_type))
+    }
+
+    fn declare_generic(&self,func:&FuncDef,symbol_table:&Rc<SymbolTable>) {
+        let mut names:Vec<String> = vec![];
+        for type_parameter in &func.type_parameters {
+            if names.contains(&type_parameter.node) {
+                self.error_pipe.report_error(
+                    CompilerError::new(
+                        ErrorType::SemanticError,
+                        format!("type parameter '{}' is declared twice",type_parameter.node).as_str(),
+                        type_parameter.pos,
+                        type_parameter.length,
+                    )
+                );
+                continue;
+            }
+            // type arguments are only inferred from the arguments of a call
+            let used = func.parameters.iter().filter_map(|p| p.node.var_type.as_ref())
+                .any(|t| Self::mentions_type_parameter(&t.node, std::slice::from_ref(&type_parameter.node)));
+            if !used {
+                self.error_pipe.report_error(
+                    CompilerError::new(
+                        ErrorType::SemanticError,
+                        format!("type parameter '{}' is not used by any parameter so it cannot be inferred",type_parameter.node).as_str(),
+                        type_parameter.pos,
+                        type_parameter.length,
+                    )
+                );
+            }
+            names.push(type_parameter.node.clone());
+        }
+        symbol_table.insert_func(func.function_name.node.clone());
+        symbol_table.update_func(func.function_name.node.clone(), None, Some(func.function_name.pos.0));
+        symbol_table.func_set_public(func.function_name.node.clone(), func.public);
+        symbol_table.func_set_type_parameters(func.function_name.node.clone(), names);
+        self.generic_functions.borrow_mut().insert(func.function_name.node.clone(), func.clone());
+    }
+
+    // infers the type arguments from the arguments, instantiates the function for them
+    // and checks the call against the instance
+    fn check_generic_call(&self,called:&FuncCall,symbol_table:&Rc<SymbolTable>) -> Option<DataType> {
+        let template = self.generic_functions.borrow().get(&called.function_name.node).cloned()?;
+        let names = template.type_parameters.iter().map(|t| t.node.clone()).collect::<Vec<String>>();
+        let parameter_types = template.parameters.iter()
+            .map(|p| p.node.var_type.as_ref().map(|t| t.node.clone()).unwrap_or(DataType::Void))
+            .collect::<Vec<DataType>>();
+        if called.arguments.len() != parameter_types.len() {
+            let parameters = parameter_types.iter().map(|t| (t.clone(),String::new())).collect::<Vec<(DataType,String)>>();
+            self.check_arguments(called, &parameters, symbol_table);
+            return None;
+        }
+        let mut bindings:HashMap<String,DataType> = HashMap::new();
+        // arguments with a type of their own decide first, a literal takes the type the others settled on
+        for literal_pass in [false,true] {
+            for (argument,parameter_type) in called.arguments.iter().zip(&parameter_types) {
+                if matches!(argument.node,Node::Literal(_)) != literal_pass {
+                    continue;
+                }
+                let bound = parameter_type.substitute(&bindings);
+                if !Self::mentions_type_parameter(&bound, &names) {
+                    continue;
+                }
+                let found = self.check_expression_type(argument, symbol_table)?;
+                Self::unify(&bound, &Self::inferred_type(argument, found), &names, &mut bindings);
+            }
+        }
+        let mut type_arguments = vec![];
+        for name in &names {
+            match bindings.get(name) {
+                Some(t) => type_arguments.push(t.to_string()),
+                None => {
                     self.error_pipe.report_error(
                         CompilerError::new(
                             ErrorType::SemanticError,
-                            format!("expected boolean expression in if statement found '{}'",condition_type.to_string()).as_str(),
-                            con.if_block.0.pos,
-                            con.if_block.0.length,
+                            format!("cannot infer type parameter '{}' of '{}'",name,called.function_name.node).as_str(),
+                            called.function_name.pos,
+                            called.function_name.length,
                         )
                     );
+                    return None;
                 }
-                self.analyze_body(&con.if_block.1, if_block);
-                for (condition,body) in &con.elif_block{
-                    let elif_block = symbol_table.insert_block_scope();
-                    let condition_type = match self.check_expression_type(&condition,&symbol_table){
-                        Some(dt) => dt,
-                        None => {
-                            return ();
-                        }
-                    };
-                    if condition_type != DataType::Boolean && !Self::type_castable(&condition_type,&DataType::Boolean){
+            }
+        }
+        let instance_name = format!("{}${}",called.function_name.node,type_arguments.join("$"));
+        self.instantiate(&template, &instance_name, bindings, called.function_name.pos);
+        symbol_table.insert_generic_call(called.function_name.pos, instance_name.clone());
+        let (instance,_) = self.symbol_table.lookup_func(instance_name)?;
+        if !self.check_arguments(called, &instance.parameter, symbol_table) {
+            return None;
+        }
+        Some(instance.return_type)
+    }
+
+    fn mentions_type_parameter(data_type:&DataType,names:&[String]) -> bool {
+        let probe = names.iter().map(|name| (name.clone(),DataType::Void)).collect::<HashMap<String,DataType>>();
+        data_type.substitute(&probe) != *data_type
+    }
+
+    // binds the type parameters inside expected to the matching part of found,
+    // a parameter that is already bound keeps its type and the argument check reports the mismatch
+    fn unify(expected:&DataType,found:&DataType,names:&[String],bindings:&mut HashMap<String,DataType>) {
+        match (expected,found) {
+            (DataType::Struct(name),_) if names.contains(name)
+                && !bindings.contains_key(name) => {
+                    bindings.insert(name.clone(), found.clone());
+                },
+            (DataType::Array(expected),DataType::Array(found)) => Self::unify(&expected.data_type, &found.data_type, names, bindings),
+            (DataType::Slice(expected),DataType::Array(found)) => Self::unify(expected, &found.data_type, names, bindings),
+            (DataType::Slice(expected),DataType::Slice(found)) | (DataType::Range(expected),DataType::Range(found)) => Self::unify(expected, found, names, bindings),
+            (DataType::Option(expected),DataType::Option(found)) => Self::unify(expected, found, names, bindings),
+            (DataType::Result(expected_value,expected_error),DataType::Result(found_value,found_error)) => {
+                Self::unify(expected_value, found_value, names, bindings);
+                Self::unify(expected_error, found_error, names, bindings);
+            },
+            (DataType::Tuple(expected),DataType::Tuple(found)) if expected.len() == found.len() => {
+                for (e,f) in expected.iter().zip(found) {
+                    Self::unify(e, f, names, bindings);
+                }
+            },
+            _ => ()
+        }
+    }
+
+    // the instance is an ordinary function named after its type arguments, checked
+    // with the type parameters standing for them, once no matter how often it is called
+    fn instantiate(&self,template:&FuncDef,instance_name:&str,bindings:HashMap<String,DataType>,call_pos:(u32,u32)) {
+        if self.symbol_table.lookup_func(instance_name.to_string()).is_some() {
+            return;
+        }
+        let mut function = template.clone();
+        function.function_name.node = instance_name.to_string();
+        function.type_parameters = vec![];
+        function.public = false;
+        let node = AstNode::new(Node::Function(function),template.function_name.pos,0);
+        let outer = mem::replace(&mut *self.type_bindings.borrow_mut(), bindings.clone());
+        let reported = self.error_pipe.error_generated.borrow().len();
+        self.analyze_node(&node, self.symbol_table.clone());
+        *self.type_bindings.borrow_mut() = outer;
+        // errors in the body only show up for some type arguments, point at the call that chose them
+        let type_arguments = template.type_parameters.iter()
+            .map(|t| format!("{} = {}",t.node,bindings.get(&t.node).map(|b| b.to_string()).unwrap_or_default()))
+            .collect::<Vec<String>>();
+        for error in self.error_pipe.error_generated.borrow_mut()[reported..].iter_mut() {
+            if error.note.is_none() {
+                error.note = Some(format!("in '{}' with {}, called at line {}",template.function_name.node,type_arguments.join(", "),call_pos.0));
+            }
+        }
+        self.symbol_table.func_set_type_bindings(instance_name.to_string(), bindings);
+        self.instances.borrow_mut().push(node);
+    }
+
+    pub fn take_instances(&self) -> Vec<AstNode<Node>> {
+        mem::take(&mut *self.instances.borrow_mut())
+    }
+
+    // m::Point names a pub struct or enum of an imported module
+    fn resolve_module_type(&self,alias:&str,type_name:&str,data_type:&AstNode<DataType>,symbol_table:&Rc<SymbolTable>) -> Option<DataType> {
+        let module = match symbol_table.lookup_module(alias.to_string()) {
+            Some(module) => module,
+            None => {
+                self.error_pipe.report_error(
+                    CompilerError::new(
+                        ErrorType::SemanticError,
+                        format!("unknown module '{}'",alias).as_str(),
+                        data_type.pos,
+                        data_type.length,
+                    )
+                );
+                return None;
+            }
+        };
+        let module_table = module.module_table.as_ref()?;
+        let (public,line_declare,found) = match (module_table.lookup_struct(type_name.to_string()),module_table.lookup_enum(type_name.to_string())) {
+            (Some(s),_) => (s.public,s.line_declare,DataType::Struct(type_name.to_string())),
+            (None,Some(e)) => (e.public,e.line_declare,DataType::Enum(type_name.to_string())),
+            (None,None) => {
+                self.error_pipe.report_error(
+                    CompilerError::new(
+                        ErrorType::SemanticError,
+                        format!("module '{}' has no type '{}'",alias,type_name).as_str(),
+                        data_type.pos,
+                        data_type.length,
+                    )
+                );
+                return None;
+            }
+        };
+        if !public {
+            let name = AstNode::new(type_name.to_string(),data_type.pos,data_type.length);
+            self.report_private("type", &name, &module, line_declare);
+            return None;
+        }
+        Some(symbol_table.import_type(&module, &found))
+    }
+
+    // m::Shape in a value, a pattern or a struct literal, the type is imported like one
+    // written as m::Shape in a declaration so both are the same type
+    fn import_path(&self,path:&AstNode<String>,symbol_table:&Rc<SymbolTable>) -> Option<DataType> {
+        let (alias,type_name) = path.node.split_once("::")?;
+        self.resolve_module_type(alias, type_name, &AstNode::new(DataType::Struct(path.node.clone()),path.pos,path.length), symbol_table)
+    }
+
+    // where an enum of an imported module is expected it can be named without the module,
+    // Shape::Empty for a m::Shape, unless this module has a type of that name
+    fn imported_enum_name(name:&str,expected:&DataType,symbol_table:&Rc<SymbolTable>) -> Option<String> {
+        let DataType::Enum(qualified) = expected else { return None };
+        let (_,enum_name) = qualified.split_once("::")?;
+        let local = symbol_table.lookup_enum(name.to_string()).is_some() || symbol_table.lookup_struct(name.to_string()).is_some();
+        (enum_name == name && !local).then(|| qualified.clone())
+    }
+
+    fn report_private(&self,kind:&str,name:&AstNode<String>,module:&ModuleAttribute,line_declare:Option<u32>) {
+        self.error_pipe.report_error(
+            CompilerError::new(
+                ErrorType::SemanticError,
+                format!("{} '{}' of module '{}' is private",kind,name.node,module.alias).as_str(),
+                name.pos,
+                name.length,
+            ).with_note(format!("'{}' is declared here: {}:{}",name.node,module.module_path,line_declare.unwrap_or(1)).as_str())
+        );
+    }
+
+    fn check_destructure(&self,d:&Destructure,symbol_table:&Rc<SymbolTable>) {
+        let value_type = match &d.var_type {
+            Some(var_type) => self.resolve_type(var_type, symbol_table).and_then(|var_type| {
+                match self.check_value_type(&d.value, &var_type, symbol_table) {
+                    Some(found) if !Self::type_matches(&var_type, &found) => {
                         self.error_pipe.report_error(
                             CompilerError::new(
                                 ErrorType::SemanticError,
-                                format!("expected boolean expression in if statement found '{}'",condition_type.to_string()).as_str(),
-                                con.if_block.0.pos,
-                                con.if_block.0.length,
+                                format!("expected '{}' found '{}'",var_type.to_string(),found.to_string()).as_str(),
+                                d.value.pos,
+                                d.value.length,
                             )
                         );
-                    }
-                    self.analyze_body(body, elif_block);
-                }
-                match &con.else_block {
-                    Some(body) => {
-                        let else_block = symbol_table.insert_block_scope();
-                        self.analyze_body(body, else_block);
+                        None
                     },
-                    None => return ()
+                    Some(_) => Some(var_type),
+                    None => None
                 }
+            }),
+            None => self.check_expression_type(&d.value, symbol_table).map(|found| Self::inferred_type(&d.value, found))
+        };
+        self.declare_tuple_names(&d.names, value_type.as_ref(), &d.value, symbol_table);
+    }
+
+    // declares every name but _ with the type of the element at its position, the names
+    // are left without a type when the value is invalid so their uses are not reported again
+    fn declare_tuple_names(&self,names:&[AstNode<String>],tuple_type:Option<&DataType>,value:&AstNode<Node>,symbol_table:&Rc<SymbolTable>) {
+        for name in names.iter().filter(|name| name.node != "_") {
+            symbol_table.insert_var(name.node.clone());
+        }
+        let element_types = match tuple_type {
+            Some(DataType::Tuple(elements)) if elements.len() == names.len() => elements,
+            None => return,
+            Some(tuple_type) => {
+                self.error_pipe.report_error(
+                    CompilerError::new(
+                        ErrorType::SemanticError,
+                        format!("expected a tuple of {} elements found '{}'",names.len(),tuple_type.to_string()).as_str(),
+                        value.pos,
+                        value.length,
+                    )
+                );
+                return;
+            }
+        };
+        for (name,element_type) in names.iter().zip(element_types) {
+            if name.node == "_" {
+                continue;
+            }
+            symbol_table.update_var(
+                name.node.clone(),
+                Some(element_type.clone()),
+                Some(symbol_table.size_of(element_type)),
+                Some(0),
+                Some(name.pos.0)
+            );
+        }
+    }
+
+    // the generated name of a closure means nothing to the reader
+    fn function_description(func:&FuncAttribute) -> String {
+        if func.func_name.contains("$closure") {
+            String::from("closure")
+        }
+        else {
+            format!("function '{}'",func.func_name)
+        }
+    }
+
+    // x? hands a None or an Err straight to the caller, so the function has to return
+    // the same kind of value, a Result with the same error type
+    fn check_try(&self,operand:&AstNode<Node>,node:&AstNode<Node>,symbol_table:&Rc<SymbolTable>) -> Option<DataType> {
+        let operand_type = self.check_expression_type(operand, symbol_table)?;
+        let current_func = match symbol_table.get_current_func_info() {
+            Some(func_info) => func_info,
+            None => {
+                self.error_pipe.report_error(
+                    CompilerError::new(
+                        ErrorType::SemanticError,
+                        "the '?' operator can only be used inside a function",
+                        node.pos,
+                        node.length,
+                    )
+                );
+                return None;
+            }
+        };
+        let (value_type,accepted,expected) = match (&operand_type,&current_func.return_type) {
+            (DataType::Option(value),DataType::Option(_)) => (value.as_ref().clone(),true,String::new()),
+            (DataType::Option(value),_) => (value.as_ref().clone(),false,String::from("an 'Option'")),
+            (DataType::Result(value,error),DataType::Result(_,returned_error)) => {
+                (value.as_ref().clone(),returned_error == error,format!("a 'Result<_, {}>'",error.to_string()))
             },
-            Node::Tuple(_) => todo!(),
-            Node::Range(_) => todo!(),
-            Node::ParserError(_) => (),
+            (DataType::Result(value,error),_) => (value.as_ref().clone(),false,format!("a 'Result<_, {}>'",error.to_string())),
+            _ => {
+                self.error_pipe.report_error(
+                    CompilerError::new(
+                        ErrorType::SemanticError,
+                        format!("the '?' operator applies to an 'Option' or a 'Result' found '{}'",operand_type.to_string()).as_str(),
+                        operand.pos,
+                        operand.length,
+                    )
+                );
+                return None;
+            }
+        };
+        if !accepted {
+            self.error_pipe.report_error(
+                CompilerError::new(
+                    ErrorType::SemanticError,
+                    format!(
+                        "the '?' operator on '{}' needs {} to return {} found '{}'",
+                        operand_type.to_string(),
+                        Self::function_description(&current_func),
+                        expected,
+                        current_func.return_type.to_string()
+                    ).as_str(),
+                    node.pos,
+                    node.length,
+                )
+            );
+            return None;
+        }
+        Some(value_type)
+    }
+
+    fn check_cast(&self,cast:&CastExp,node:&AstNode<Node>,symbol_table:&Rc<SymbolTable>) -> Option<DataType> {
+        let original_type = self.check_expression_type(&cast.value, symbol_table)?;
+        let target_type = self.resolve_type(&cast.target, symbol_table)?;
+        let lossy = match Self::explicit_cast(&original_type, &target_type) {
+            Some(lossy) => lossy,
+            None => {
+                self.error_pipe.report_error(
+                    CompilerError::new(
+                        ErrorType::SemanticError,
+                        format!("cannot cast '{}' to '{}'",original_type.to_string(),target_type.to_string()).as_str(),
+                        node.pos,
+                        node.length,
+                    )
+                );
+                return None;
+            }
+        };
+        // a literal only loses information when it does not fit the target type
+        let lossy = match (&cast.value.node,&target_type) {
+            (Node::Literal(LiteralValue::Int(i)),target) if Self::is_integer(target) => {
+                let (start,end) = target.int_domain();
+                (*i as i128) < start || (*i as i128) >= end
+            },
+            (Node::Literal(LiteralValue::Int(i)),DataType::F32) => i.unsigned_abs() > 1 << 24,
+            (Node::Literal(LiteralValue::Int(i)),DataType::F64) => i.unsigned_abs() > 1 << 53,
+            _ => lossy
         };
+        // a literal that does not fit is an i32, or an i64 when it does not fit an i32 either
+        let original_type = match &cast.value.node {
+            Node::Literal(LiteralValue::Int(i)) if Self::is_integer(&original_type) => if i32::try_from(*i).is_ok() { DataType::I32 } else { DataType::I64 },
+            _ => original_type
+        };
+        if lossy {
+            self.error_pipe.report_error(
+                CompilerError::new(
+                    ErrorType::Warning,
+                    format!("cast from '{}' to '{}' may lose information",original_type.to_string(),target_type.to_string()).as_str(),
+                    node.pos,
+                    node.length,
+                )
+            );
+        }
+        Some(target_type)
+    }
+
+    fn check_loop_label(&self,label:&Option<AstNode<String>>,symbol_table:&Rc<SymbolTable>){
+        if let Some(label) = label {
+            if symbol_table.lookup_loop(Some(label.node.clone())).is_some() {
+                self.error_pipe.report_error(
+                    CompilerError::new(
+                        ErrorType::SemanticError,
+                        format!("label '{}' shadows the label of an enclosing loop",label.node).as_str(),
+                        label.pos,
+                        label.length,
+                    )
+                );
+            }
+        }
     }
 
     fn analyze_body(&self,bd:&Body,scope_symbol_table:Rc<SymbolTable>){
@@ -1,5 +1,5 @@
 use std::{cell::RefCell, collections::HashMap, mem::discriminant, rc::{Rc, Weak}};
-use crate::tokenizer::DataType;
+use crate::tokenizer::{Array, DataType};
 
 
 #[derive(Debug,Clone,PartialEq, Eq)]
@@ -15,7 +15,18 @@ pub struct VarAttribute {
     pub dimension:Option<u32>,
     pub line_declare:Option<u32>,
     pub line_ref:Vec<u32>,
-    pub current_version:u32
+    pub current_version:u32,
+    pub constant:bool,
+    // the literal of `let x = <int literal>` until the first use decides the integer type
+    pub inferred_literal:Option<i64>,
+    // a pub const can be read by the modules importing this one
+    pub public:bool,
+    // the copy a closure holds of a variable of the function around it
+    pub captured:bool,
+    // declared by let or as a parameter, the only variables a reference can be taken to
+    pub addressable:bool,
+    // a reference is taken to it, so it lives in memory instead of a versioned name
+    pub in_memory:bool
 }
 
 impl VarAttribute {
@@ -39,6 +50,37 @@ impl VarAttribute {
         self.line_ref.push(line_ref);
         self
     }
+    pub fn set_constant(&mut self,constant:bool) -> &mut Self{
+        self.constant = constant;
+        self
+    }
+    pub fn set_inferred_literal(&mut self,inferred_literal:Option<i64>) -> &mut Self{
+        self.inferred_literal = inferred_literal;
+        self
+    }
+    pub fn set_public(&mut self,public:bool) -> &mut Self{
+        self.public = public;
+        self
+    }
+    pub fn set_captured(&mut self,captured:bool) -> &mut Self{
+        self.captured = captured;
+        self
+    }
+    pub fn set_addressable(&mut self,addressable:bool) -> &mut Self{
+        self.addressable = addressable;
+        self
+    }
+    pub fn set_in_memory(&mut self,in_memory:bool) -> &mut Self{
+        self.in_memory = in_memory;
+        self
+    }
+}
+
+// extern functions are called the way C expects and keep their name in the ir
+#[derive(Debug,Clone,Copy,PartialEq)]
+pub enum CallingConvention {
+    Ark,
+    C
 }
 
 #[derive(Debug,Clone)]
@@ -48,6 +90,14 @@ pub struct FuncAttribute {
     pub line_used:Vec<u32>,
     pub parameter:Vec<(DataType,String)>,
     pub return_type:DataType,
+    pub public:bool,
+    pub calling_convention:CallingConvention,
+    // empty unless the function is generic, its instances are functions of their own
+    pub type_parameters:Vec<String>,
+    // the types the type parameters stand for in an instance of a generic function
+    pub type_bindings:HashMap<String,DataType>,
+    // variables of the enclosing functions a closure reads, copied into it when it is created
+    pub captures:Vec<(DataType,String)>,
     pub func_table:Rc<SymbolTable>
 }
 
@@ -68,6 +118,112 @@ impl FuncAttribute {
         self.parameter.push(param);
         self
     }
+    pub fn set_public(&mut self,public:bool) -> &mut Self{
+        self.public = public;
+        self
+    }
+    pub fn set_calling_convention(&mut self,calling_convention:CallingConvention) -> &mut Self{
+        self.calling_convention = calling_convention;
+        self
+    }
+    pub fn set_type_parameters(&mut self,type_parameters:Vec<String>) -> &mut Self{
+        self.type_parameters = type_parameters;
+        self
+    }
+    pub fn set_type_bindings(&mut self,type_bindings:HashMap<String,DataType>) -> &mut Self{
+        self.type_bindings = type_bindings;
+        self
+    }
+    pub fn push_capture(&mut self,capture:(DataType,String)) -> &mut Self{
+        self.captures.push(capture);
+        self
+    }
+}
+
+#[derive(Debug,Clone)]
+pub struct StructAttribute {
+    pub struct_name:String,
+    pub line_declare:Option<u32>,
+    // (type, name, offset in bytes) in declaration order
+    pub fields:Vec<(DataType,String,u32)>,
+    pub size:u32,
+    pub public:bool
+}
+
+impl StructAttribute {
+    pub fn push_field(&mut self,field_type:DataType,field_name:String,field_size:u32) -> &mut Self{
+        self.fields.push((field_type,field_name,self.size));
+        self.size += field_size;
+        self
+    }
+    pub fn get_field(&self,field_name:&str) -> Option<(DataType,u32)> {
+        for (field_type,name,offset) in &self.fields {
+            if name == field_name {
+                return Some((field_type.clone(),*offset));
+            }
+        }
+        None
+    }
+}
+
+#[derive(Debug,Clone)]
+pub struct EnumAttribute {
+    pub enum_name:String,
+    pub line_declare:Option<u32>,
+    // (name, payload types) in declaration order, the index is the tag of the variant
+    pub variants:Vec<(String,Vec<DataType>)>,
+    // size of the tag plus the largest payload, 0 when no variant has a payload
+    pub size:u32,
+    pub public:bool
+}
+
+impl EnumAttribute {
+    pub fn push_variant(&mut self,variant_name:String,payload:Vec<DataType>) -> &mut Self{
+        self.variants.push((variant_name,payload));
+        self
+    }
+    pub fn get_variant(&self,variant_name:&str) -> Option<(u32,Vec<DataType>)> {
+        for (tag,(name,payload)) in self.variants.iter().enumerate() {
+            if name == variant_name {
+                return Some((tag as u32,payload.clone()));
+            }
+        }
+        None
+    }
+    // enums without payload are plain integer tags, the others are a tag followed by the payload
+    pub fn has_payload(&self) -> bool {
+        self.variants.iter().any(|(_,payload)| !payload.is_empty())
+    }
+}
+
+#[derive(Debug,Clone)]
+pub struct ModuleAttribute {
+    pub alias:String,
+    // prefix of the functions of the module in the ir
+    pub module_name:String,
+    // the file as it was imported, private items are reported with their place in it
+    pub module_path:String,
+    // global scope of the analyzed module, None when the file could not be loaded
+    pub module_table:Option<Rc<SymbolTable>>
+}
+
+#[derive(Debug,Clone)]
+pub struct LoopAttribute {
+    pub label:Option<String>,
+    // `loop`, it can only be left through break
+    pub infinite:bool,
+    // a `loop` used as a value takes the type of its break values
+    pub as_value:bool,
+    pub value_type:Option<DataType>,
+    pub has_break:bool,
+    // filled in by the ir generator, break jumps to the exit label and continue to the latch label
+    pub exit_label:Option<String>,
+    pub latch_label:Option<String>,
+    // variable holding the value of a `loop` used as a value
+    pub result:Option<String>,
+    // versions of the variables when the loop was entered, both jumps copy the
+    // variables back to these versions first
+    pub versions:Vec<(String,u32)>
 }
 
 #[derive(Debug)]
@@ -75,7 +231,18 @@ pub struct SymbolTable{
     pub scope:Scope,
     var_table:RefCell<HashMap<String,VarAttribute>>,
     func_table:RefCell<HashMap<String,FuncAttribute>>,
+    struct_table:RefCell<HashMap<String,StructAttribute>>,
+    enum_table:RefCell<HashMap<String,EnumAttribute>>,
+    module_table:RefCell<HashMap<String,ModuleAttribute>>,
+    // position of a call -> instance of the generic function it calls
+    generic_calls:RefCell<HashMap<(u32,u32),String>>,
+    // position of an Option or Result variant -> its type, the variant alone does not tell
+    variant_types:RefCell<HashMap<(u32,u32),DataType>>,
     inner_scope:RefCell<Vec<Rc<SymbolTable>>>,
+    // next block scope handed out by next_block_scope
+    block_cursor:RefCell<usize>,
+    // set when the scope is the body of a loop
+    loop_info:RefCell<Option<LoopAttribute>>,
     higher_scope:RefCell<Weak<SymbolTable>>
 }
 
@@ -85,7 +252,14 @@ impl SymbolTable{
             scope,
             var_table: RefCell::new(HashMap::new()),
             func_table:RefCell::new(HashMap::new()),
+            struct_table:RefCell::new(HashMap::new()),
+            enum_table: RefCell::new(HashMap::new()),
+            module_table: RefCell::new(HashMap::new()),
+            generic_calls: RefCell::new(HashMap::new()),
+            variant_types: RefCell::new(HashMap::new()),
             inner_scope: RefCell::new(vec![]),
+            block_cursor: RefCell::new(0),
+            loop_info: RefCell::new(None),
             higher_scope: RefCell::new(Weak::new())
         }
     }
@@ -102,12 +276,31 @@ impl SymbolTable{
                 dimension: None,
                 line_declare: None,
                 line_ref: vec![],
-                current_version:0
+                current_version:0,
+                constant:false,
+                inferred_literal:None,
+                public:false,
+                captured:false,
+                addressable:false,
+                in_memory:false
             }
         );
     }
+    // the scope a variable was declared in, variables of enclosing scopes are
+    // versioned where they live so every block sees the same version
+    fn owner_of(self:&Rc<Self>,identifier:&String) -> Rc<SymbolTable> {
+        let mut iter = Rc::clone(self);
+        loop {
+            if iter.var_table.borrow().contains_key(identifier) || iter.scope == Scope::Global {
+                return iter;
+            }
+            let temp_iter = iter.higher_scope.borrow().upgrade().unwrap();
+            iter = temp_iter;
+        }
+    }
     pub fn get_var_version(self:& Rc<Self>,identifier:String) -> u32{
-        let mut table = self.var_table.borrow_mut();
+        let owner = self.owner_of(&identifier);
+        let mut table = owner.var_table.borrow_mut();
         let var_attribute = match table.get_mut(&identifier){
             Some(at) => at,
             None => panic!("Try to update unintialize entry in symbol table"),
@@ -115,7 +308,8 @@ impl SymbolTable{
         return var_attribute.current_version;
     }
     pub fn consume_var_version(self:& Rc<Self>,identifier:String) -> u32{
-        let mut table = self.var_table.borrow_mut();
+        let owner = self.owner_of(&identifier);
+        let mut table = owner.var_table.borrow_mut();
         let var_attribute = match table.get_mut(&identifier){
             Some(at) => at,
             None => panic!("Try to update unintialize entry in symbol table"),
@@ -123,6 +317,33 @@ impl SymbolTable{
         var_attribute.current_version += 1;
         return var_attribute.current_version;
     }
+    pub fn set_var_version(self:& Rc<Self>,identifier:String,version:u32){
+        let owner = self.owner_of(&identifier);
+        let mut table = owner.var_table.borrow_mut();
+        if let Some(var_attribute) = table.get_mut(&identifier) {
+            var_attribute.current_version = version;
+        }
+    }
+    // current version of every variable visible from this scope, a shadowed
+    // variable is left out
+    pub fn var_versions(self:& Rc<Self>) -> Vec<(String,u32)> {
+        let mut versions:Vec<(String,u32)> = vec![];
+        let mut iter = Rc::clone(self);
+        loop {
+            for (name,var_attribute) in iter.var_table.borrow().iter() {
+                if !versions.iter().any(|(seen,_)| seen == name) {
+                    versions.push((name.clone(),var_attribute.current_version));
+                }
+            }
+            if iter.scope == Scope::Global {
+                break;
+            }
+            let temp_iter = iter.higher_scope.borrow().upgrade().unwrap();
+            iter = temp_iter;
+        }
+        versions.sort();
+        versions
+    }
     pub fn update_var(
         self:& Rc<Self>,
         identifier:String,
@@ -161,7 +382,8 @@ impl SymbolTable{
 
         let mut iter = Rc::clone(&self);
         loop {
-            if iter.scope == scope{
+            // nested blocks can share an index, the owner also has to declare the variable
+            if iter.scope == scope && iter.var_table.borrow().contains_key(&identifier){
                 
                 break;
             }
@@ -178,10 +400,52 @@ impl SymbolTable{
         self.var_table.borrow_mut().get_mut(&identifier).unwrap().push_line_ref(line_ref);
     }
 
+    pub fn var_set_constant(self:& Rc<Self>,identifier:String,constant:bool){
+        self.var_table.borrow_mut().get_mut(&identifier).unwrap().set_constant(constant);
+    }
+
+    pub fn var_set_public(self:& Rc<Self>,identifier:String,public:bool){
+        self.var_table.borrow_mut().get_mut(&identifier).unwrap().set_public(public);
+    }
+
+    pub fn var_set_captured(self:& Rc<Self>,identifier:String,captured:bool){
+        self.var_table.borrow_mut().get_mut(&identifier).unwrap().set_captured(captured);
+    }
+
+    pub fn var_set_addressable(self:& Rc<Self>,identifier:String,addressable:bool){
+        self.var_table.borrow_mut().get_mut(&identifier).unwrap().set_addressable(addressable);
+    }
+
+    pub fn var_set_in_memory_at(self:& Rc<Self>,scope:Scope,identifier:String,in_memory:bool){
+        let mut iter = Rc::clone(self);
+        while !(iter.scope == scope && iter.var_table.borrow().contains_key(&identifier)) {
+            let temp_iter = iter.higher_scope.borrow().upgrade().unwrap();
+            iter = temp_iter;
+        }
+        iter.var_table.borrow_mut().get_mut(&identifier).unwrap().set_in_memory(in_memory);
+    }
+
+    pub fn var_set_inferred_literal(self:& Rc<Self>,identifier:String,inferred_literal:Option<i64>){
+        self.var_table.borrow_mut().get_mut(&identifier).unwrap().set_inferred_literal(inferred_literal);
+    }
+
+    // gives a variable declared from an integer literal its final type
+    pub fn settle_var_type_at(self:& Rc<Self>,scope:Scope,identifier:String,data_type:DataType){
+        let mut iter = Rc::clone(self);
+        while !(iter.scope == scope && iter.var_table.borrow().contains_key(&identifier)) {
+            let temp_iter = iter.higher_scope.borrow().upgrade().unwrap();
+            iter = temp_iter;
+        }
+        let size = iter.size_of(&data_type);
+        iter.update_var(identifier.clone(), Some(data_type), Some(size), None, None);
+        iter.var_set_inferred_literal(identifier, None);
+    }
+
     pub fn var_push_line_ref_at(self:& Rc<Self>,scope:Scope,identifier:String,line_ref:u32){
         let mut iter = Rc::clone(&self);
         loop {
-            if iter.scope == scope{
+            // nested blocks can share an index, the owner also has to declare the variable
+            if iter.scope == scope && iter.var_table.borrow().contains_key(&identifier){
                 
                 break;
             }
@@ -205,12 +469,24 @@ impl SymbolTable{
             line_declare: None,
             parameter: vec![],
             line_used: vec![],
+            public: false,
+            calling_convention: CallingConvention::Ark,
+            type_parameters: vec![],
+            type_bindings: HashMap::new(),
+            captures: vec![],
             func_table: Rc::new(
                 SymbolTable {
                     scope: Scope::Function(identifier.clone()),
                     var_table: RefCell::new(HashMap::new()),
                     func_table: RefCell::new(HashMap::new()),
+                    struct_table: RefCell::new(HashMap::new()),
+                    enum_table: RefCell::new(HashMap::new()),
+                    module_table: RefCell::new(HashMap::new()),
+                    generic_calls: RefCell::new(HashMap::new()),
+                    variant_types: RefCell::new(HashMap::new()),
                     inner_scope: RefCell::new(vec![]),
+                    block_cursor: RefCell::new(0),
+                    loop_info: RefCell::new(None),
                     higher_scope: RefCell::new(Rc::downgrade(&self))
                 }
             )
@@ -308,6 +584,97 @@ impl SymbolTable{
         self.func_table.borrow_mut().get_mut(&identifier).unwrap().push_parameter(data);
     }
 
+    pub fn func_set_public(self:& Rc<Self>,identifier:String,public:bool){
+        self.func_table.borrow_mut().get_mut(&identifier).unwrap().set_public(public);
+    }
+
+    pub fn func_set_calling_convention(self:& Rc<Self>,identifier:String,calling_convention:CallingConvention){
+        self.func_table.borrow_mut().get_mut(&identifier).unwrap().set_calling_convention(calling_convention);
+    }
+
+    pub fn func_set_type_parameters(self:& Rc<Self>,identifier:String,type_parameters:Vec<String>){
+        self.func_table.borrow_mut().get_mut(&identifier).unwrap().set_type_parameters(type_parameters);
+    }
+
+    pub fn func_set_type_bindings(self:& Rc<Self>,identifier:String,type_bindings:HashMap<String,DataType>){
+        self.func_table.borrow_mut().get_mut(&identifier).unwrap().set_type_bindings(type_bindings);
+    }
+
+    // a closure is named after the function it is written in and its position,
+    // so the analyzer and the ir generator find the same function
+    pub fn closure_name(self:& Rc<Self>,pos:(u32,u32)) -> String {
+        let enclosing = self.get_current_func_info().map(|f| f.func_name).unwrap_or_default();
+        format!("{}$closure{}_{}",enclosing,pos.0,pos.1)
+    }
+
+    // a variable of an enclosing function used inside a closure becomes a variable of the
+    // closure holding a copy, every closure between the use and the variable copies it
+    pub fn capture_var(self:& Rc<Self>,identifier:&str) {
+        let mut closures = vec![];
+        let mut iter = Rc::clone(self);
+        while !iter.var_table.borrow().contains_key(identifier) {
+            if iter.scope == Scope::Global {
+                return;
+            }
+            let temp_iter = iter.higher_scope.borrow().upgrade().unwrap();
+            // named functions are only declared at the global scope
+            if let (Scope::Function(name),false) = (&iter.scope,temp_iter.scope == Scope::Global) {
+                closures.push((Rc::clone(&iter),Rc::clone(&temp_iter),name.clone()));
+            }
+            iter = temp_iter;
+        }
+        if iter.scope == Scope::Global {
+            return;
+        }
+        let var_attribute = iter.var_table.borrow().get(identifier).unwrap().clone();
+        let data_type = var_attribute.data_type.clone().unwrap_or(DataType::Void);
+        for (closure,parent,name) in closures.iter().rev() {
+            closure.insert_var(identifier.to_string());
+            closure.update_var(identifier.to_string(), var_attribute.data_type.clone(), var_attribute.size, var_attribute.dimension, var_attribute.line_declare);
+            closure.var_set_captured(identifier.to_string(), true);
+            closure.var_set_addressable(identifier.to_string(), var_attribute.addressable);
+            if let Some(func_attribute) = parent.func_table.borrow_mut().get_mut(name) {
+                func_attribute.push_capture((data_type.clone(),identifier.to_string()));
+            }
+        }
+    }
+
+    // the function, or the global scope, the table belongs to
+    fn function_scope(self:& Rc<Self>) -> Rc<SymbolTable> {
+        let mut iter = Rc::clone(self);
+        while !matches!(iter.scope,Scope::Function(_) | Scope::Global) {
+            let temp_iter = iter.higher_scope.borrow().upgrade().unwrap();
+            iter = temp_iter;
+        }
+        iter
+    }
+
+    // a call of a generic function is keyed by where its name is, the instance it uses
+    // differs between the instances of the function making the call
+    pub fn insert_generic_call(self:& Rc<Self>,pos:(u32,u32),instance_name:String){
+        self.function_scope().generic_calls.borrow_mut().insert(pos, instance_name);
+    }
+
+    pub fn lookup_generic_call(self:& Rc<Self>,pos:(u32,u32)) -> Option<String> {
+        self.function_scope().generic_calls.borrow().get(&pos).cloned()
+    }
+
+    pub fn insert_variant_type(self:& Rc<Self>,pos:(u32,u32),data_type:DataType){
+        self.function_scope().variant_types.borrow_mut().insert(pos, data_type);
+    }
+
+    pub fn lookup_variant_type(self:& Rc<Self>,pos:(u32,u32)) -> Option<DataType> {
+        self.function_scope().variant_types.borrow().get(&pos).cloned()
+    }
+
+    // a type written in the source, with the type parameters of the instance being generated replaced
+    pub fn source_type(self:& Rc<Self>,data_type:&DataType) -> DataType {
+        match self.get_current_func_info() {
+            Some(func) => data_type.substitute(&func.type_bindings),
+            None => data_type.clone()
+        }
+    }
+
     pub fn func_push_line_ref_at(self:& Rc<Self>,scope:Scope,identifier:String,line_ref:u32){
         let mut iter = Rc::clone(&self);
         loop {
@@ -357,6 +724,37 @@ impl SymbolTable{
         return None;
     }
 
+    // the scope a visible variable is declared in, two variables of the same name
+    // are the same variable only when their scopes are the same table
+    pub fn var_owner(self:&Rc<Self>,identifier:&str) -> Option<Rc<SymbolTable>> {
+        let mut iter = Rc::clone(self);
+        loop {
+            if iter.var_table.borrow().contains_key(identifier) {
+                return Some(iter);
+            }
+            if iter.scope == Scope::Global {
+                return None;
+            }
+            let temp_iter = iter.higher_scope.borrow().upgrade().unwrap();
+            iter = temp_iter;
+        }
+    }
+
+    // whether inner is this scope or a scope nested in it
+    pub fn encloses(self:&Rc<Self>,inner:&Rc<SymbolTable>) -> bool {
+        let mut iter = Rc::clone(inner);
+        loop {
+            if Rc::ptr_eq(&iter,self) {
+                return true;
+            }
+            let temp_iter = match iter.higher_scope.borrow().upgrade() {
+                Some(higher) => higher,
+                None => return false
+            };
+            iter = temp_iter;
+        }
+    }
+
     pub fn lookup_func(self:&Rc<Self>,identifier:String) -> Option<(FuncAttribute,Scope)> {
         let mut iter = Rc::clone(&self);
         loop {
@@ -374,13 +772,238 @@ impl SymbolTable{
         return None;
     }
 
+    pub fn insert_struct(self:&Rc<Self>,struct_attribute:StructAttribute){
+        self.struct_table.borrow_mut().insert(
+            struct_attribute.struct_name.clone(),
+            struct_attribute
+        );
+    }
+
+    pub fn lookup_struct(self:&Rc<Self>,identifier:String) -> Option<StructAttribute> {
+        let mut iter = Rc::clone(self);
+        loop {
+            if iter.struct_table.borrow().contains_key(&identifier) {
+                return Some(iter.struct_table.borrow().get(&identifier).unwrap().clone());
+            }
+            if iter.scope == Scope::Global {
+                break;
+            }
+            let temp_iter = iter.higher_scope.borrow().upgrade().unwrap();
+            iter = temp_iter;
+        }
+        None
+    }
+
+    pub fn insert_enum(self:&Rc<Self>,enum_attribute:EnumAttribute){
+        self.enum_table.borrow_mut().insert(
+            enum_attribute.enum_name.clone(),
+            enum_attribute
+        );
+    }
+
+    pub fn lookup_enum(self:&Rc<Self>,identifier:String) -> Option<EnumAttribute> {
+        let mut iter = Rc::clone(self);
+        loop {
+            if iter.enum_table.borrow().contains_key(&identifier) {
+                return Some(iter.enum_table.borrow().get(&identifier).unwrap().clone());
+            }
+            if iter.scope == Scope::Global {
+                break;
+            }
+            let temp_iter = iter.higher_scope.borrow().upgrade().unwrap();
+            iter = temp_iter;
+        }
+        None
+    }
+
+    pub fn insert_module(self:&Rc<Self>,module_attribute:ModuleAttribute){
+        self.module_table.borrow_mut().insert(
+            module_attribute.alias.clone(),
+            module_attribute
+        );
+    }
+
+    pub fn lookup_module(self:&Rc<Self>,alias:String) -> Option<ModuleAttribute> {
+        let mut iter = Rc::clone(self);
+        loop {
+            if iter.module_table.borrow().contains_key(&alias) {
+                return Some(iter.module_table.borrow().get(&alias).unwrap().clone());
+            }
+            if iter.scope == Scope::Global {
+                break;
+            }
+            let temp_iter = iter.higher_scope.borrow().upgrade().unwrap();
+            iter = temp_iter;
+        }
+        None
+    }
+
+    fn global_scope(self:&Rc<Self>) -> Rc<SymbolTable> {
+        let mut iter = Rc::clone(self);
+        while iter.scope != Scope::Global {
+            let temp_iter = iter.higher_scope.borrow().upgrade().unwrap();
+            iter = temp_iter;
+        }
+        iter
+    }
+
+    // a type of an imported module as seen from this module, the structs and enums it
+    // names are copied into the global scope as alias::name so their layout is known here
+    pub fn import_type(self:&Rc<Self>,module:&ModuleAttribute,data_type:&DataType) -> DataType {
+        let module_table = match &module.module_table {
+            Some(t) => t,
+            None => return data_type.clone()
+        };
+        match data_type {
+            DataType::Struct(name) => {
+                let qualified = format!("{}::{}",module.alias,name);
+                if self.lookup_struct(qualified.clone()).is_none() {
+                    if let Some(mut struct_attribute) = module_table.lookup_struct(name.clone()) {
+                        struct_attribute.struct_name = qualified.clone();
+                        struct_attribute.fields = struct_attribute.fields.iter()
+                            .map(|(field_type,field_name,offset)| (self.import_type(module, field_type),field_name.clone(),*offset))
+                            .collect();
+                        self.global_scope().insert_struct(struct_attribute);
+                    }
+                }
+                DataType::Struct(qualified)
+            },
+            DataType::Enum(name) => {
+                let qualified = format!("{}::{}",module.alias,name);
+                if self.lookup_enum(qualified.clone()).is_none() {
+                    if let Some(mut enum_attribute) = module_table.lookup_enum(name.clone()) {
+                        enum_attribute.enum_name = qualified.clone();
+                        enum_attribute.variants = enum_attribute.variants.iter()
+                            .map(|(variant,payload)| (variant.clone(),payload.iter().map(|t| self.import_type(module, t)).collect()))
+                            .collect();
+                        self.global_scope().insert_enum(enum_attribute);
+                    }
+                }
+                DataType::Enum(qualified)
+            },
+            DataType::Array(arr) => DataType::Array(Array { length: arr.length, data_type: Box::new(self.import_type(module, &arr.data_type)) }),
+            DataType::Slice(element) => DataType::Slice(Box::new(self.import_type(module, element))),
+            DataType::Tuple(elements) => DataType::Tuple(elements.iter().map(|t| self.import_type(module, t)).collect()),
+            DataType::Reference(target,mutable) => DataType::Reference(Box::new(self.import_type(module, target)),*mutable),
+            DataType::Option(value) => DataType::Option(Box::new(self.import_type(module, value))),
+            DataType::Result(value,error) => DataType::Result(Box::new(self.import_type(module, value)),Box::new(self.import_type(module, error))),
+            _ => data_type.clone()
+        }
+    }
+
+    // same as DataType::get_size_in_bytes but also knows the layout of declared structs
+    pub fn size_of(self:&Rc<Self>,data_type:&DataType) -> u32 {
+        match data_type {
+            DataType::Struct(name) => match self.lookup_struct(name.clone()) {
+                Some(struct_attribute) => struct_attribute.size,
+                None => 0
+            },
+            DataType::Enum(name) => match self.lookup_enum(name.clone()) {
+                Some(enum_attribute) if enum_attribute.has_payload() => enum_attribute.size,
+                _ => DataType::I32.get_size_in_bytes()
+            },
+            DataType::Array(arr) => arr.length * self.size_of(&arr.data_type),
+            DataType::Tuple(elements) => elements.iter().map(|e| self.size_of(e)).sum(),
+            DataType::Option(_) | DataType::Result(_,_) => self.enum_of(data_type).map_or(0,|e| e.size),
+            _ => data_type.get_size_in_bytes()
+        }
+    }
+
+    // the variants of an enum type, Option<T> is laid out like enum Option { None, Some(T) }
+    // and Result<T, E> like enum Result { Ok(T), Err(E) }
+    pub fn enum_of(self:&Rc<Self>,data_type:&DataType) -> Option<EnumAttribute> {
+        let (enum_name,variants) = match data_type {
+            DataType::Enum(name) => return self.lookup_enum(name.clone()),
+            DataType::Option(value) => ("Option",vec![(String::from("None"),vec![]),(String::from("Some"),vec![(**value).clone()])]),
+            DataType::Result(value,error) => ("Result",vec![(String::from("Ok"),vec![(**value).clone()]),(String::from("Err"),vec![(**error).clone()])]),
+            _ => return None
+        };
+        let largest_payload = variants.iter().map(|(_,payload)| payload.iter().map(|t| self.size_of(t)).sum::<u32>()).max().unwrap_or(0);
+        Some(EnumAttribute {
+            enum_name: enum_name.to_string(),
+            line_declare: None,
+            variants,
+            size: DataType::I32.get_size_in_bytes() + largest_payload,
+            public: true
+        })
+    }
+
+    pub fn insert_loop(self:&Rc<Self>,loop_attribute:LoopAttribute){
+        *self.loop_info.borrow_mut() = Some(loop_attribute);
+    }
+
+    pub fn update_loop(self:&Rc<Self>,exit_label:String,latch_label:String,result:Option<String>,versions:Vec<(String,u32)>){
+        if let Some(loop_attribute) = self.loop_info.borrow_mut().as_mut() {
+            loop_attribute.exit_label = Some(exit_label);
+            loop_attribute.latch_label = Some(latch_label);
+            loop_attribute.result = result;
+            loop_attribute.versions = versions;
+        }
+    }
+
+    // marks the loop a break leaves, the first break value decides the type of the loop
+    pub fn update_loop_break(self:&Rc<Self>,label:Option<String>,value_type:Option<DataType>){
+        if let Some(scope) = self.loop_scope(label) {
+            if let Some(loop_attribute) = scope.loop_info.borrow_mut().as_mut() {
+                loop_attribute.has_break = true;
+                if loop_attribute.value_type.is_none() {
+                    loop_attribute.value_type = value_type;
+                }
+            }
+        }
+    }
+
+    // innermost enclosing loop, or the enclosing loop with the given label,
+    // loops outside of the current function are not visible
+    fn loop_scope(self:&Rc<Self>,label:Option<String>) -> Option<Rc<SymbolTable>> {
+        let mut iter = Rc::clone(self);
+        loop {
+            if let Some(loop_attribute) = iter.loop_info.borrow().as_ref() {
+                if label.is_none() || loop_attribute.label == label {
+                    return Some(Rc::clone(&iter));
+                }
+            }
+            if discriminant(&iter.scope) != discriminant(&Scope::Block(0)) {
+                break;
+            }
+            let temp_iter = iter.higher_scope.borrow().upgrade().unwrap();
+            iter = temp_iter;
+        }
+        None
+    }
+
+    pub fn lookup_loop(self:&Rc<Self>,label:Option<String>) -> Option<LoopAttribute> {
+        let scope = self.loop_scope(label)?;
+        let loop_attribute = scope.loop_info.borrow().clone();
+        loop_attribute
+    }
+
+    // walks the block scopes in the order insert_block_scope created them, so a
+    // later pass can revisit the scopes the semantic analyzer filled
+    pub fn next_block_scope(self:&Rc<Self>) -> Rc<SymbolTable>{
+        let index = *self.block_cursor.borrow();
+        *self.block_cursor.borrow_mut() += 1;
+        let block = self.inner_scope.borrow().get(index).cloned();
+        match block {
+            Some(block) => block,
+            None => self.insert_block_scope()
+        }
+    }
+
     pub fn insert_block_scope(self:&Rc<Self>) -> Rc<SymbolTable>{
         let child = Rc::new(
             SymbolTable {
                 scope: Scope::Block(self.inner_scope.borrow().len()),
                 var_table: RefCell::new(HashMap::new()),
                 func_table: RefCell::new(HashMap::new()),
+                struct_table: RefCell::new(HashMap::new()),
+                enum_table: RefCell::new(HashMap::new()),
+                module_table: RefCell::new(HashMap::new()),
+                generic_calls: RefCell::new(HashMap::new()),
+                variant_types: RefCell::new(HashMap::new()),
                 inner_scope: RefCell::new(vec![]),
+                block_cursor: RefCell::new(0),
+                loop_info: RefCell::new(None),
                 higher_scope: RefCell::new(Rc::downgrade(&self))
             }
         );
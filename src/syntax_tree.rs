@@ -0,0 +1,165 @@
+use crate::tokenizer::Token;
+
+#[derive(Debug,Clone,Copy,PartialEq)]
+pub enum SyntaxKind {
+    Root,
+    Function,
+    Closure,
+    TypeParameterList,
+    TypeArgumentList,
+    ParameterList,
+    Parameter,
+    ReturnType,
+    Block,
+    Import,
+    VarDeclaration,
+    DataType,
+    Return,
+    Conditional,
+    ForLoop,
+    WhileLoop,
+    Loop,
+    Break,
+    Continue,
+    Assignment,
+    BinaryExpression,
+    UnaryExpression,
+    FunctionCall,
+    MethodCall,
+    ClosureCall,
+    FieldAccess,
+    Index,
+    Cast,
+    Try,
+    ArrayLiteral,
+    Struct,
+    Trait,
+    Impl,
+    Field,
+    StructLiteral,
+    FieldInit,
+    Enum,
+    Variant,
+    EnumValue,
+    Match,
+    MatchArm,
+    Pattern,
+    ArgumentList,
+    Paren,
+    Range,
+    Error,
+}
+
+#[derive(Debug,Clone)]
+pub enum SyntaxElement {
+    Node(SyntaxNode),
+    Token(Token),
+}
+
+#[derive(Debug,Clone)]
+pub struct SyntaxNode {
+    pub kind:SyntaxKind,
+    pub children:Vec<SyntaxElement>,
+}
+
+impl SyntaxNode {
+    pub fn new(kind:SyntaxKind) -> SyntaxNode {
+        SyntaxNode { kind, children:vec![] }
+    }
+
+    pub fn tokens(&self) -> Vec<&Token> {
+        let mut tokens = vec![];
+        for child in &self.children {
+            match child {
+                SyntaxElement::Node(node) => tokens.extend(node.tokens()),
+                SyntaxElement::Token(token) => tokens.push(token),
+            }
+        }
+        tokens
+    }
+
+    // concatenating every token with its leading trivia gives back the source text
+    pub fn text(&self) -> String {
+        let mut text = String::new();
+        for token in self.tokens() {
+            for trivia in &token.leading_trivia {
+                text += trivia.text.as_str();
+            }
+            text += token.text.as_str();
+        }
+        text
+    }
+
+    fn fmt_tree(&self,depth:usize,out:&mut String){
+        out.push_str(&format!("{}{:?}\n","  ".repeat(depth),self.kind));
+        for child in &self.children {
+            match child {
+                SyntaxElement::Node(node) => node.fmt_tree(depth + 1, out),
+                SyntaxElement::Token(token) => {
+                    for trivia in &token.leading_trivia {
+                        out.push_str(&format!("{}{:?} {:?}\n","  ".repeat(depth + 1),trivia.kind,trivia.text));
+                    }
+                    out.push_str(&format!("{}{:?} {:?}\n","  ".repeat(depth + 1),token.token,token.text));
+                }
+            }
+        }
+    }
+
+    pub fn to_tree_string(&self) -> String {
+        let mut out = String::new();
+        self.fmt_tree(0, &mut out);
+        out
+    }
+}
+
+pub struct TreeBuilder {
+    stack:Vec<SyntaxNode>,
+}
+
+impl TreeBuilder {
+    pub fn new() -> TreeBuilder {
+        TreeBuilder { stack:vec![SyntaxNode::new(SyntaxKind::Root)] }
+    }
+
+    pub fn depth(&self) -> usize {
+        self.stack.len()
+    }
+
+    pub fn checkpoint(&self) -> usize {
+        self.stack.last().unwrap().children.len()
+    }
+
+    pub fn start_node(&mut self,kind:SyntaxKind){
+        self.stack.push(SyntaxNode::new(kind));
+    }
+
+    // wraps every element pushed since the checkpoint into a new node, used for
+    // left recursive constructs where the node kind is only known after its first child
+    pub fn start_node_at(&mut self,checkpoint:usize,kind:SyntaxKind){
+        let parent = self.stack.last_mut().unwrap();
+        let children = parent.children.split_off(checkpoint.min(parent.children.len()));
+        self.stack.push(SyntaxNode { kind, children });
+    }
+
+    pub fn set_kind(&mut self,kind:SyntaxKind){
+        self.stack.last_mut().unwrap().kind = kind;
+    }
+
+    pub fn token(&mut self,token:Token){
+        self.stack.last_mut().unwrap().children.push(SyntaxElement::Token(token));
+    }
+
+    pub fn finish_node(&mut self){
+        if self.stack.len() > 1 {
+            let node = self.stack.pop().unwrap();
+            self.stack.last_mut().unwrap().children.push(SyntaxElement::Node(node));
+        }
+    }
+
+    pub fn finish(mut self) -> SyntaxNode {
+        while self.stack.len() > 1 {
+            self.finish_node();
+        }
+        self.stack.pop().unwrap()
+    }
+}
@@ -1,6 +1,6 @@
-use std::{mem::discriminant, rc::Rc, vec};
+use std::{collections::HashMap, vec};
 use enum_map::Enum;
-use fancy_regex::Regex;
+use regex::Regex;
 
 use crate::ErrorPipeline;
 
@@ -18,7 +18,18 @@ pub enum KeyWords{
     ELSE,
     ELSEIF,
     RETURN,
-    LET
+    LET,
+    STRUCT,
+    ENUM,
+    MATCH,
+    BREAK,
+    CONTINUE,
+    LOOP,
+    PUB,
+    EXTERN,
+    TRAIT,
+    IMPL,
+    MUT
 }
 
 #[derive(Debug,PartialEq,Clone)]
@@ -37,7 +48,22 @@ pub enum DataType{
     Char,
     Boolean,
     Str(u32),
-    Array(Array)
+    Array(Array),
+    // pointer and length of a run of elements, str is a slice of bytes
+    Slice(Box<DataType>),
+    // a range of integers, i32[..]
+    Range(Box<DataType>),
+    // (i32, bool), laid out like a struct with the elements as fields
+    Tuple(Vec<DataType>),
+    Struct(String),
+    Enum(String),
+    // func(i32) : bool, a pointer to a record of the function and the values it captured
+    Function(Vec<DataType>,Box<DataType>),
+    // &i32 or &mut i32, the address of a value
+    Reference(Box<DataType>,bool),
+    // Option<T> and Result<T, E> are enums built into the language, None/Some and Ok/Err
+    Option(Box<DataType>),
+    Result(Box<DataType>,Box<DataType>)
 }
 
 #[derive(Debug,PartialEq,Clone)]
@@ -63,7 +89,23 @@ impl DataType{
             DataType::Char => "char".to_string(),
             DataType::Boolean => "bool".to_string(),
             DataType::Str(_) => "str".to_string(),
-            DataType::Array(arr) => format!("array [{}]",arr.data_type.to_string())
+            DataType::Array(arr) => format!("{}[{}]",arr.data_type.to_string(),arr.length),
+            DataType::Slice(element) => format!("{}[]",element.to_string()),
+            DataType::Range(element) => format!("{}[..]",element.to_string()),
+            DataType::Tuple(elements) => format!("({})",elements.iter().map(|e| e.to_string()).collect::<Vec<String>>().join(", ")),
+            DataType::Struct(name) => name.clone(),
+            DataType::Enum(name) => name.clone(),
+            DataType::Function(parameters,return_type) => {
+                let parameters = parameters.iter().map(|p| p.to_string()).collect::<Vec<String>>().join(", ");
+                match **return_type {
+                    DataType::Void => format!("func({})",parameters),
+                    _ => format!("func({}) : {}",parameters,return_type.to_string())
+                }
+            },
+            DataType::Reference(target,true) => format!("&mut {}",target.to_string()),
+            DataType::Reference(target,false) => format!("&{}",target.to_string()),
+            DataType::Option(value) => format!("Option<{}>",value.to_string()),
+            DataType::Result(value,error) => format!("Result<{}, {}>",value.to_string(),error.to_string())
         }
     }
     // pub fn to_c_type_string(&self) -> String{
@@ -84,6 +126,20 @@ impl DataType{
     //         DataType::Array(_) => "[]",
     //     }.to_string()
     // }
+    // the values of an integer type as [start,end)
+    pub fn int_domain(&self) -> (i128,i128) {
+        match self {
+            DataType::I8 => (i8::MIN.into(),i8::MAX as i128 + 1),
+            DataType::I16 => (i16::MIN.into(),i16::MAX as i128 + 1),
+            DataType::I32 => (i32::MIN.into(),i32::MAX as i128 + 1),
+            DataType::U8 => (0,u8::MAX as i128 + 1),
+            DataType::U16 => (0,u16::MAX as i128 + 1),
+            DataType::U32 => (0,u32::MAX as i128 + 1),
+            DataType::U64 => (0,u64::MAX as i128 + 1),
+            _ => (i64::MIN.into(),i64::MAX as i128 + 1),
+        }
+    }
+
     pub fn get_size_in_bytes(&self) -> u32{
         match self{
             DataType::Void => 0,
@@ -99,13 +155,35 @@ impl DataType{
             DataType::F64 => 16,
             DataType::Char => 4,
             DataType::Boolean => 1,
-            DataType::Str(length) => 4,
+            DataType::Str(_) | DataType::Slice(_) => 2 * DataType::U64.get_size_in_bytes(),
+            // start and end followed by whether the end is included
+            DataType::Range(element) => 2 * element.get_size_in_bytes() + DataType::Boolean.get_size_in_bytes(),
+            DataType::Tuple(elements) => elements.iter().map(|e| e.get_size_in_bytes()).sum(),
             DataType::Array(arr) => {
                 arr.length * arr.data_type.get_size_in_bytes()
                 // while discriminant(arr) == discriminant(DataType::Array(Array { length: 0, data_type: Void })){
 
                 // }
-            }
+            },
+            // layout of a struct lives in the symbol table, see SymbolTable::size_of
+            DataType::Struct(_) => 0,
+            DataType::Enum(_) | DataType::Option(_) | DataType::Result(_,_) => 0,
+            DataType::Function(_,_) | DataType::Reference(_,_) => DataType::U64.get_size_in_bytes()
+        }
+    }
+    // a type parameter is parsed as a struct name, replaces it with the type it stands for
+    pub fn substitute(&self,bindings:&HashMap<String,DataType>) -> DataType{
+        match self{
+            DataType::Struct(name) => bindings.get(name).cloned().unwrap_or(self.clone()),
+            DataType::Array(arr) => DataType::Array(Array { length: arr.length, data_type: Box::new(arr.data_type.substitute(bindings)) }),
+            DataType::Slice(element) => DataType::Slice(Box::new(element.substitute(bindings))),
+            DataType::Range(element) => DataType::Range(Box::new(element.substitute(bindings))),
+            DataType::Tuple(elements) => DataType::Tuple(elements.iter().map(|e| e.substitute(bindings)).collect()),
+            DataType::Function(parameters,return_type) => DataType::Function(parameters.iter().map(|p| p.substitute(bindings)).collect(),Box::new(return_type.substitute(bindings))),
+            DataType::Reference(target,mutable) => DataType::Reference(Box::new(target.substitute(bindings)),*mutable),
+            DataType::Option(value) => DataType::Option(Box::new(value.substitute(bindings))),
+            DataType::Result(value,error) => DataType::Result(Box::new(value.substitute(bindings)),Box::new(error.substitute(bindings))),
+            _ => self.clone()
         }
     }
 }
@@ -140,13 +218,27 @@ pub enum TokenType{
     StringLiteral(String),
     CharLiteral(char),
     BooleanLiteral(bool),
+    Label(String),
     AdditionOperator,
     SubtractionOperator,
     MultiplicationOperator,
     DivisionOperator,
     ModuloOperator,
     AssignmentOperator,
+    AdditionAssign,
+    SubtractionAssign,
+    MultiplicationAssign,
+    DivisionAssign,
+    ModuloAssign,
+    BitwiseAndAssign,
+    BitwiseOrAssign,
+    BitwiseXorAssign,
+    ShiftLeftAssign,
+    ShiftRightAssign,
+    Increment,
+    Decrement,
     Equal,
+    NotEqual,
     Less,
     LessEqual,
     More,
@@ -154,6 +246,12 @@ pub enum TokenType{
     And,
     Or,
     Not,
+    BitwiseAnd,
+    BitwiseOr,
+    BitwiseXor,
+    BitwiseNot,
+    ShiftLeft,
+    ShiftRight,
     LeftParen,
     RightParen,
     LeftBrace,
@@ -162,19 +260,59 @@ pub enum TokenType{
     RightBracket,
     Comma,
     Range,
+    RangeInclusive,
     Dot,
     SingleQuote,
     DoubleQuote,
     ScopeResolution,
+    FatArrow,
+    // x? returns early with the None or Err in x
+    QuestionMark,
     Colon,
     SemiColon,
     EOF
 }
 
+impl TokenType {
+    // binary operator applied by a compound assignment, `a += b` is `a = a + b`
+    pub fn compound_operator(&self) -> Option<TokenType> {
+        match self {
+            TokenType::AdditionAssign | TokenType::Increment => Some(TokenType::AdditionOperator),
+            TokenType::SubtractionAssign | TokenType::Decrement => Some(TokenType::SubtractionOperator),
+            TokenType::MultiplicationAssign => Some(TokenType::MultiplicationOperator),
+            TokenType::DivisionAssign => Some(TokenType::DivisionOperator),
+            TokenType::ModuloAssign => Some(TokenType::ModuloOperator),
+            TokenType::BitwiseAndAssign => Some(TokenType::BitwiseAnd),
+            TokenType::BitwiseOrAssign => Some(TokenType::BitwiseOr),
+            TokenType::BitwiseXorAssign => Some(TokenType::BitwiseXor),
+            TokenType::ShiftLeftAssign => Some(TokenType::ShiftLeft),
+            TokenType::ShiftRightAssign => Some(TokenType::ShiftRight),
+            _ => None
+        }
+    }
+}
+
 #[derive(Debug,PartialEq,Clone)]
 pub struct Token {
     pub token:TokenType,
     pub length:u32,
+    pub pos:(u32,u32),
+    pub text:String,
+    pub leading_trivia:Vec<Trivia>
+}
+
+#[derive(Debug,PartialEq,Clone,Copy)]
+pub enum TriviaKind {
+    Whitespace,
+    LineComment,
+    BlockComment,
+    Skipped
+}
+
+#[derive(Debug,PartialEq,Clone)]
+pub struct Trivia {
+    pub kind:TriviaKind,
+    pub text:String,
     pub pos:(u32,u32)
 }
 
@@ -208,83 +346,153 @@ pub struct Token {
 //     AssignmentOperator,
 // }
 
+// what has to come right after a rule's match for the rule to apply, kept out of
+// the pattern so every rule is a plain anchored regex and finding a token never
+// looks further than the token itself
+#[derive(Clone)]
+enum Follow {
+    Any,
+    Is(Regex),
+    IsNot(Regex)
+}
+
+impl Follow {
+    fn allows(&self,rest:&str) -> bool {
+        match self {
+            Follow::Any => true,
+            Follow::Is(follow) => follow.is_match(rest),
+            Follow::IsNot(follow) => !follow.is_match(rest)
+        }
+    }
+}
+
+fn rule(pattern:&str,follow:Follow,token:TokenType) -> (Regex,Follow,TokenType) {
+    (Regex::new(pattern).unwrap(),follow,token)
+}
+
 pub struct Tokenizer<'a,'b>{
     pos:(u32,u32),
     cursor:u32,
     source:&'a str,
-    rules:Vec<(Regex,TokenType)>,
-    error_pipe:&'b ErrorPipeline
+    rules:Vec<(Regex,Follow,TokenType)>,
+    error_pipe:&'b ErrorPipeline,
+    emit_trivia:bool,
+    pending_trivia:Vec<Trivia>
 }
 
 impl<'a,'b> Tokenizer<'a,'b>{
     pub fn new(source_code:&'a str,error_pipe:&'b ErrorPipeline) -> Tokenizer<'a,'b> {
+        let space = Follow::Is(Regex::new(r"\A\s").unwrap());
+        let space_or_paren = Follow::Is(Regex::new(r"\A[\s(]").unwrap());
+        let space_or_brace = Follow::Is(Regex::new(r"\A[\s{]").unwrap());
+        let space_or_semicolon = Follow::Is(Regex::new(r"\A[\s;]").unwrap());
+        let space_paren_or_semicolon = Follow::Is(Regex::new(r"\A[\s(;]").unwrap());
+        let non_word = Follow::Is(Regex::new(r"\A\W").unwrap());
         return Tokenizer{
             pos:(1,1),
             cursor:0,
             source:source_code,
             error_pipe,
+            emit_trivia:false,
+            pending_trivia:vec![],
             rules:vec![
-                (Regex::new(r"\Afunc\s+").unwrap(),TokenType::Keyword(KeyWords::FUNC)),
-                (Regex::new(r"\Aimport\s+").unwrap(),TokenType::Keyword(KeyWords::IMPORT)),
-                (Regex::new(r"\Aas\s+").unwrap(),TokenType::Keyword(KeyWords::AS)),
-                (Regex::new(r"\Aconst\s+").unwrap(),TokenType::Keyword(KeyWords::CONST)),
-                (Regex::new(r"\Areturn(?=\s+|\(|;)").unwrap(),TokenType::Keyword(KeyWords::RETURN)),
-                (Regex::new(r"\Alet(?=\s+|\()").unwrap(),TokenType::Keyword(KeyWords::LET)),
-                (Regex::new(r"\Awhile(?=\s+|\()").unwrap(),TokenType::Keyword(KeyWords::WHILE)),
-                (Regex::new(r"\Afor(?=\s+|\()").unwrap(),TokenType::Keyword(KeyWords::FOR)),
-                (Regex::new(r"\Aif(?=\s+|\()").unwrap(),TokenType::Keyword(KeyWords::IF)),
-                (Regex::new(r"\Aelse(?=\s+|\{)").unwrap(),TokenType::Keyword(KeyWords::ELSE)),
-                (Regex::new(r"\Aelse\s+if(?=\s+|\()").unwrap(),TokenType::Keyword(KeyWords::ELSEIF)),
-                (Regex::new(r"\Ain(?=\s+|\()").unwrap(),TokenType::Keyword(KeyWords::IN)),
-                (Regex::new(r#"\A".*""#).unwrap(),TokenType::StringLiteral(String::new())),
-                (Regex::new(r#"\A'*'"#).unwrap(),TokenType::CharLiteral('a')),
-                (Regex::new(r"\Ai8(?=\W)").unwrap(),TokenType::DataType(DataType::I8)),
-                (Regex::new(r"\Ai16(?=\W)").unwrap(),TokenType::DataType(DataType::I16)),
-                (Regex::new(r"\Ai32(?=\W)").unwrap(),TokenType::DataType(DataType::I32)),
-                (Regex::new(r"\Ai64(?=\W)").unwrap(),TokenType::DataType(DataType::I64)),
-                (Regex::new(r"\Au8(?=\W)").unwrap(),TokenType::DataType(DataType::U8)),
-                (Regex::new(r"\Au16(?=\W)").unwrap(),TokenType::DataType(DataType::U16)),
-                (Regex::new(r"\Au32(?=\W)").unwrap(),TokenType::DataType(DataType::U32)),
-                (Regex::new(r"\Au64(?=\W)").unwrap(),TokenType::DataType(DataType::U64)),
-                (Regex::new(r"\Af32(?=\W)").unwrap(),TokenType::DataType(DataType::F32)),
-                (Regex::new(r"\Af64(?=\W)").unwrap(),TokenType::DataType(DataType::F64)),
-                (Regex::new(r"\Avoid(?=\W)").unwrap(),TokenType::DataType(DataType::Void)),
-                (Regex::new(r"\Achar(?=\W)").unwrap(),TokenType::DataType(DataType::Char)),
-                (Regex::new(r"\Astr(?=\W)").unwrap(),TokenType::DataType(DataType::Str(0))),
-                (Regex::new(r"\Abool(?=\W)").unwrap(),TokenType::DataType(DataType::Boolean)),
-                (Regex::new(r"\Atrue(?=\W)").unwrap(),TokenType::BooleanLiteral(true)),
-                (Regex::new(r"\Afalse(?=\W)").unwrap(),TokenType::BooleanLiteral(false)),
-                (Regex::new(r"\A[a-zA-Z]+[_0-9]*[_a-zA-Z0-9]*").unwrap(),TokenType::Identifier(String::new())),
-                (Regex::new(r"\A[0-9]+\.(?!\.)[0-9]*").unwrap(),TokenType::FloatLiteral(0.0)),
-                (Regex::new(r"\A[0-9]+").unwrap(),TokenType::IntLiteral(0)),
-                (Regex::new(r"\A\+").unwrap(),TokenType::AdditionOperator),
-                (Regex::new(r"\A-").unwrap(),TokenType::SubtractionOperator),
-                (Regex::new(r"\A\*").unwrap(),TokenType::MultiplicationOperator),
-                (Regex::new(r"\A/").unwrap(),TokenType::DivisionOperator),
-                (Regex::new(r"\A%").unwrap(),TokenType::ModuloOperator),
-                (Regex::new(r"\A==").unwrap(),TokenType::Equal),
-                (Regex::new(r"\A=").unwrap(),TokenType::AssignmentOperator),
-                (Regex::new(r"\A::").unwrap(),TokenType::ScopeResolution),
-                (Regex::new(r"\A:").unwrap(),TokenType::Colon),
-                (Regex::new(r"\A>=").unwrap(),TokenType::MoreEqual),
-                (Regex::new(r"\A>").unwrap(),TokenType::More),
-                (Regex::new(r"\A<=").unwrap(),TokenType::LessEqual),
-                (Regex::new(r"\A<").unwrap(),TokenType::Less),
-                (Regex::new(r"\A\&\&").unwrap(),TokenType::And),
-                (Regex::new(r"\A\|\|").unwrap(),TokenType::Or),
-                (Regex::new(r"\A!").unwrap(),TokenType::Not),
-                (Regex::new(r"\A'").unwrap(),TokenType::DoubleQuote),
-                (Regex::new(r#"\A""#).unwrap(),TokenType::DoubleQuote),
-                (Regex::new(r"\A\(").unwrap(),TokenType::LeftParen),
-                (Regex::new(r"\A\)").unwrap(),TokenType::RightParen),
-                (Regex::new(r"\A\{").unwrap(),TokenType::LeftBrace),
-                (Regex::new(r"\A}").unwrap(),TokenType::RightBrace),
-                (Regex::new(r"\A\[").unwrap(),TokenType::LeftBracket),
-                (Regex::new(r"\A\]").unwrap(),TokenType::RightBracket),
-                (Regex::new(r"\A,").unwrap(),TokenType::Comma),
-                (Regex::new(r"\A\.\.").unwrap(),TokenType::Range),
-                (Regex::new(r"\A\.").unwrap(),TokenType::Dot),
-                (Regex::new(r"\A;").unwrap(),TokenType::SemiColon),
+                rule(r"\Afunc",space_or_paren.clone(),TokenType::Keyword(KeyWords::FUNC)),
+                rule(r"\Aimport",space.clone(),TokenType::Keyword(KeyWords::IMPORT)),
+                rule(r"\Aas",non_word.clone(),TokenType::Keyword(KeyWords::AS)),
+                rule(r"\Aconst",space.clone(),TokenType::Keyword(KeyWords::CONST)),
+                rule(r"\Apub",space.clone(),TokenType::Keyword(KeyWords::PUB)),
+                rule(r"\Aextern",space.clone(),TokenType::Keyword(KeyWords::EXTERN)),
+                rule(r"\Atrait",space.clone(),TokenType::Keyword(KeyWords::TRAIT)),
+                rule(r"\Aimpl",space.clone(),TokenType::Keyword(KeyWords::IMPL)),
+                rule(r"\Amut",space.clone(),TokenType::Keyword(KeyWords::MUT)),
+                rule(r"\Astruct",space.clone(),TokenType::Keyword(KeyWords::STRUCT)),
+                rule(r"\Aenum",space.clone(),TokenType::Keyword(KeyWords::ENUM)),
+                rule(r"\Amatch",space_or_paren.clone(),TokenType::Keyword(KeyWords::MATCH)),
+                rule(r"\Areturn",space_paren_or_semicolon.clone(),TokenType::Keyword(KeyWords::RETURN)),
+                rule(r"\Alet",space_or_paren.clone(),TokenType::Keyword(KeyWords::LET)),
+                rule(r"\Awhile",space_or_paren.clone(),TokenType::Keyword(KeyWords::WHILE)),
+                rule(r"\Afor",space_or_paren.clone(),TokenType::Keyword(KeyWords::FOR)),
+                rule(r"\Aif",space_or_paren.clone(),TokenType::Keyword(KeyWords::IF)),
+                rule(r"\Aelse\s+if",space_or_paren.clone(),TokenType::Keyword(KeyWords::ELSEIF)),
+                rule(r"\Aelse",space_or_brace.clone(),TokenType::Keyword(KeyWords::ELSE)),
+                rule(r"\Ain",space_or_paren.clone(),TokenType::Keyword(KeyWords::IN)),
+                rule(r"\Aloop",space_or_brace.clone(),TokenType::Keyword(KeyWords::LOOP)),
+                rule(r"\Abreak",space_or_semicolon.clone(),TokenType::Keyword(KeyWords::BREAK)),
+                rule(r"\Acontinue",space_or_semicolon.clone(),TokenType::Keyword(KeyWords::CONTINUE)),
+                rule(r#"\A"(?:[^"\\\n]|\\.)*""#,Follow::Any,TokenType::StringLiteral(String::new())),
+                rule(r"\A'[_a-zA-Z][_a-zA-Z0-9]*",Follow::IsNot(Regex::new(r"\A[_a-zA-Z0-9']").unwrap()),TokenType::Label(String::new())),
+                rule(r"\A'[^'\n]+'",Follow::Any,TokenType::CharLiteral('a')),
+                rule(r"\Ai8",non_word.clone(),TokenType::DataType(DataType::I8)),
+                rule(r"\Ai16",non_word.clone(),TokenType::DataType(DataType::I16)),
+                rule(r"\Ai32",non_word.clone(),TokenType::DataType(DataType::I32)),
+                rule(r"\Ai64",non_word.clone(),TokenType::DataType(DataType::I64)),
+                rule(r"\Au8",non_word.clone(),TokenType::DataType(DataType::U8)),
+                rule(r"\Au16",non_word.clone(),TokenType::DataType(DataType::U16)),
+                rule(r"\Au32",non_word.clone(),TokenType::DataType(DataType::U32)),
+                rule(r"\Au64",non_word.clone(),TokenType::DataType(DataType::U64)),
+                rule(r"\Af32",non_word.clone(),TokenType::DataType(DataType::F32)),
+                rule(r"\Af64",non_word.clone(),TokenType::DataType(DataType::F64)),
+                rule(r"\Avoid",non_word.clone(),TokenType::DataType(DataType::Void)),
+                rule(r"\Achar",non_word.clone(),TokenType::DataType(DataType::Char)),
+                rule(r"\Astr",non_word.clone(),TokenType::DataType(DataType::Str(0))),
+                rule(r"\Abool",non_word.clone(),TokenType::DataType(DataType::Boolean)),
+                rule(r"\Atrue",non_word.clone(),TokenType::BooleanLiteral(true)),
+                rule(r"\Afalse",non_word.clone(),TokenType::BooleanLiteral(false)),
+                rule(r"\A[_a-zA-Z][_a-zA-Z0-9]*",Follow::Any,TokenType::Identifier(String::new())),
+                rule(r"\A[0-9]+\.[0-9]+",Follow::Any,TokenType::FloatLiteral(0.0)),
+                rule(r"\A[0-9]+\.",Follow::IsNot(Regex::new(r"\A\.").unwrap()),TokenType::FloatLiteral(0.0)),
+                rule(r"\A[0-9]+",Follow::Any,TokenType::IntLiteral(0)),
+                rule(r"\A\+\+",Follow::Any,TokenType::Increment),
+                // x-- is only a statement, when an operand follows 5--3 is 5 - -3
+                rule(r"\A--",Follow::IsNot(Regex::new(r#"\A[ \t\r\n]*[\w('"\[!~&*-]"#).unwrap()),TokenType::Decrement),
+                rule(r"\A\+=",Follow::Any,TokenType::AdditionAssign),
+                rule(r"\A-=",Follow::Any,TokenType::SubtractionAssign),
+                rule(r"\A\*=",Follow::Any,TokenType::MultiplicationAssign),
+                rule(r"\A/=",Follow::Any,TokenType::DivisionAssign),
+                rule(r"\A%=",Follow::Any,TokenType::ModuloAssign),
+                rule(r"\A\&=",Follow::Any,TokenType::BitwiseAndAssign),
+                rule(r"\A\|=",Follow::Any,TokenType::BitwiseOrAssign),
+                rule(r"\A\^=",Follow::Any,TokenType::BitwiseXorAssign),
+                rule(r"\A<<=",Follow::Any,TokenType::ShiftLeftAssign),
+                rule(r"\A>>=",Follow::Any,TokenType::ShiftRightAssign),
+                rule(r"\A\+",Follow::Any,TokenType::AdditionOperator),
+                rule(r"\A-",Follow::Any,TokenType::SubtractionOperator),
+                rule(r"\A\*",Follow::Any,TokenType::MultiplicationOperator),
+                rule(r"\A/",Follow::Any,TokenType::DivisionOperator),
+                rule(r"\A%",Follow::Any,TokenType::ModuloOperator),
+                rule(r"\A=>",Follow::Any,TokenType::FatArrow),
+                rule(r"\A==",Follow::Any,TokenType::Equal),
+                rule(r"\A=",Follow::Any,TokenType::AssignmentOperator),
+                rule(r"\A::",Follow::Any,TokenType::ScopeResolution),
+                rule(r"\A:",Follow::Any,TokenType::Colon),
+                rule(r"\A!=",Follow::Any,TokenType::NotEqual),
+                rule(r"\A<<",Follow::Any,TokenType::ShiftLeft),
+                rule(r"\A>>",Follow::Any,TokenType::ShiftRight),
+                rule(r"\A>=",Follow::Any,TokenType::MoreEqual),
+                rule(r"\A>",Follow::Any,TokenType::More),
+                rule(r"\A<=",Follow::Any,TokenType::LessEqual),
+                rule(r"\A<",Follow::Any,TokenType::Less),
+                rule(r"\A\&\&",Follow::Any,TokenType::And),
+                rule(r"\A\|\|",Follow::Any,TokenType::Or),
+                rule(r"\A!",Follow::Any,TokenType::Not),
+                rule(r"\A\&",Follow::Any,TokenType::BitwiseAnd),
+                rule(r"\A\|",Follow::Any,TokenType::BitwiseOr),
+                rule(r"\A\^",Follow::Any,TokenType::BitwiseXor),
+                rule(r"\A~",Follow::Any,TokenType::BitwiseNot),
+                rule(r"\A'",Follow::Any,TokenType::SingleQuote),
+                rule(r#"\A""#,Follow::Any,TokenType::DoubleQuote),
+                rule(r"\A\(",Follow::Any,TokenType::LeftParen),
+                rule(r"\A\)",Follow::Any,TokenType::RightParen),
+                rule(r"\A\{",Follow::Any,TokenType::LeftBrace),
+                rule(r"\A}",Follow::Any,TokenType::RightBrace),
+                rule(r"\A\[",Follow::Any,TokenType::LeftBracket),
+                rule(r"\A\]",Follow::Any,TokenType::RightBracket),
+                rule(r"\A,",Follow::Any,TokenType::Comma),
+                rule(r"\A\.\.=",Follow::Any,TokenType::RangeInclusive),
+                rule(r"\A\.\.",Follow::Any,TokenType::Range),
+                rule(r"\A\.",Follow::Any,TokenType::Dot),
+                rule(r"\A\?",Follow::Any,TokenType::QuestionMark),
+                rule(r"\A;",Follow::Any,TokenType::SemiColon),
             ]
         }
     }
@@ -293,47 +501,96 @@ impl<'a,'b> Tokenizer<'a,'b>{
         return self.cursor>=self.source.len() as u32;
     }
 
-    pub fn get_next_token(&mut self) -> Token {
+    // when enabled, whitespace, comments and skipped characters are kept on the
+    // following token so the original source can be rebuilt from the token stream
+    pub fn emit_trivia(&mut self,enabled:bool) -> &mut Self {
+        self.emit_trivia = enabled;
+        self
+    }
 
-        if self.is_finished() {
-            return Token {token:TokenType::EOF,length:0,pos:self.pos};
+    fn push_trivia(&mut self,kind:TriviaKind,start:u32,pos:(u32,u32)){
+        if self.emit_trivia {
+            self.pending_trivia.push(
+                Trivia {
+                    kind,
+                    text:self.source[(start as usize)..(self.cursor as usize)].to_string(),
+                    pos
+                }
+            );
         }
-        let get_current_char = self.source.chars().nth(self.cursor as usize);
-        let mut current_char = match get_current_char{
-            Some(ch) => {
-                ch
-            },
-            None =>{
-                panic!("cursor somehow exceed string length");
-            }
-        };
-        while current_char.is_whitespace() {
-            if current_char == '\n'{
-                self.pos.0 += 1;
-                self.pos.1 = 1;
+    }
+
+    fn advance_char(&mut self) -> Option<char> {
+        let ch = self.source.get((self.cursor as usize)..)?.chars().next()?;
+        if ch == '\n'{
+            self.pos.0 += 1;
+            self.pos.1 = 0;
+        }
+        self.cursor += ch.len_utf8() as u32;
+        self.pos.1 +=1;
+        Some(ch)
+    }
+
+    fn skip_trivia(&mut self){
+        while !self.is_finished() {
+            let rest = self.source.get((self.cursor as usize)..).unwrap();
+            let start = self.cursor;
+            let pos = self.pos;
+            if rest.starts_with(char::is_whitespace) {
+                while self.source.get((self.cursor as usize)..).unwrap().starts_with(char::is_whitespace) {
+                    self.advance_char();
+                }
+                self.push_trivia(TriviaKind::Whitespace, start, pos);
             }
-            self.cursor += 1;
-            self.pos.1 +=1;
-            if self.is_finished() {
-                return Token {token:TokenType::EOF,length:0,pos:self.pos};
+            else if rest.starts_with("//") {
+                while !self.is_finished() && !self.source.get((self.cursor as usize)..).unwrap().starts_with('\n') {
+                    self.advance_char();
+                }
+                self.push_trivia(TriviaKind::LineComment, start, pos);
             }
-            let get_current_char = self.source.chars().nth(self.cursor as usize);
-            current_char = match get_current_char{
-                Some(ch) => {
-                    ch
-                },
-                None =>{
-                    panic!("cursor somehow exceeded string length.");
+            else if rest.starts_with("/*") {
+                self.advance_char();
+                self.advance_char();
+                while !self.source.get((self.cursor as usize)..).unwrap().starts_with("*/") {
+                    if self.advance_char().is_none() {
+                        self.error_pipe.raise_error(crate::ErrorType::LexicalError, "unclosed block comment", pos, 2);
+                        break;
+                    }
                 }
-            };
+                self.advance_char();
+                self.advance_char();
+                self.push_trivia(TriviaKind::BlockComment, start, pos);
+            }
+            else {
+                break;
+            }
+        }
+    }
+
+    fn make_token(&mut self,token:TokenType,length:u32,pos:(u32,u32),start:u32) -> Token {
+        Token {
+            token,
+            length,
+            pos,
+            text:self.source[(start as usize)..(self.cursor as usize)].to_string(),
+            leading_trivia:std::mem::take(&mut self.pending_trivia)
+        }
+    }
+
+    pub fn get_next_token(&mut self) -> Token {
+        self.skip_trivia();
+        if self.is_finished() {
+            let (pos,cursor) = (self.pos,self.cursor);
+            return self.make_token(TokenType::EOF, 0, pos, cursor);
         }
         
         let current_sli = self.source.get((self.cursor as usize)..).unwrap();
-        for (rule , token) in &self.rules{
-            let match_result = rule.find(current_sli);
-            
+        for rule_index in 0..self.rules.len(){
+            let (rule , follow, token) = &self.rules[rule_index];
+            let match_result = rule.find(current_sli).filter(|cap| follow.allows(&current_sli[cap.end()..]));
+
             match match_result {
-                Ok(Some(cap))=>{
+                Some(cap)=>{
                     let pos = self.pos;
                     let length = cap.as_str().len() as u32;
                     let current_t = match token{
@@ -355,8 +612,11 @@ impl<'a,'b> Tokenizer<'a,'b>{
                         TokenType::StringLiteral(_) => {
                             TokenType::StringLiteral(cap.as_str()[1..cap.as_str().len()-1].to_string())
                         },
+                        TokenType::Label(_) => {
+                            TokenType::Label(cap.as_str()[1..].to_string())
+                        },
                         TokenType::CharLiteral(_) => {
-                            if cap.as_str().len() > 3 {
+                            if cap.as_str().chars().count() > 3 {
                                 self.error_pipe.raise_error(crate::ErrorType::LexicalError, "char literal contain multiple character, consider using str instead", self.pos, 1);
                             }
                             TokenType::CharLiteral(cap.as_str().chars().nth(1).unwrap())
@@ -379,15 +639,35 @@ impl<'a,'b> Tokenizer<'a,'b>{
                         TokenType::AssignmentOperator => {
                             TokenType::AssignmentOperator
                         },
+                        TokenType::AdditionAssign
+                        | TokenType::SubtractionAssign
+                        | TokenType::MultiplicationAssign
+                        | TokenType::DivisionAssign
+                        | TokenType::ModuloAssign
+                        | TokenType::BitwiseAndAssign
+                        | TokenType::BitwiseOrAssign
+                        | TokenType::BitwiseXorAssign
+                        | TokenType::ShiftLeftAssign
+                        | TokenType::ShiftRightAssign
+                        | TokenType::Increment
+                        | TokenType::Decrement => {
+                            token.clone()
+                        },
                         TokenType::ScopeResolution => {
                             TokenType::ScopeResolution
                         },
+                        TokenType::FatArrow => {
+                            TokenType::FatArrow
+                        },
                         TokenType::Colon => {
                             TokenType::Colon
                         },
                         TokenType::Equal => {
                             TokenType::Equal
                         },
+                        TokenType::NotEqual => {
+                            TokenType::NotEqual
+                        },
                         TokenType::Less => {
                             TokenType::Less
                         },
@@ -418,9 +698,15 @@ impl<'a,'b> Tokenizer<'a,'b>{
                         TokenType::Range => {
                             TokenType::Range
                         },
+                        TokenType::RangeInclusive => {
+                            TokenType::RangeInclusive
+                        },
                         TokenType::Dot => {
                             TokenType::Dot
                         },
+                        TokenType::QuestionMark => {
+                            TokenType::QuestionMark
+                        },
                         TokenType::SemiColon => {
                             TokenType::SemiColon
                         },
@@ -436,17 +722,41 @@ impl<'a,'b> Tokenizer<'a,'b>{
                         TokenType::Not => {
                             TokenType::Not
                         },
+                        TokenType::BitwiseAnd => {
+                            TokenType::BitwiseAnd
+                        },
+                        TokenType::BitwiseOr => {
+                            TokenType::BitwiseOr
+                        },
+                        TokenType::BitwiseXor => {
+                            TokenType::BitwiseXor
+                        },
+                        TokenType::BitwiseNot => {
+                            TokenType::BitwiseNot
+                        },
+                        TokenType::ShiftLeft => {
+                            TokenType::ShiftLeft
+                        },
+                        TokenType::ShiftRight => {
+                            TokenType::ShiftRight
+                        },
                         TokenType::SingleQuote => {
                             self.error_pipe.raise_error(crate::ErrorType::LexicalError, "unclosed single quote", self.pos, 1);
+                            let start = self.cursor;
                             self.cursor = self.source.len() as u32;
                             self.pos.1 += 1;
-                            return Token {token:TokenType::EOF,length:0,pos};
+                            self.push_trivia(TriviaKind::Skipped, start, pos);
+                            let cursor = self.cursor;
+                            return self.make_token(TokenType::EOF, 0, pos, cursor);
                         },
                         TokenType::DoubleQuote => {
                             self.error_pipe.raise_error(crate::ErrorType::LexicalError, "unclosed double quote", self.pos, 1);
+                            let start = self.cursor;
                             self.cursor = self.source.len() as u32;
                             self.pos.1 += 1;
-                            return Token {token:TokenType::EOF,length:0,pos};
+                            self.push_trivia(TriviaKind::Skipped, start, pos);
+                            let cursor = self.cursor;
+                            return self.make_token(TokenType::EOF, 0, pos, cursor);
                         },
                         TokenType::LeftBracket => {
                             TokenType::LeftBracket
@@ -458,22 +768,21 @@ impl<'a,'b> Tokenizer<'a,'b>{
                             TokenType::BooleanLiteral(*b)
                         },
                     };
+                    let start = self.cursor;
                     self.cursor += length;
                     self.pos.1 += length;
                     //println!("{:#?}",current_t);
-                    return Token {token:current_t,length,pos};
+                    return self.make_token(current_t, length, pos, start);
                 },
-                Ok(None)=>{
+                None=>{
                     continue;
-                },
-                Err(err)=>{
-                    panic!("{:?}",err);
                 }
             }
         }
         self.error_pipe.raise_error(crate::ErrorType::LexicalError, "unidentified token", self.pos, 1);
-        self.cursor += 1;
-        self.pos.1 += 1;
+        let (start,pos) = (self.cursor,self.pos);
+        self.advance_char();
+        self.push_trivia(TriviaKind::Skipped, start, pos);
         self.get_next_token()
     }
 }
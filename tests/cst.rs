@@ -0,0 +1,112 @@
+use std::{fs, path::{Path, PathBuf}, process::Command};
+
+// every .ark file of source/, tests/malformed/ and tests/cst/ is parsed with --cst and the
+// text of the printed tree, trivia included, has to be the exact text of the file
+fn ark_files(dir:&Path,files:&mut Vec<PathBuf>) {
+    for entry in fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+            ark_files(&path, files);
+        }
+        else if path.extension().is_some_and(|ext| ext == "ark") {
+            files.push(path);
+        }
+    }
+}
+
+// undoes the escapes of a string printed with {:?}
+fn unescape(text:&str) -> String {
+    let mut out = String::new();
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => out.push('\n'),
+            Some('r') => out.push('\r'),
+            Some('t') => out.push('\t'),
+            Some('0') => out.push('\0'),
+            Some('u') => {
+                let code = chars.by_ref().skip(1).take_while(|c| *c != '}').collect::<String>();
+                out.push(char::from_u32(u32::from_str_radix(&code, 16).unwrap()).unwrap());
+            },
+            Some(other) => out.push(other),
+            None => ()
+        }
+    }
+    out
+}
+
+// a token or trivia line of the tree ends with its text as a quoted string, inside it
+// every quote is escaped
+fn leaf_text(line:&str) -> Option<String> {
+    let body = line.strip_suffix('"')?;
+    let bytes = body.as_bytes();
+    let mut open = body.len();
+    while open > 0 {
+        open -= 1;
+        let escapes = bytes[..open].iter().rev().take_while(|b| **b == b'\\').count();
+        if bytes[open] == b'"' && escapes % 2 == 0 {
+            return Some(unescape(&body[open + 1..]));
+        }
+    }
+    None
+}
+
+// the printed syntax tree of a file, it ends where the ast is printed
+fn syntax_tree(source:&Path) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_ark_compiler"))
+        .arg("--cst")
+        .arg(source)
+        .env("NO_COLOR", "1")
+        .output()
+        .unwrap();
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        !stderr.contains("panicked"),
+        "compiler panicked on {}:\n{}",
+        source.display(),
+        stderr
+    );
+    String::from_utf8_lossy(&output.stdout).lines()
+        .take_while(|line| !line.starts_with("Body {"))
+        .map(|line| format!("{}\n",line))
+        .collect()
+}
+
+fn indent(line:&str) -> usize {
+    line.len() - line.trim_start().len()
+}
+
+#[test]
+fn syntax_tree_reproduces_the_source() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let mut sources = vec![];
+    for dir in ["source","tests/malformed","tests/cst"] {
+        ark_files(&root.join(dir), &mut sources);
+    }
+    sources.sort();
+    assert!(sources.iter().any(|source| fs::read_to_string(source).unwrap().contains("\r\n")));
+    for source in sources {
+        let text = syntax_tree(&source).lines()
+            .filter_map(leaf_text)
+            .collect::<String>();
+        assert_eq!(text, fs::read_to_string(&source).unwrap(), "tree of {} lost text", source.display());
+    }
+}
+
+// tokens skipped while recovering from a syntax error stay in the tree under an Error node
+#[test]
+fn skipped_tokens_are_wrapped_in_error_nodes() {
+    let source = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/malformed/stray_tokens.ark");
+    let tree = syntax_tree(&source);
+    let lines = tree.lines().collect::<Vec<&str>>();
+    let error = lines.iter().position(|line| line.trim() == "Error").expect("no Error node in the tree");
+    let skipped = lines[error + 1..].iter()
+        .take_while(|line| indent(line) > indent(lines[error]))
+        .filter_map(|line| leaf_text(line))
+        .collect::<String>();
+    assert_eq!(skipped, "\n    ) a = 1");
+}
@@ -0,0 +1,48 @@
+use std::{fs, path::Path, process::Command};
+
+// every tests/ir/*.ark file has to compile without errors and the generated
+// intermediate representation has to match the .ir file next to it
+fn intermediate_representation(output:&str) -> Vec<String> {
+    // the syntax tree is printed first and ends with a closing brace at column 0,
+    // errors and warnings are printed after the code
+    output.lines()
+        .skip_while(|line| *line != "}")
+        .skip(1)
+        .take_while(|line| !(line.contains(" Error: ") || line.starts_with("Warning: ")))
+        .filter(|line| !line.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+#[test]
+fn generated_code_matches() {
+    let corpus = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("ir");
+    let mut sources = fs::read_dir(&corpus)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "ark"))
+        .collect::<Vec<_>>();
+    sources.sort();
+    assert!(!sources.is_empty());
+    for source in sources {
+        let output = Command::new(env!("CARGO_BIN_EXE_ark_compiler"))
+            .arg(&source)
+            .env("NO_COLOR", "1")
+            .output()
+            .unwrap();
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(
+            !stdout.lines().any(|line| line.contains(" Error: ")),
+            "{} failed to compile:\n{}",
+            source.display(),
+            stdout
+        );
+        let expected = fs::read_to_string(source.with_extension("ir")).unwrap();
+        assert_eq!(
+            intermediate_representation(&stdout),
+            expected.lines().filter(|l| !l.is_empty()).map(String::from).collect::<Vec<String>>(),
+            "unexpected code for {}",
+            source.display()
+        );
+    }
+}
@@ -0,0 +1,56 @@
+use std::{fs, path::Path, process::Command};
+
+// every tests/malformed/*.ark file is compiled and the reported errors and warnings,
+// as "line:col Error Type: message", have to match the .expected file next to it
+fn reported_errors(output:&str) -> Vec<String> {
+    let lines = output.lines().collect::<Vec<&str>>();
+    let mut errors = vec![];
+    for (i,line) in lines.iter().enumerate() {
+        if line.starts_with(' ') || !(line.contains(" Error: ") || line.starts_with("Warning: ")) {
+            continue;
+        }
+        let location = match lines.get(i + 1) {
+            Some(l) => l.trim(),
+            None => continue
+        };
+        let mut parts = location.rsplitn(3, ':');
+        let col = parts.next().unwrap_or("");
+        let row = parts.next().unwrap_or("");
+        errors.push(format!("{}:{} {}",row,col,line));
+    }
+    errors
+}
+
+#[test]
+fn malformed_sources_recover() {
+    let corpus = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("malformed");
+    let mut sources = fs::read_dir(&corpus)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "ark"))
+        .collect::<Vec<_>>();
+    sources.sort();
+    assert!(!sources.is_empty());
+    for source in sources {
+        let output = Command::new(env!("CARGO_BIN_EXE_ark_compiler"))
+            .arg(&source)
+            .env("NO_COLOR", "1")
+            .output()
+            .unwrap();
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(
+            !stderr.contains("panicked"),
+            "compiler panicked on {}:\n{}",
+            source.display(),
+            stderr
+        );
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let expected = fs::read_to_string(source.with_extension("expected")).unwrap();
+        assert_eq!(
+            reported_errors(&stdout),
+            expected.lines().filter(|l| !l.is_empty()).map(String::from).collect::<Vec<String>>(),
+            "unexpected errors for {}",
+            source.display()
+        );
+    }
+}